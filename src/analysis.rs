@@ -0,0 +1,94 @@
+//! Steganalysis of packages, which estimates how detectable concealed messages are.
+//!
+//! The functions in this module apply well-known statistical attacks to the bytes of a
+//! suspected package, so that patterns and options of a [`Carrier`][crate::binary::Carrier]
+//! can be compared by how well they withstand the attacks, e.g. in regression tests.
+//!
+//! Samples of images and audio are best analyzed channel by channel, as the channels of
+//! a file usually have different statistics. The [`formats`][crate::formats] adapters
+//! provide the samples of each channel via `channel_samples`.
+//!
+//! ## Chi-square Attack
+//!
+//! Replacing the least significant bits of samples with uniformly distributed bits evens
+//! out the frequencies of each pair of values that differ only in the least significant bit.
+//! The attack by Westfeld and Pfitzmann, implemented by [`chi_square`], tests how close the
+//! frequencies are to being even, and returns the probability of embedding for increasingly
+//! long prefixes of the samples, which reveals where a message concealed in order ends.
+//!
+//! ## RS Analysis and Sample Pair Analysis
+//!
+//! Consecutive samples of natural images are correlated, and replacing their least
+//! significant bits disturbs the correlation in a way that depends on how many bits were
+//! replaced. [`rs`] and [`sample_pairs`] measure the disturbance and estimate the proportion
+//! of the samples that carry message bits, which detects messages scattered over all the
+//! samples by a sparse pattern as well, and tells how the density of the pattern affects
+//! detectability.
+//!
+//! ## Examples
+//!
+//! Telling a cover apart from a package whose samples are all used:
+//!
+//! ```
+//! use asbs::{analysis, binary, Conceal};
+//!
+//! // A cover of even values only, which is as far from evenly distributed pairs as it gets
+//! let cover: Vec<u8> = (0..4096u32).map(|i| (i * 37 % 101 * 2) as u8).collect();
+//! let payload: Vec<u8> = (0..512u32).map(|i| (i * 7919 % 251) as u8).collect();
+//!
+//! let mut package = Vec::new();
+//! binary::Carrier::new(|_| Some(0b1), &mut package)
+//!     .conceal(payload.as_slice(), cover.as_slice())?;
+//!
+//! let cover_curve = analysis::chi_square(&cover, 1024);
+//! let package_curve = analysis::chi_square(&package, 1024);
+//!
+//! assert!(cover_curve.iter().all(|point| point.probability < 0.01));
+//! assert!(package_curve.iter().all(|point| point.probability > 0.5));
+//! # Ok::<(), asbs::Error>(())
+//! ```
+//!
+//! Estimating the proportion of samples used by a sparse pattern:
+//!
+//! ```
+//! use asbs::{analysis, binary, Conceal};
+//!
+//! // A smooth gradient with some noise
+//! let cover: Vec<u8> = (0..65536u32)
+//!     .map(|i| (i / 512 + i * 7919 % 13 / 4) as u8)
+//!     .collect();
+//! let payload: Vec<u8> = (0..2048u32).map(|i| (i * 7919 % 251) as u8).collect();
+//!
+//! assert!(analysis::rs(&cover).unwrap().ratio.abs() < 0.1);
+//! assert!(analysis::sample_pairs(&cover).unwrap().ratio.abs() < 0.1);
+//!
+//! // Every fourth sample carries a bit of the payload
+//! let mut package = Vec::new();
+//! binary::Carrier::new(|i| Some(u8::from(i % 4 == 1)), &mut package)
+//!     .conceal(payload.as_slice(), cover.as_slice())?;
+//!
+//! let ratio = analysis::sample_pairs(&package).unwrap().ratio;
+//! assert!((ratio - 0.25).abs() < 0.1);
+//! # Ok::<(), asbs::Error>(())
+//! ```
+
+mod chi_square;
+mod rs;
+mod spa;
+
+pub use chi_square::{chi_square, ChiSquare};
+pub use rs::{rs, Rs};
+pub use spa::{sample_pairs, SamplePairs};
+
+/// Returns the roots of the quadratic polynomial with the supplied coefficients.
+///
+/// Complex roots are replaced with their real part, as estimation errors of clean samples
+/// can make the discriminant slightly negative.
+fn real_roots(a: f64, b: f64, c: f64) -> [f64; 2] {
+    let discriminant = (b * b - 4.0 * a * c).max(0.0);
+
+    [
+        (-b + discriminant.sqrt()) / (2.0 * a),
+        (-b - discriminant.sqrt()) / (2.0 * a),
+    ]
+}
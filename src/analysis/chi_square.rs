@@ -0,0 +1,190 @@
+/// The result of the chi-square attack on a prefix of the samples.
+///
+/// See [`chi_square`] for details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct ChiSquare {
+    /// The number of samples from the start that were examined.
+    pub len: usize,
+
+    /// The chi-square statistic of the frequencies of the values with even least significant
+    /// bits with respect to the means of their pairs.
+    pub statistic: f64,
+
+    /// The number of degrees of freedom of the statistic.
+    pub degrees_of_freedom: usize,
+
+    /// The probability that the least significant bits of the samples were replaced, which is
+    /// close to one if they were and close to zero otherwise.
+    pub probability: f64,
+}
+
+/// Performs the chi-square attack by Westfeld and Pfitzmann on increasingly long prefixes
+/// of the samples, returning the probability of embedding for each of them.
+///
+/// The prefixes grow by the supplied step, and the last one consists of all the samples.
+/// Pairs of values that occur too rarely to be tested reliably are ignored, so that the
+/// probability is zero if the prefix is too short to tell anything.
+///
+/// The attack detects sequential replacement of the least significant bits well, but not
+/// messages that use only a small part of the samples or are scattered over all of them,
+/// nor [LSB matching][crate::binary::Carrier::lsb_matching].
+///
+/// # Panics
+///
+/// Panics if the step is zero.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::analysis;
+///
+/// // Pairs of values occur equally often, which is what embedding causes
+/// let samples: Vec<u8> = (0..=255).cycle().take(8192).collect();
+///
+/// let curve = analysis::chi_square(&samples, 1024);
+///
+/// assert_eq!(curve.len(), 8);
+/// assert_eq!(curve[7].len, 8192);
+/// assert!(curve[7].probability > 0.99);
+/// ```
+#[must_use]
+pub fn chi_square(samples: &[u8], step: usize) -> Vec<ChiSquare> {
+    assert!(step > 0, "step must be positive");
+
+    let mut histogram = [0u64; 256];
+    let mut curve = Vec::with_capacity(samples.len().div_ceil(step));
+
+    for (index, chunk) in samples.chunks(step).enumerate() {
+        for &sample in chunk {
+            histogram[usize::from(sample)] += 1;
+        }
+
+        curve.push(evaluate(&histogram, index * step + chunk.len()));
+    }
+
+    curve
+}
+
+fn evaluate(histogram: &[u64; 256], len: usize) -> ChiSquare {
+    let mut statistic = 0.0;
+    let mut categories = 0usize;
+
+    for pair in histogram.chunks_exact(2) {
+        let expected = (pair[0] + pair[1]) as f64 / 2.0;
+
+        // The chi-square distribution is a poor approximation for small frequencies.
+        if expected <= 4.0 {
+            continue;
+        }
+
+        statistic += (pair[0] as f64 - expected).powi(2) / expected;
+        categories += 1;
+    }
+
+    let degrees_of_freedom = categories.saturating_sub(1);
+    let probability = match degrees_of_freedom {
+        0 => 0.0,
+        _ => upper_gamma(degrees_of_freedom as f64 / 2.0, statistic / 2.0),
+    };
+
+    ChiSquare {
+        len,
+        statistic,
+        degrees_of_freedom,
+        probability,
+    }
+}
+
+const EPSILON: f64 = 1e-15;
+
+/// Returns the regularized upper incomplete gamma function Q(a, x), which is the probability
+/// of a chi-square statistic with `2a` degrees of freedom being at least `2x`.
+fn upper_gamma(a: f64, x: f64) -> f64 {
+    if x <= 0.0 {
+        return 1.0;
+    }
+
+    let scale = (a * x.ln() - x - ln_gamma(a)).exp();
+
+    if x < a + 1.0 {
+        // The series of the lower function converges quickly for small x.
+        let mut term = 1.0 / a;
+        let mut sum = term;
+
+        for n in 1..1000 {
+            term *= x / (a + f64::from(n));
+            sum += term;
+
+            if term.abs() < sum.abs() * EPSILON {
+                break;
+            }
+        }
+
+        return (1.0 - sum * scale).clamp(0.0, 1.0);
+    }
+
+    // The continued fraction of the upper function converges quickly for large x, and is
+    // evaluated by the modified Lentz's method.
+    let tiny = f64::MIN_POSITIVE / EPSILON;
+
+    let mut b = x + 1.0 - a;
+    let mut c = 1.0 / tiny;
+    let mut d = 1.0 / b;
+    let mut fraction = d;
+
+    for n in 1..1000 {
+        let n = f64::from(n);
+        let an = -n * (n - a);
+        b += 2.0;
+
+        d = an * d + b;
+        if d.abs() < tiny {
+            d = tiny;
+        }
+
+        c = b + an / c;
+        if c.abs() < tiny {
+            c = tiny;
+        }
+
+        d = 1.0 / d;
+        let delta = d * c;
+        fraction *= delta;
+
+        if (delta - 1.0).abs() < EPSILON {
+            break;
+        }
+    }
+
+    (fraction * scale).clamp(0.0, 1.0)
+}
+
+/// Returns the natural logarithm of the gamma function for positive arguments by the
+/// Lanczos approximation.
+fn ln_gamma(x: f64) -> f64 {
+    const G: f64 = 7.0;
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    let x = x - 1.0;
+    let sum = COEFFICIENTS[1..]
+        .iter()
+        .zip(1..)
+        .fold(COEFFICIENTS[0], |sum, (&coefficient, i)| {
+            sum + coefficient / (x + f64::from(i))
+        });
+
+    let t = x + G + 0.5;
+
+    0.5 * (2.0 * std::f64::consts::PI).ln() + (x + 0.5) * t.ln() - t + sum.ln()
+}
@@ -0,0 +1,126 @@
+/// The result of RS analysis of the samples.
+///
+/// See [`rs`] for details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct Rs {
+    /// The proportion of regular groups under the mask that flips the least significant bits.
+    pub regular: f64,
+
+    /// The proportion of singular groups under the mask that flips the least significant bits.
+    pub singular: f64,
+
+    /// The proportion of regular groups under the mask that shifts the values by one in the
+    /// opposite direction.
+    pub shifted_regular: f64,
+
+    /// The proportion of singular groups under the mask that shifts the values by one in the
+    /// opposite direction.
+    pub shifted_singular: f64,
+
+    /// The estimated proportion of the samples whose least significant bits were replaced
+    /// with message bits.
+    pub ratio: f64,
+}
+
+/// The mask applied to each group of samples.
+const MASK: [bool; 4] = [false, true, true, false];
+
+/// Performs RS analysis by Fridrich, Goljan, and Du, estimating the proportion of the samples
+/// whose least significant bits were replaced with message bits.
+///
+/// The samples are split into groups of four consecutive samples, which are classified as
+/// regular or singular depending on whether flipping the least significant bits of some of
+/// them makes the group more or less noisy. Replacing the least significant bits changes the
+/// proportions of the groups in a predictable way, which allows estimating how many bits
+/// were replaced. The estimate is most accurate for natural images, whose consecutive
+/// samples are correlated, and may be slightly negative or exceed one. The estimate is
+/// unreliable if nearly all the samples were used, in which case [`sample_pairs`] fares
+/// better.
+///
+/// [`sample_pairs`]: crate::analysis::sample_pairs
+///
+/// [`None`] is returned if there are too few samples, or the proportions of the groups do
+/// not allow an estimate.
+///
+/// See the [module documentation](crate::analysis#examples) for an example.
+#[must_use]
+pub fn rs(samples: &[u8]) -> Option<Rs> {
+    let [regular, singular, shifted_regular, shifted_singular] = proportions(samples, 0)?;
+    let [flipped_regular, flipped_singular, flipped_shifted_regular, flipped_shifted_singular] =
+        proportions(samples, 1)?;
+
+    let d0 = regular - singular;
+    let d1 = flipped_regular - flipped_singular;
+    let shifted_d0 = shifted_regular - shifted_singular;
+    let shifted_d1 = flipped_shifted_regular - flipped_shifted_singular;
+
+    let a = 2.0 * (d1 + d0);
+    let b = shifted_d0 - shifted_d1 - d1 - 3.0 * d0;
+    let c = d0 - shifted_d0;
+
+    let z = if a.abs() < f64::EPSILON {
+        -c / b
+    } else {
+        let roots = super::real_roots(a, b, c);
+        if roots[0].abs() < roots[1].abs() {
+            roots[0]
+        } else {
+            roots[1]
+        }
+    };
+
+    let ratio = z / (z - 0.5);
+
+    ratio.is_finite().then_some(Rs {
+        regular,
+        singular,
+        shifted_regular,
+        shifted_singular,
+        ratio,
+    })
+}
+
+/// Returns the proportions of regular and singular groups under the mask and the shifted
+/// mask, after flipping the supplied bits of all the samples.
+fn proportions(samples: &[u8], flip: u8) -> Option<[f64; 4]> {
+    let groups = samples.chunks_exact(MASK.len());
+    let len = groups.len();
+
+    if len == 0 {
+        return None;
+    }
+
+    let mut counts = [0usize; 4];
+
+    for group in groups {
+        let group: [i32; 4] = std::array::from_fn(|index| i32::from(group[index] ^ flip));
+        let original = variation(group);
+
+        let flipped = variation(masked(group, |value| value ^ 1));
+        let shifted = variation(masked(group, |value| ((value + 1) ^ 1) - 1));
+
+        counts[0] += usize::from(flipped > original);
+        counts[1] += usize::from(flipped < original);
+        counts[2] += usize::from(shifted > original);
+        counts[3] += usize::from(shifted < original);
+    }
+
+    Some(counts.map(|count| count as f64 / len as f64))
+}
+
+/// Returns the total variation of the group, i.e. how noisy it is.
+fn variation(group: [i32; 4]) -> i32 {
+    group.windows(2).map(|pair| (pair[1] - pair[0]).abs()).sum()
+}
+
+/// Applies the flipping function to the samples of the group selected by the mask.
+fn masked(mut group: [i32; 4], flip: impl Fn(i32) -> i32) -> [i32; 4] {
+    for (value, &is_masked) in group.iter_mut().zip(&MASK) {
+        if is_masked {
+            *value = flip(*value);
+        }
+    }
+
+    group
+}
@@ -0,0 +1,63 @@
+use std::cmp::Ordering;
+
+/// The result of Sample Pair Analysis of the samples.
+///
+/// See [`sample_pairs`] for details.
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct SamplePairs {
+    /// The number of pairs of consecutive samples that were examined.
+    pub pairs: usize,
+
+    /// The estimated proportion of the samples whose least significant bits were replaced
+    /// with message bits.
+    pub ratio: f64,
+}
+
+/// Performs Sample Pair Analysis by Dumitrescu, Wu, and Wang, estimating the proportion of
+/// the samples whose least significant bits were replaced with message bits.
+///
+/// Pairs of consecutive samples are classified by whether the parity of the second sample
+/// of each pair agrees with the order of the samples. The two classes are about equally
+/// large in natural images, and replacing the least significant bits moves pairs between
+/// them in a way that depends on the number of replaced bits, which allows estimating it.
+/// The estimate may be slightly negative or exceed one.
+///
+/// [`None`] is returned if there are too few samples, or the classes of pairs do not allow
+/// an estimate.
+///
+/// See the [module documentation](crate::analysis#examples) for an example.
+#[must_use]
+pub fn sample_pairs(samples: &[u8]) -> Option<SamplePairs> {
+    let pairs = samples.len().checked_sub(1)?;
+
+    // Pairs whose second sample is even and larger or odd and smaller, pairs whose second
+    // sample is even and smaller or odd and larger, and pairs that differ only in the least
+    // significant bit, if at all.
+    let (mut x, mut y, mut z) = (0u64, 0u64, 0u64);
+
+    for pair in samples.windows(2) {
+        let (r, s) = (pair[0], pair[1]);
+
+        match (s % 2 == 0, r.cmp(&s)) {
+            (true, Ordering::Less) | (false, Ordering::Greater) => x += 1,
+            (true, Ordering::Greater) | (false, Ordering::Less) => y += 1,
+            _ => {}
+        }
+
+        z += u64::from(r / 2 == s / 2);
+    }
+
+    if z == 0 {
+        return None;
+    }
+
+    let a = 2.0 * z as f64;
+    let b = 2.0 * (2.0 * x as f64 - pairs as f64);
+    let c = y as f64 - x as f64;
+
+    let [root, other_root] = super::real_roots(a, b, c);
+    let ratio = 2.0 * root.min(other_root);
+
+    ratio.is_finite().then_some(SamplePairs { pairs, ratio })
+}
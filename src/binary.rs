@@ -13,13 +13,80 @@
 //! is used for all messages.
 //!
 //! It is up to the *sender* to ensure that the message in its concealed form can be stored
-//! fully in the cover data.
+//! fully in the cover data. The [`capacity`] function, [`Carrier::capacity_for`], and
+//! [`Carrier::dry_run`] tell how much data fits.
 //!
 //! It is required for the *receiver* of the message to know the bit pattern and, optionally,
 //! the length of the message, although the latter may be included in the former by
 //! short-circuiting the function by identifying the last package byte index which contains
 //! the message.
 //!
+//! Patterns are represented by the [`Pattern`] trait, which is implemented for closures.
+//! The [`pattern::KeyedPattern`] derives a pseudo-random pattern from a passphrase, so that
+//! only the passphrase has to be shared.
+//!
+//! Patterns apply to single cover bytes by default. A pattern may instead apply to wider
+//! [samples][sample], such as 16-bit audio samples, which are then addressed by their index
+//! and masked as a whole. See [`pattern::Words`] for details.
+//!
+//! ## Framing
+//!
+//! The receiver has to know where the message ends. It may either know the length of the
+//! message beforehand ([`Package::with_len`]), read it from the package, where it is
+//! stored as a 64-bit integer ([`Carrier::with_embedded_len`] and
+//! [`Package::with_embedded_len`]), or read a self-describing versioned [`Header`]
+//! ([`Carrier::with_header`] and [`Package::with_header`]). The header also describes the
+//! options the message was concealed with and allows telling whether a package contains
+//! a message at all via [`Package::detect`].
+//!
+//! ## Verification
+//!
+//! A pattern that is wrong still reveals *something*. [`Carrier::checksum`] appends a
+//! [`Checksum`] of the payload, which [`Package::checksum`] verifies, returning
+//! [`Error::Verification`][crate::Error::Verification] if the revealed message does not
+//! match it.
+//!
+//! ## Embedding Order
+//!
+//! By default, the payload is written into the cover bytes in order, so that it occupies
+//! a contiguous region at the start of the cover. [`Carrier::permuted`] and
+//! [`Package::permuted`] scatter the payload over a pseudo-random permutation of the cover
+//! bytes instead, which is derived from a seed shared by the sender and the receiver.
+//!
+//! ## Minimizing Changes
+//!
+//! Bit replacement changes about half of the cover bits it uses. [`Carrier::matrix_embedding`]
+//! embeds several bits into each group of cover bits while changing at most one of them,
+//! choosing the group size from the sizes of the payload and the cover, so that the cover is
+//! changed less at the expense of capacity. See [`MatrixStatistics`] for details.
+//!
+//! [`Carrier::syndrome_trellis`] goes further and chooses the cover bits to change by the
+//! cost of changing them, which is supplied for every sample, so that the changes concentrate
+//! where they are the least detectable. See [`TrellisStatistics`] for details.
+//!
+//! Carriers that use [`Carrier::reporting`] produce a [`Report`] with every
+//! [`conceal`][crate::Conceal::conceal], available from [`Carrier::report`], which tells how
+//! many cover bits were used and changed, and at which bit positions.
+//!
+//! ## Skipped Regions
+//!
+//! Some parts of a cover, such as magic numbers and file headers, have to stay intact.
+//! [`Carrier::skip_regions`] and [`Package::skip_regions`] exclude the supplied [`Regions`]
+//! from embedding, so that they are copied through unchanged.
+//!
+//! ## Error Correction
+//!
+//! Covers may be altered in transit, and a single flipped bit can corrupt the embedded length
+//! and thus the whole message. [`Carrier::fec`] and [`Package::fec`] apply a forward error
+//! correction code to the concealed data, so that moderate corruption is corrected
+//! transparently. See [`Fec`] for the available codes.
+//!
+//! ## Encryption
+//!
+//! With the `encrypt` feature enabled, [`EncryptedCarrier`] and [`EncryptedPackage`] wrap
+//! [`Carrier`] and [`Package`] in order to encrypt and authenticate the payload with a key
+//! derived from a password, so that guessing the pattern is not enough to read the message.
+//!
 //! ## Use with Various Formats
 //!
 //! Binary data is always represented in some sort of *format*. It is straightforward to
@@ -28,6 +95,8 @@
 //! to hide a PNG image inside a WAV audio file, use a WAV reader output as cover data
 //! and PNG writer as package destination.
 //!
+//! The [`formats`][crate::formats] module provides such adapters for common formats.
+//!
 //! ## Examples
 //!
 //! Concealing a secret message with embedded length:
@@ -54,7 +123,7 @@
 //!     payload.as_slice(),
 //!     File::open("cover")?,
 //! )?;
-//! # Ok::<(), std::io::Error>(())
+//! # Ok::<(), asbs::Error>(())
 //! ```
 //!
 //! Extracting a hidden message with embedded length:
@@ -74,12 +143,37 @@
 //!
 //! // Write the extracted message into a file
 //! package.reveal(File::create("message")?)?;
-//! # Ok::<(), std::io::Error>(())
+//! # Ok::<(), asbs::Error>(())
 //! ```
 
+pub mod pattern;
+pub mod sample;
+
 mod bits;
+mod capacity;
 mod carrier;
+mod checksum;
+#[cfg(feature = "encrypt")]
+mod encrypted;
+mod fec;
+mod header;
+mod keystream;
+mod matrix;
 mod package;
+mod regions;
+mod report;
+mod trellis;
 
+pub use capacity::{capacity, Estimate};
 pub use carrier::Carrier;
+pub use checksum::{Checksum, VerificationError};
+#[cfg(feature = "encrypt")]
+pub use encrypted::{AuthenticationError, EncryptedCarrier, EncryptedPackage};
+pub use fec::{Fec, UncorrectableError};
+pub use header::{Header, HeaderError};
+pub use matrix::MatrixStatistics;
 pub use package::Package;
+pub use pattern::Pattern;
+pub use regions::Regions;
+pub use report::Report;
+pub use trellis::TrellisStatistics;
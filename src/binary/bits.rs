@@ -1,14 +1,13 @@
+use std::io;
+
+/// An iterator over the positions of the set bits of a mask, from the least significant one.
 pub(super) struct Ones {
-    byte: u8,
-    offset: usize,
+    word: u32,
 }
 
-impl From<u8> for Ones {
-    fn from(value: u8) -> Self {
-        Self {
-            byte: value,
-            offset: 0,
-        }
+impl From<u32> for Ones {
+    fn from(value: u32) -> Self {
+        Self { word: value }
     }
 }
 
@@ -16,19 +15,78 @@ impl Iterator for Ones {
     type Item = usize;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if self.offset == 8 {
+        if self.word == 0 {
             return None;
         }
 
-        while self.byte & (1 << self.offset) == 0 {
-            self.offset += 1;
+        let pow = self.word.trailing_zeros() as usize;
+        self.word &= self.word - 1;
+
+        Some(pow)
+    }
+}
 
-            if self.offset == 8 {
-                return None;
+pub(super) struct Bits<I> {
+    bytes: I,
+    byte: u8,
+    remaining: u8,
+    position: u64,
+}
+
+impl<I> Bits<I>
+where
+    I: Iterator<Item = io::Result<u8>>,
+{
+    pub(super) fn new(bytes: I) -> Self {
+        Self {
+            bytes,
+            byte: 0,
+            remaining: 0,
+            position: 0,
+        }
+    }
+
+    /// Returns a reference to the underlying iterator.
+    pub(super) fn get_ref(&self) -> &I {
+        &self.bytes
+    }
+
+    /// Returns how many bits were returned so far.
+    pub(super) fn position(&self) -> u64 {
+        self.position
+    }
+
+    /// Returns the number of bits left in the current byte.
+    pub(super) fn remaining(&self) -> u64 {
+        u64::from(self.remaining)
+    }
+
+    /// Returns `true` if there are no more bits left.
+    pub(super) fn is_exhausted(&mut self) -> io::Result<bool> {
+        if self.remaining == 0 {
+            match self.bytes.next() {
+                Some(byte) => {
+                    self.byte = byte?;
+                    self.remaining = 8;
+                }
+                None => return Ok(true),
             }
         }
 
-        let next_offset = self.offset + 1;
-        Some(std::mem::replace(&mut self.offset, next_offset))
+        Ok(false)
+    }
+
+    /// Returns the next bit, starting from the least significant bit of each byte.
+    pub(super) fn next_bit(&mut self) -> io::Result<Option<u8>> {
+        if self.is_exhausted()? {
+            return Ok(None);
+        }
+
+        let bit = self.byte & 1;
+        self.byte >>= 1;
+        self.remaining -= 1;
+        self.position += 1;
+
+        Ok(Some(bit))
     }
 }
@@ -0,0 +1,72 @@
+use crate::binary::{
+    sample::{self, Sample},
+    Pattern,
+};
+
+/// Returns how many bits the supplied pattern provides in a cover of the supplied length
+/// in bytes.
+///
+/// This is the raw number of bits available for the concealed data, which includes the
+/// embedded length, the [`Header`][crate::binary::Header], and any error correction.
+/// See [`Carrier::capacity_for`][crate::binary::Carrier::capacity_for] for the number of
+/// payload bytes a carrier can actually conceal.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::binary;
+///
+/// assert_eq!(binary::capacity(|_| Some(0b101), 64), 128);
+/// assert_eq!(binary::capacity(|i| (i < 8).then_some(0b1), 64), 8);
+/// ```
+#[must_use]
+pub fn capacity<P: Pattern>(mut pattern: P, cover_len: usize) -> u64 {
+    count_bits(&mut pattern, 0..cover_len / P::Sample::LEN)
+}
+
+/// Returns how many bits the pattern provides at the supplied sample indices, stopping
+/// at the first index the pattern returns [`None`] for.
+pub(super) fn count_bits<P: Pattern>(
+    pattern: &mut P,
+    indices: impl IntoIterator<Item = usize>,
+) -> u64 {
+    indices
+        .into_iter()
+        .map_while(|index| pattern.mask(index))
+        .map(|mask| u64::from(sample::mask_bits::<P::Sample>(mask).count_ones()))
+        .sum()
+}
+
+/// The result of a [`Carrier::dry_run`][crate::binary::Carrier::dry_run], which compares
+/// the number of bits required to conceal a payload with the number of bits available.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::binary;
+///
+/// let estimate = binary::Carrier::with_embedded_len(4, |_| Some(0b11), Vec::new())
+///     .dry_run(b"four".as_slice(), [0u8; 64].as_slice())?;
+///
+/// assert_eq!(estimate.needed_bits, 96);
+/// assert_eq!(estimate.available_bits, 128);
+/// assert!(estimate.fits());
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Estimate {
+    /// The number of bits required to conceal the payload, including any framing and
+    /// error correction.
+    pub needed_bits: u64,
+
+    /// The number of bits the cover and the pattern provide.
+    pub available_bits: u64,
+}
+
+impl Estimate {
+    /// Returns `true` if the payload fits into the cover.
+    #[must_use]
+    pub fn fits(&self) -> bool {
+        self.needed_bits <= self.available_bits
+    }
+}
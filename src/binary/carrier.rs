@@ -1,6 +1,40 @@
-use crate::{binary::bits, Conceal};
+use crate::{
+    binary::{
+        bits::{self, Bits},
+        capacity::{self, Estimate},
+        checksum::Checksummed,
+        fec::Encoder,
+        keystream::{Keystream, Permutation},
+        matrix::{self, MatrixStatistics, Syndromes},
+        regions::Skipping,
+        report::Report,
+        sample::{self, Sample},
+        trellis::{self, Trellis, TrellisStatistics},
+        Checksum, Fec, Header, Pattern, Regions,
+    },
+    Conceal, Error,
+};
 use std::io::{self, BufReader, BufWriter, Read, Write};
 
+#[derive(Debug, Clone, Copy)]
+enum Framing {
+    None,
+    Length(u64),
+    Header(Header),
+}
+
+#[derive(Debug)]
+enum Code {
+    Matrix,
+    Trellis(Trellis),
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Statistics {
+    Matrix(MatrixStatistics),
+    Trellis(TrellisStatistics),
+}
+
 /// A binary carrier that can conceal a steganographic message.
 ///
 /// It writes to the carrier writer in the [`conceal`][crate::Conceal::conceal] method until
@@ -9,10 +43,13 @@ use std::io::{self, BufReader, BufWriter, Read, Write};
 /// 1. The specified length of the payload is reached, or
 /// 2. The payload is empty, in which case the remainder of the cover is copied into
 ///    the writer, or
-/// 3. The cover is empty or the writer no longer accepts any writes, in which case the
+/// 3. The cover or the pattern ends before the payload is concealed, in which case the
 ///    message may remain partially written; it may be possible if the message is too
-///    large or the bit pattern is too sparse. In this case an error of kind
-///    [`std::io::ErrorKind::WriteZero`] is returned.
+///    large or the bit pattern is too sparse. In this case
+///    [`Error::InsufficientCapacity`] or [`Error::PatternExhausted`] is returned.
+///
+/// Use [`Carrier::capacity_for`] or [`Carrier::dry_run`] to check whether the payload fits
+/// beforehand.
 ///
 /// # Examples
 ///
@@ -31,7 +68,7 @@ use std::io::{self, BufReader, BufWriter, Read, Write};
 ///     b"a very secret message".as_slice(),
 ///     File::open("cover")?,
 /// )?;
-/// # Ok::<(), std::io::Error>(())
+/// # Ok::<(), asbs::Error>(())
 /// ```
 ///
 /// Concealing a secret message in the supplied cover with its length embedded:
@@ -52,28 +89,38 @@ use std::io::{self, BufReader, BufWriter, Read, Write};
 ///     payload.as_slice(),
 ///     File::open("cover")?,
 /// )?;
-/// # Ok::<(), std::io::Error>(())
+/// # Ok::<(), asbs::Error>(())
 /// ```
 #[derive(Debug)]
 pub struct Carrier<P, W>
 where
-    P: FnMut(usize) -> Option<u8>,
+    P: Pattern,
     W: Write,
 {
     pattern: P,
     writer: BufWriter<W>,
-    len: Option<u64>,
+    framing: Framing,
+    order: Option<Keystream>,
+    matching: Option<Keystream>,
+    code: Option<Code>,
+    regions: Option<Regions>,
+    fec: Option<Fec>,
+    checksum: Option<Checksum>,
+    statistics: Option<Statistics>,
+    is_reporting: bool,
+    report: Option<Report>,
 }
 
 impl<P, W> Carrier<P, W>
 where
-    P: FnMut(usize) -> Option<u8>,
+    P: Pattern,
     W: Write,
 {
     /// Creates a new [`Carrier<P, W>`] with the supplied length, pattern, and writer.
     ///
     /// This embeds a length into the payload and stops writing when the length is reached.
-    /// The length is encoded as a 64-bit integer in big-endian byte order.
+    /// The length is encoded as a 64-bit integer in big-endian byte order. If the payload
+    /// turns out to be shorter, [`Error::TruncatedPayload`] is returned.
     ///
     /// # Examples
     ///
@@ -86,13 +133,60 @@ where
     ///     |_| Some(0b11),
     ///     File::create("package")?,
     /// );
-    /// # Ok::<(), std::io::Error>(())
+    /// # Ok::<(), asbs::Error>(())
     /// ```
     pub fn with_embedded_len(len: usize, pattern: P, writer: W) -> Self {
         Self {
             pattern,
             writer: BufWriter::new(writer),
-            len: Some(len as u64),
+            framing: Framing::Length(len as u64),
+            order: None,
+            matching: None,
+            code: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            statistics: None,
+            is_reporting: false,
+            report: None,
+        }
+    }
+
+    /// Creates a new [`Carrier<P, W>`] with the supplied length, pattern, and writer.
+    ///
+    /// This writes a self-describing [`Header`] in front of the payload and stops writing
+    /// when the length is reached. The header contains the payload length as well as the
+    /// options the payload is concealed with, so that the receiver only needs to know the
+    /// pattern and use [`Package::with_header`][crate::binary::Package::with_header].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::Carrier::with_header(
+    ///     2048,
+    ///     |_| Some(0b11),
+    ///     File::create("package")?,
+    /// );
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_header(len: usize, pattern: P, writer: W) -> Self {
+        Self {
+            pattern,
+            writer: BufWriter::new(writer),
+            framing: Framing::Header(Header::new(len as u64)),
+            order: None,
+            matching: None,
+            code: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            statistics: None,
+            is_reporting: false,
+            report: None,
         }
     }
 
@@ -112,94 +206,1073 @@ where
     ///     |_| Some(0b101),
     ///     File::create("package")?,
     /// );
-    /// # Ok::<(), std::io::Error>(())
+    /// # Ok::<(), asbs::Error>(())
     /// ```
     #[must_use]
     pub fn new(pattern: P, writer: W) -> Self {
         Self {
             pattern,
             writer: BufWriter::new(writer),
-            len: None,
+            framing: Framing::None,
+            order: None,
+            matching: None,
+            code: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            statistics: None,
+            is_reporting: false,
+            report: None,
+        }
+    }
+
+    #[cfg(feature = "encrypt")]
+    pub(super) fn set_encrypted_len(&mut self, len: usize) {
+        match &mut self.framing {
+            Framing::None => {}
+            Framing::Length(n) => *n = len as u64,
+            Framing::Header(header) => {
+                header.len = len as u64;
+                header.encrypted = true;
+            }
+        }
+    }
+
+    /// Makes the carrier scatter the payload over a pseudo-random permutation of cover bytes.
+    ///
+    /// The permutation is derived from the supplied seed, which has to be shared with the
+    /// receiver, who should use [`Package::permuted`][crate::binary::Package::permuted] with
+    /// the same seed. The pattern is queried with the indices of cover bytes in the order
+    /// they are visited.
+    ///
+    /// The whole cover is buffered in memory during [`conceal`][crate::Conceal::conceal],
+    /// and nothing is written if the payload does not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::Carrier::new(
+    ///     |_| Some(0b1),
+    ///     File::create("package")?,
+    /// )
+    /// .permuted("a shared seed");
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn permuted(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.order = Some(Keystream::new(b"asbs permutation", seed.as_ref()));
+        self
+    }
+
+    /// Makes the carrier change cover samples by LSB matching instead of bit replacement.
+    ///
+    /// Bit replacement sets the bits selected by the pattern to the bits of the payload,
+    /// which evens out the frequencies of pairs of values differing only in the least
+    /// significant bit, a signature that chi-square attacks detect. LSB matching instead
+    /// adds or subtracts one (or the value of the masked bit in general) whenever a bit has
+    /// to change, choosing the direction pseudo-randomly unless the sample is at the edge of
+    /// its range, where it never wraps around. Signed samples do not wrap around between the
+    /// positive and negative extremes either. [`f32`] samples step their magnitude and keep
+    /// their sign, so that finite samples never become infinite or NaN.
+    ///
+    /// The directions are derived from the supplied seed, so that concealing the same payload
+    /// in the same cover always produces the same package. The receiver does not have to
+    /// know the seed, as the revealed bits do not depend on it.
+    ///
+    /// # Examples
+    ///
+    /// Samples at the edges of their range are only ever changed towards the middle:
+    ///
+    /// ```
+    /// use asbs::{binary, Conceal};
+    ///
+    /// let mut package = Vec::new();
+    ///
+    /// binary::Carrier::new(|_| Some(0b1), &mut package)
+    ///     .lsb_matching("a seed")
+    ///     .conceal([0b1111_0000].as_slice(), [0xff, 0xff, 0x00, 0x00].repeat(2).as_slice())?;
+    ///
+    /// assert_eq!(package, [0xfe, 0xfe, 0x00, 0x00, 0xff, 0xff, 0x01, 0x01]);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn lsb_matching(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.matching = Some(Keystream::new(b"asbs matching", seed.as_ref()));
+        self
+    }
+
+    /// Makes the carrier conceal the data by matrix embedding, which changes fewer cover bits
+    /// than bit replacement.
+    ///
+    /// The cover bits selected by the pattern are split into groups of 2<sup>k</sup> − 1 bits,
+    /// each of which holds `k` bits of the concealed data while at most one of its bits is
+    /// changed. The largest `k` with which the data fits into the cover is chosen
+    /// automatically during [`conceal`][crate::Conceal::conceal], and reported by
+    /// [`Carrier::matrix_statistics`] afterwards. Matrix embedding may be combined with
+    /// [`Carrier::lsb_matching`], in which case the changed bits are changed by LSB matching,
+    /// unless that would change other bits selected by the pattern.
+    ///
+    /// Carriers created with [`Carrier::with_header`] store `k` in the header, which is
+    /// itself concealed by bit replacement, so that packages created with
+    /// [`Package::with_header`][crate::binary::Package::with_header] detect it. Otherwise, the
+    /// receiver should use [`Package::matrix_embedding`][crate::binary::Package::matrix_embedding]
+    /// with the same `k`.
+    ///
+    /// Both the payload and the cover are buffered in memory during
+    /// [`conceal`][crate::Conceal::conceal], and nothing is written if the payload does
+    /// not fit.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::{binary, Conceal};
+    ///
+    /// let payload = b"a secret";
+    ///
+    /// let mut package = Vec::new();
+    /// let mut carrier = binary::Carrier::with_embedded_len(8, |_| Some(0b1), &mut package)
+    ///     .matrix_embedding();
+    ///
+    /// carrier.conceal(payload.as_slice(), [0; 512].as_slice())?;
+    ///
+    /// // The payload and its length take 32 groups of 15 cover bits
+    /// let statistics = carrier.matrix_statistics().unwrap();
+    /// assert_eq!(statistics.k, 4);
+    /// assert_eq!(statistics.cover_bits, 480);
+    /// assert!(statistics.changed_bits <= 32);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn matrix_embedding(mut self) -> Self {
+        self.code = Some(Code::Matrix);
+        self
+    }
+
+    /// Returns the statistics of the last matrix embedding, if any.
+    ///
+    /// The statistics are only available after [`conceal`][crate::Conceal::conceal] with a
+    /// carrier that uses [`Carrier::matrix_embedding`].
+    #[must_use]
+    pub fn matrix_statistics(&self) -> Option<&MatrixStatistics> {
+        match &self.statistics {
+            Some(Statistics::Matrix(statistics)) => Some(statistics),
+            _ => None,
+        }
+    }
+
+    /// Makes the carrier conceal the data by syndrome-trellis coding, which minimizes the
+    /// total cost of the changed cover bits.
+    ///
+    /// Every bit of the concealed data is embedded into a block of cover bits selected by
+    /// the pattern as its syndrome under a parity-check matrix, which is derived from the
+    /// supplied seed. Out of all the ways to change the cover bits so that they have the
+    /// required syndromes, the one with the least total cost is chosen, where changing a bit
+    /// of the sample at some index costs what the supplied function returns for that index.
+    /// Costs should reflect how detectable changes of the samples are, e.g. be low in noisy
+    /// regions of an image and high in smooth ones. Negative costs count as zero.
+    ///
+    /// The largest width of the blocks with which the data fits into the cover is chosen
+    /// automatically during [`conceal`][crate::Conceal::conceal] and stored in the header,
+    /// which is itself concealed by bit replacement. The receiver should use
+    /// [`Package::syndrome_trellis`][crate::binary::Package::syndrome_trellis] with the same
+    /// seed, but does not need to know the costs. The statistics of the embedding are
+    /// reported by [`Carrier::trellis_statistics`] afterwards.
+    ///
+    /// The width is at most 32 cover bits per data bit. Data that leaves more of the cover
+    /// unused only occupies the first cover bits selected by the pattern, which
+    /// [`Carrier::permuted`] scatters over the whole cover.
+    ///
+    /// Both the payload and the cover are buffered in memory during
+    /// [`conceal`][crate::Conceal::conceal], and nothing is written if the payload does
+    /// not fit. Besides, the Viterbi algorithm keeps 16 bytes for every cover bit that the
+    /// data occupies, i.e. up to 4 KiB per byte of data.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the carrier was not created with [`Carrier::with_header`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::{binary, Conceal};
+    ///
+    /// let cover: Vec<u8> = (0..=255).cycle().take(4096).collect();
+    ///
+    /// // Changes of even samples are much more expensive
+    /// let mut package = Vec::new();
+    /// let mut carrier = binary::Carrier::with_header(8, |_| Some(0b1), &mut package)
+    ///     .syndrome_trellis("a shared seed", |i| if i.is_multiple_of(2) { 100.0 } else { 1.0 });
+    ///
+    /// carrier.conceal(b"a secret".as_slice(), cover.as_slice())?;
+    ///
+    /// let statistics = carrier.trellis_statistics().unwrap();
+    /// assert!(statistics.cost < 100.0);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn syndrome_trellis<F>(mut self, seed: impl AsRef<[u8]>, costs: F) -> Self
+    where
+        F: FnMut(usize) -> f64 + Send + Sync + 'static,
+    {
+        assert!(
+            matches!(self.framing, Framing::Header(_)),
+            "syndrome-trellis coding requires a header",
+        );
+
+        self.code = Some(Code::Trellis(Trellis {
+            keystream: Keystream::new(b"asbs trellis", seed.as_ref()),
+            costs: Box::new(costs),
+        }));
+
+        self
+    }
+
+    /// Returns the statistics of the last syndrome-trellis embedding, if any.
+    ///
+    /// The statistics are only available after [`conceal`][crate::Conceal::conceal] with a
+    /// carrier that uses [`Carrier::syndrome_trellis`].
+    #[must_use]
+    pub fn trellis_statistics(&self) -> Option<&TrellisStatistics> {
+        match &self.statistics {
+            Some(Statistics::Trellis(statistics)) => Some(statistics),
+            _ => None,
+        }
+    }
+
+    /// Makes the carrier report how the cover was changed by every
+    /// [`conceal`][crate::Conceal::conceal], which is available from [`Carrier::report`]
+    /// afterwards.
+    ///
+    /// To tell how many bits the whole cover provides, the pattern is queried for every
+    /// sample of the cover, including the ones after the concealed data.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::{binary, Conceal};
+    ///
+    /// let mut package = Vec::new();
+    /// let mut carrier = binary::Carrier::new(|_| Some(0b1), &mut package).reporting();
+    ///
+    /// carrier.conceal([0xff].as_slice(), [0u8; 16].as_slice())?;
+    ///
+    /// assert_eq!(carrier.report().unwrap().utilization(), 0.5);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn reporting(mut self) -> Self {
+        self.is_reporting = true;
+        self
+    }
+
+    /// Returns the report of the last embedding, if any.
+    ///
+    /// The report is available after every successful [`conceal`][crate::Conceal::conceal]
+    /// with a carrier that uses [`Carrier::reporting`]. See [`Report`] for details.
+    #[must_use]
+    pub fn report(&self) -> Option<&Report> {
+        self.report.as_ref()
+    }
+
+    /// Makes the carrier leave the cover bytes within the supplied regions unchanged.
+    ///
+    /// The regions have to be shared with the receiver, who should use
+    /// [`Package::skip_regions`][crate::binary::Package::skip_regions] with the same regions.
+    /// Carriers created with [`Carrier::with_header`] store the
+    /// [`fingerprint`][Regions::fingerprint] of the regions in the header, so that
+    /// mismatching regions are detected.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Regions};
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::Carrier::with_header(
+    ///     2048,
+    ///     |_| Some(0b1),
+    ///     File::create("package")?,
+    /// )
+    /// .skip_regions(Regions::new().skip(..512));
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn skip_regions(mut self, regions: Regions) -> Self {
+        self.regions = Some(regions).filter(|regions| !regions.is_empty());
+        self
+    }
+
+    /// Makes the carrier encode the concealed data, including the embedded length, with the
+    /// supplied forward error correction code.
+    ///
+    /// The receiver should use [`Package::fec`][crate::binary::Package::fec] with the same code.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters of the code are invalid. See [`Fec`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Fec};
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::Carrier::with_embedded_len(
+    ///     2048,
+    ///     |_| Some(0b11),
+    ///     File::create("package")?,
+    /// )
+    /// .fec(Fec::Hamming);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn fec(mut self, fec: Fec) -> Self {
+        fec.validate();
+        self.fec = Some(fec);
+        self
+    }
+
+    /// Makes the carrier append the supplied checksum of the payload to the payload.
+    ///
+    /// The receiver should use [`Package::checksum`][crate::binary::Package::checksum] with
+    /// the same checksum, unless the carrier writes a [`Header`], in which case the checksum
+    /// is read from the header.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Checksum};
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::Carrier::with_embedded_len(
+    ///     2048,
+    ///     |_| Some(0b11),
+    ///     File::create("package")?,
+    /// )
+    /// .checksum(Checksum::Sha256);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Returns the maximum length of a payload in bytes that the carrier can conceal in the
+    /// supplied cover, accounting for the framing, the checksum, and the error correction.
+    ///
+    /// Zero is returned if not even an empty payload fits into the cover.
+    ///
+    /// The pattern is queried for every sample of the cover, so it has to be a pure function
+    /// of the index, see [`Pattern`].
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::binary;
+    ///
+    /// let mut carrier = binary::Carrier::with_embedded_len(0, |_| Some(0b11), Vec::new());
+    ///
+    /// assert_eq!(carrier.capacity_for(&[0; 64]), 8);
+    /// ```
+    pub fn capacity_for(&mut self, cover: &[u8]) -> usize {
+        let available_bits = self.available_bits(cover.len());
+
+        if self.needed_bits(0) > available_bits {
+            return 0;
+        }
+
+        let (mut low, mut high) = (0, available_bits / 8);
+        while low < high {
+            let mid = low + (high - low).div_ceil(2);
+
+            if self.needed_bits(mid) <= available_bits {
+                low = mid;
+            } else {
+                high = mid - 1;
+            }
         }
+
+        low as usize
     }
+
+    /// Estimates whether the payload fits into the cover without writing anything.
+    ///
+    /// Both the payload and the cover are read to the end, or up to the length of the
+    /// payload the carrier was created with, in order to compare the number of bits
+    /// [`conceal`][crate::Conceal::conceal] would require with the number of bits available.
+    /// Like [`Carrier::capacity_for`], it queries the pattern for every sample of the cover.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::TruncatedPayload`] if the payload is shorter than the length the carrier
+    /// was created with.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let payload = b"a very secret message";
+    ///
+    /// let estimate = binary::Carrier::with_embedded_len(
+    ///     payload.len(),
+    ///     |_| Some(0b1),
+    ///     File::create("package")?,
+    /// )
+    /// .dry_run(payload.as_slice(), File::open("cover")?)?;
+    ///
+    /// if !estimate.fits() {
+    ///     println!("{} more bits are needed", estimate.needed_bits - estimate.available_bits);
+    /// }
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    pub fn dry_run(&mut self, payload: impl Read, mut cover: impl Read) -> Result<Estimate, Error> {
+        let len = match self.framing {
+            Framing::None => None,
+            Framing::Length(len) => Some(len),
+            Framing::Header(header) => Some(header.len),
+        };
+
+        let payload_len = io::copy(&mut payload.take(len.unwrap_or(u64::MAX)), &mut io::sink())?;
+
+        if let Some(len) = len.filter(|&len| payload_len < len) {
+            return Err(Error::TruncatedPayload {
+                expected: len,
+                actual: payload_len,
+            });
+        }
+
+        let cover_len = io::copy(&mut cover, &mut io::sink())?;
+
+        Ok(Estimate {
+            needed_bits: self.needed_bits(payload_len),
+            available_bits: self.available_bits(cover_len as usize),
+        })
+    }
+
+    /// Returns the header to write, describing the options the payload is concealed with.
+    fn describe(&self, header: Header) -> Header {
+        Header {
+            fec: self.fec,
+            checksum: self.checksum,
+            regions: self.regions.as_ref().map(Regions::fingerprint),
+            // The actual parameters of the codes are only known once the payload is read,
+            // but they do not change the length of the header.
+            matrix: matches!(self.code, Some(Code::Matrix)).then_some(1),
+            trellis: matches!(self.code, Some(Code::Trellis(_))).then_some(1),
+            ..header
+        }
+    }
+
+    /// Returns how many bits are needed to conceal a payload of the supplied length.
+    fn needed_bits(&self, len: u64) -> u64 {
+        let (prefix_len, len_bytes) = match self.framing {
+            Framing::None => (0, 0),
+            Framing::Length(_) => (0, 8),
+            Framing::Header(header) => (self.describe(Header { len, ..header }).encode().len(), 0),
+        };
+
+        let raw_len = len_bytes + len + self.checksum.map_or(0, Checksum::size) as u64;
+        let coded_len = self.fec.map_or(raw_len, |fec| fec.encoded_len(raw_len));
+
+        (prefix_len as u64 + coded_len) * 8
+    }
+
+    /// Collects the cover bits selected by the pattern for concealing the data by a code.
+    fn coded(&mut self, header: Option<Header>, samples: usize) -> Coded {
+        let indices: Box<dyn Iterator<Item = usize>> = match &self.order {
+            None => Box::new(0..samples),
+            Some(keystream) => Box::new(Permutation::new(keystream.clone(), samples)),
+        };
+
+        let mut pattern = Skipping::new(&mut self.pattern, self.regions.as_ref());
+        let mut is_pattern_exhausted = false;
+
+        let mut slots = Vec::new();
+        for index in indices {
+            let Some(mask) = pattern.mask(index) else {
+                is_pattern_exhausted = true;
+                break;
+            };
+
+            let mask = sample::mask_bits::<P::Sample>(mask);
+            slots.extend(bits::Ones::from(mask).map(|pow| Slot { index, pow, mask }));
+        }
+
+        Coded {
+            slots,
+            is_pattern_exhausted,
+            header,
+            prefix_len: header.map_or(0, |header| header.encode().len() * 8),
+            matching: self.matching.clone(),
+        }
+    }
+
+    /// Returns how many bits are available in a cover of the supplied length.
+    fn available_bits(&mut self, cover_len: usize) -> u64 {
+        let mut pattern = Skipping::new(&mut self.pattern, self.regions.as_ref());
+
+        let samples = cover_len / P::Sample::LEN;
+
+        match &self.order {
+            None => capacity::count_bits(&mut pattern, 0..samples),
+            Some(keystream) => {
+                capacity::count_bits(&mut pattern, Permutation::new(keystream.clone(), samples))
+            }
+        }
+    }
+}
+
+fn embed<S, I>(
+    index: usize,
+    mask: S::Mask,
+    sample: &mut [u8],
+    bits: &mut Bits<I>,
+    mut matching: Option<&mut Keystream>,
+    report: &mut Report,
+) -> io::Result<()>
+where
+    S: Sample,
+    I: Iterator<Item = io::Result<u8>>,
+{
+    let cover_word = S::read(sample);
+
+    let mut package_word = cover_word;
+    let mut is_touched = false;
+    for pow in bits::Ones::from(sample::mask_bits::<S>(mask)) {
+        let Some(bit) = bits.next_bit()? else {
+            break;
+        };
+
+        package_word = match matching.as_deref_mut() {
+            None => package_word & !(1 << pow) | u32::from(bit) << pow,
+            Some(keystream) => match_bit::<S>(package_word, pow, bit, keystream),
+        };
+        is_touched = true;
+    }
+
+    S::write(package_word, sample);
+
+    if is_touched {
+        report.record::<S>(index, cover_word, package_word);
+    }
+
+    Ok(())
+}
+
+/// The cover bits selected by the pattern, which codes embed the data into as a whole.
+struct Coded {
+    slots: Vec<Slot>,
+    is_pattern_exhausted: bool,
+    header: Option<Header>,
+    prefix_len: usize,
+    matching: Option<Keystream>,
+}
+
+impl Coded {
+    /// Returns how many cover bits are available after the header, if it fits at all.
+    fn available_bits(&self) -> Option<usize> {
+        self.slots.len().checked_sub(self.prefix_len)
+    }
+
+    /// Returns the error of the data not fitting into the cover.
+    fn capacity_error(&self, data_bits: usize) -> Error {
+        if self.is_pattern_exhausted {
+            return Error::PatternExhausted;
+        }
+
+        Error::InsufficientCapacity {
+            needed_bits: (self.prefix_len + data_bits) as u64,
+            available_bits: self.slots.len() as u64,
+        }
+    }
+
+    /// Returns the indices of the samples that the supplied number of leading slots lie in.
+    fn samples(&self, slots: usize) -> impl Iterator<Item = usize> + '_ {
+        // The slots of every sample are adjacent, as each sample is visited once.
+        self.slots[..slots]
+            .iter()
+            .enumerate()
+            .filter(|&(position, slot)| {
+                position == 0 || self.slots[position - 1].index != slot.index
+            })
+            .map(|(_, slot)| slot.index)
+    }
+
+    /// Returns the words of the samples that the slots lie in.
+    fn words<S: Sample>(&self, buffer: &[u8]) -> Vec<u32> {
+        self.samples(self.slots.len())
+            .map(|index| S::read(&buffer[index * S::LEN..]))
+            .collect()
+    }
+
+    /// Returns the report of an embedding into the supplied number of leading slots, given
+    /// the [`words`][Coded::words] of the samples before the embedding.
+    fn report<S: Sample>(
+        &self,
+        embedded_bits: u64,
+        slots: u64,
+        words: &[u32],
+        buffer: &[u8],
+    ) -> Report {
+        let mut report = Report::new::<S>();
+
+        for (index, &word) in self.samples(slots as usize).zip(words) {
+            report.record::<S>(index, word, S::read(&buffer[index * S::LEN..]));
+        }
+
+        Report {
+            embedded_bits,
+            available_bits: self.slots.len() as u64,
+            ..report
+        }
+    }
+
+    /// Embeds the header by bit replacement, returning the number of changed bits.
+    fn embed_header<S: Sample>(&mut self, header: Option<Header>, buffer: &mut [u8]) -> u64 {
+        let prefix = header.map_or_else(Vec::new, Header::encode);
+        let prefix_bits = prefix
+            .iter()
+            .flat_map(|&byte| (0..8).map(move |pow| byte >> pow & 1));
+
+        let mut changed_bits = 0;
+        for (slot, bit) in self.slots.iter().zip(prefix_bits) {
+            if slot.bit::<S>(buffer) != bit {
+                slot.flip::<S>(buffer, self.matching.as_mut());
+                changed_bits += 1;
+            }
+        }
+
+        changed_bits
+    }
+
+    /// Embeds the header and the data bits by matrix embedding.
+    fn embed_matrix<S: Sample>(
+        &mut self,
+        data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<MatrixStatistics, Error> {
+        let k = self
+            .available_bits()
+            .and_then(|available_bits| matrix::choose_k(data.len() as u64, available_bits as u64))
+            .ok_or_else(|| self.capacity_error(data.len()))?;
+
+        let header = self.header.map(|header| Header {
+            matrix: Some(k),
+            ..header
+        });
+
+        let mut changed_bits = self.embed_header::<S>(header, buffer);
+
+        let group_len = matrix::group_len(k) as usize;
+        let groups = self.slots[self.prefix_len..].chunks(group_len);
+
+        for (group, chunk) in groups.zip(data.chunks(k.into())) {
+            let mut syndromes = Syndromes::new(k);
+            let syndrome = group
+                .iter()
+                .find_map(|slot| syndromes.push(slot.bit::<S>(buffer)))
+                .unwrap_or_default();
+
+            let value = chunk
+                .iter()
+                .enumerate()
+                .fold(0, |value, (pow, &bit)| value | u32::from(bit) << pow);
+
+            let position = (syndrome ^ value) as usize;
+            if position != 0 {
+                group[position - 1].flip::<S>(buffer, self.matching.as_mut());
+                changed_bits += 1;
+            }
+        }
+
+        let group_count = data.len().div_ceil(k.into());
+
+        Ok(MatrixStatistics {
+            k,
+            embedded_bits: (self.prefix_len + data.len()) as u64,
+            cover_bits: (self.prefix_len + group_count * group_len) as u64,
+            changed_bits,
+        })
+    }
+
+    /// Embeds the header and the data bits by syndrome-trellis coding.
+    fn embed_trellis<S: Sample>(
+        &mut self,
+        trellis: &mut Trellis,
+        data: &[u8],
+        buffer: &mut [u8],
+    ) -> Result<TrellisStatistics, Error> {
+        let width = self
+            .available_bits()
+            .and_then(|available_bits| trellis::choose_width(data.len(), available_bits))
+            .ok_or_else(|| self.capacity_error(data.len()))?;
+
+        let header = self.header.map(|header| Header {
+            trellis: Some(width as u32),
+            ..header
+        });
+
+        let mut changed_bits = self.embed_header::<S>(header, buffer);
+
+        let slots = &self.slots[self.prefix_len..][..data.len() * width];
+        let cover: Vec<u8> = slots.iter().map(|slot| slot.bit::<S>(buffer)).collect();
+
+        // Costs are clamped, so that the total cost of any path stays finite.
+        let limit = f64::MAX / (cover.len() as f64 + 1.0);
+        let costs: Vec<f64> = slots
+            .iter()
+            .map(|slot| (trellis.costs)(slot.index).max(0.0).min(limit))
+            .collect();
+
+        let stego = trellis::embed(&mut trellis.keystream.clone(), width, &cover, &costs, data);
+
+        let mut cost = 0.0;
+        for (position, slot) in slots.iter().enumerate() {
+            if stego[position] != cover[position] {
+                slot.flip::<S>(buffer, self.matching.as_mut());
+                changed_bits += 1;
+                cost += costs[position];
+            }
+        }
+
+        Ok(TrellisStatistics {
+            width: width as u32,
+            embedded_bits: (self.prefix_len + data.len()) as u64,
+            cover_bits: (self.prefix_len + slots.len()) as u64,
+            changed_bits,
+            cost,
+        })
+    }
+}
+
+/// A cover bit selected by the pattern.
+struct Slot {
+    index: usize,
+    pow: usize,
+    mask: u32,
+}
+
+impl Slot {
+    /// Returns the bit within the buffered cover.
+    fn bit<S: Sample>(&self, buffer: &[u8]) -> u8 {
+        (S::read(&buffer[self.index * S::LEN..]) >> self.pow) as u8 & 1
+    }
+
+    /// Flips the bit within the buffered cover, by LSB matching if the keystream is supplied
+    /// and the other bits selected by the pattern stay unchanged.
+    fn flip<S: Sample>(&self, buffer: &mut [u8], matching: Option<&mut Keystream>) {
+        let sample = &mut buffer[self.index * S::LEN..][..S::LEN];
+
+        let word = S::read(sample);
+        let flipped = word ^ 1 << self.pow;
+
+        let word = match matching {
+            None => flipped,
+            Some(keystream) => {
+                let bit = (flipped >> self.pow) as u8 & 1;
+                let matched = match_bit::<S>(word, self.pow, bit, keystream);
+
+                if (matched ^ flipped) & self.mask == 0 {
+                    matched
+                } else {
+                    flipped
+                }
+            }
+        };
+
+        S::write(word, sample);
+    }
+}
+
+/// Changes the bit of the word at the supplied position to the supplied one by adding or
+/// subtracting the value of the bit, which may carry into or borrow from higher bits.
+fn match_bit<S: Sample>(word: u32, pow: usize, bit: u8, keystream: &mut Keystream) -> u32 {
+    if (word >> pow) & 1 == u32::from(bit) {
+        return word;
+    }
+
+    if S::FLOAT {
+        return match_float_bit(word, pow, keystream);
+    }
+
+    // Flipping the sign bit maps two's complement onto an unsigned range of the same order.
+    let bits = 8 * S::LEN as u32;
+    let sign = if S::SIGNED { 1 << (bits - 1) } else { 0 };
+    let max = u32::MAX >> (32 - bits);
+
+    let value = word ^ sign;
+    let step = 1 << pow;
+
+    let is_addition = match (value <= max - step, value >= step) {
+        (true, true) => keystream.next_u64() & 1 == 0,
+        (can_add, _) => can_add,
+    };
+
+    let value = if is_addition {
+        value + step
+    } else {
+        value - step
+    };
+
+    value ^ sign
+}
+
+/// Changes the bit of the IEEE 754 representation at the supplied position by stepping the
+/// magnitude, so that finite samples stay finite and keep their sign.
+///
+/// The sign bit, non-finite samples, and magnitudes that can step in neither direction
+/// without becoming infinite or NaN have the bit replaced instead.
+fn match_float_bit(word: u32, pow: usize, keystream: &mut Keystream) -> u32 {
+    const SIGN: u32 = 1 << 31;
+    const MAX_FINITE: u32 = 0x7f7f_ffff;
+
+    let magnitude = word & !SIGN;
+    let step = 1 << pow;
+
+    if step == SIGN || magnitude > MAX_FINITE {
+        return word ^ step;
+    }
+
+    let is_addition = match (magnitude <= MAX_FINITE - step, magnitude >= step) {
+        (true, true) => keystream.next_u64() & 1 == 0,
+        (false, false) => return word ^ step,
+        (can_add, _) => can_add,
+    };
+
+    let magnitude = if is_addition {
+        magnitude + step
+    } else {
+        magnitude - step
+    };
+
+    word & SIGN | magnitude
 }
 
 impl<M, W> Conceal for &mut Carrier<M, W>
 where
-    M: FnMut(usize) -> Option<u8>,
+    M: Pattern,
     W: Write,
 {
-    type Err = io::Error;
+    type Err = Error;
 
-    fn conceal<P: Read, C: Read>(self, payload: P, cover: C) -> io::Result<usize> {
-        let len_bytes = self
-            .len
-            .map(|len| len.to_be_bytes().to_vec())
-            .unwrap_or_default();
+    fn conceal<P: Read, C: Read>(self, payload: P, cover: C) -> Result<usize, Error> {
+        self.statistics = None;
+        self.report = None;
 
-        let mut cover = BufReader::new(cover);
+        let (header, len_bytes, len) = match self.framing {
+            Framing::None => (None, Vec::new(), None),
+            Framing::Length(len) => (None, len.to_be_bytes().to_vec(), Some(len)),
+            Framing::Header(header) => {
+                let header = self.describe(header);
+                (Some(header), Vec::new(), Some(header.len))
+            }
+        };
 
-        let mut payload_bytes = len_bytes.chain(BufReader::new(payload)).bytes();
-        let mut payload_byte = match payload_bytes.next() {
-            Some(byte) => byte?,
-            _ => return Ok(io::copy(&mut cover, &mut self.writer)? as usize),
+        // Codes conceal the header separately, once their parameters are known.
+        let prefix = match header {
+            Some(header) if self.code.is_none() => header.encode(),
+            _ => Vec::new(),
         };
 
-        let mut payload_bytes_written = 0u64;
+        let overhead = (len_bytes.len() + self.checksum.map_or(0, Checksum::size)) as u64;
+        let needed_bits = len.map(|len| self.needed_bits(len));
 
-        let mut bytes_written = 0;
-        let mut bit_count = 0u8;
+        let payload = BufReader::new(payload).take(len.unwrap_or(u64::MAX));
+        let payload = Checksummed::new(self.checksum, payload.bytes());
+        let mut bits = Bits::new(Encoder::with_prefix(
+            prefix,
+            self.fec,
+            len_bytes.into_iter().map(Ok).chain(payload),
+        ));
 
-        for (index, cover_byte) in cover.by_ref().bytes().enumerate() {
-            let Some(mask) = (self.pattern)(index) else {
-                break;
-            };
+        let mut cover = BufReader::new(cover);
 
-            let mut package_byte = cover_byte? & !mask;
-            for pow in bits::Ones::from(mask) {
-                package_byte |= (payload_byte & 1) << pow;
-                payload_byte >>= 1;
-                bit_count += 1;
+        if self.code.is_some() {
+            let mut data = Vec::new();
+            while let Some(bit) = bits.next_bit()? {
+                data.push(bit);
+            }
 
-                if bit_count < 8 {
-                    continue;
-                }
+            check_complete(&mut bits, false, len, overhead, needed_bits)?;
 
-                payload_bytes_written += 1;
+            let mut buffer = Vec::new();
+            cover.read_to_end(&mut buffer)?;
 
-                if self
-                    .len
-                    .is_some_and(|n| payload_bytes_written >= 8 && payload_bytes_written - 8 >= n)
-                {
-                    break;
+            let mut coded = self.coded(header, buffer.len() / M::Sample::LEN);
+            let words = self.is_reporting.then(|| coded.words::<M::Sample>(&buffer));
+
+            let statistics = match &mut self.code {
+                Some(Code::Trellis(trellis)) => Statistics::Trellis(
+                    coded.embed_trellis::<M::Sample>(trellis, &data, &mut buffer)?,
+                ),
+                _ => Statistics::Matrix(coded.embed_matrix::<M::Sample>(&data, &mut buffer)?),
+            };
+
+            let (embedded_bits, cover_bits) = match statistics {
+                Statistics::Matrix(statistics) => (statistics.embedded_bits, statistics.cover_bits),
+                Statistics::Trellis(statistics) => {
+                    (statistics.embedded_bits, statistics.cover_bits)
                 }
+            };
+
+            self.statistics = Some(statistics);
+            self.report = words
+                .map(|words| coded.report::<M::Sample>(embedded_bits, cover_bits, &words, &buffer));
+
+            self.writer.write_all(&buffer)?;
+            self.writer.flush()?;
+
+            return Ok(buffer.len());
+        }
 
-                payload_byte = match payload_bytes.next() {
-                    Some(byte) => byte?,
-                    None => break,
+        let mut pattern = Skipping::new(&mut self.pattern, self.regions.as_ref());
+        let mut matching = self.matching.clone();
+        let mut report = Report::new::<M::Sample>();
+
+        // Reporting carriers keep going after the data to count the bits of the whole cover.
+        let is_reporting = self.is_reporting;
+        let mut available_bits = 0u64;
+        let mut bytes_written = 0;
+
+        let Some(keystream) = &self.order else {
+            let mut sample = Vec::with_capacity(M::Sample::LEN);
+            let mut index = 0;
+            let mut is_pattern_exhausted = false;
+
+            while is_reporting || !bits.is_exhausted()? {
+                sample.clear();
+                cover
+                    .by_ref()
+                    .take(M::Sample::LEN as u64)
+                    .read_to_end(&mut sample)?;
+
+                // The pattern is not queried past the end of the cover.
+                let is_complete = sample.len() == M::Sample::LEN;
+                let mask = if is_complete {
+                    pattern.mask(index)
+                } else {
+                    None
+                };
+
+                let Some(mask) = mask else {
+                    is_pattern_exhausted = is_complete;
+                    self.writer.write_all(&sample)?;
+                    bytes_written += sample.len();
+                    break;
                 };
 
-                bit_count = 0;
+                available_bits += u64::from(sample::mask_bits::<M::Sample>(mask).count_ones());
+                embed::<M::Sample, _>(
+                    index,
+                    mask,
+                    &mut sample,
+                    &mut bits,
+                    matching.as_mut(),
+                    &mut report,
+                )?;
+
+                self.writer.write_all(&sample)?;
+                bytes_written += sample.len();
+
+                index += 1;
             }
 
-            bytes_written += self.writer.write(&[package_byte])?;
+            check_complete(&mut bits, is_pattern_exhausted, len, overhead, needed_bits)?;
+
+            bytes_written += io::copy(&mut cover, &mut self.writer)? as usize;
+
+            self.writer.flush()?;
 
-            if bit_count == 8 {
+            report.embedded_bits = bits.position();
+            report.available_bits = available_bits;
+            self.report = is_reporting.then_some(report);
+
+            return Ok(bytes_written);
+        };
+
+        let mut buffer = Vec::new();
+        cover.read_to_end(&mut buffer)?;
+
+        let mut is_pattern_exhausted = false;
+        let sample_len = M::Sample::LEN;
+        for index in Permutation::new(keystream.clone(), buffer.len() / sample_len) {
+            if !is_reporting && bits.is_exhausted()? {
                 break;
             }
-        }
 
-        if match self.len {
-            Some(n) => payload_bytes_written < 8 || payload_bytes_written - 8 < n,
-            None => payload_bytes.next().is_some(),
-        } {
-            return Err(io::Error::from(io::ErrorKind::WriteZero));
+            let Some(mask) = pattern.mask(index) else {
+                is_pattern_exhausted = true;
+                break;
+            };
+
+            available_bits += u64::from(sample::mask_bits::<M::Sample>(mask).count_ones());
+
+            let sample = &mut buffer[index * sample_len..][..sample_len];
+            embed::<M::Sample, _>(
+                index,
+                mask,
+                sample,
+                &mut bits,
+                matching.as_mut(),
+                &mut report,
+            )?;
         }
 
-        bytes_written += io::copy(&mut cover, &mut self.writer)? as usize;
+        check_complete(&mut bits, is_pattern_exhausted, len, overhead, needed_bits)?;
+
+        self.writer.write_all(&buffer)?;
+        bytes_written += buffer.len();
 
         self.writer.flush()?;
 
+        report.embedded_bits = bits.position();
+        report.available_bits = available_bits;
+        self.report = is_reporting.then_some(report);
+
         Ok(bytes_written)
     }
 }
+
+/// Checks that the whole payload was concealed.
+///
+/// The number of needed bits is reported as supplied if the payload length is known, and
+/// as a lower bound otherwise, so that an unbounded payload is not read to the end.
+fn check_complete<I>(
+    bits: &mut Bits<Encoder<I>>,
+    is_pattern_exhausted: bool,
+    len: Option<u64>,
+    overhead: u64,
+    needed_bits: Option<u64>,
+) -> Result<(), Error>
+where
+    I: Iterator<Item = io::Result<u8>>,
+{
+    if !bits.is_exhausted()? {
+        if is_pattern_exhausted {
+            return Err(Error::PatternExhausted);
+        }
+
+        let available_bits = bits.position();
+        return Err(Error::InsufficientCapacity {
+            needed_bits: needed_bits.unwrap_or(available_bits + bits.remaining()),
+            available_bits,
+        });
+    }
+
+    let consumed = bits.get_ref().consumed();
+    if let Some(len) = len.filter(|&len| consumed < len + overhead) {
+        return Err(Error::TruncatedPayload {
+            expected: len,
+            actual: consumed.saturating_sub(overhead),
+        });
+    }
+
+    Ok(())
+}
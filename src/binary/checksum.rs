@@ -0,0 +1,202 @@
+use crate::Error;
+use sha2::{Digest, Sha256};
+use std::{error, fmt, io};
+use xxhash_rust::xxh64::Xxh64;
+
+/// A checksum appended to the payload in order to verify it upon extraction.
+///
+/// The checksum is computed over the payload only and is concealed right after it. It allows
+/// the receiver to tell a recovered message apart from garbage produced by a wrong pattern
+/// or a corrupted package.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary::{self, Checksum}, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut carrier = binary::Carrier::with_embedded_len(
+///     payload.len(),
+///     |_| Some(0b1),
+///     File::create("package")?,
+/// )
+/// .checksum(Checksum::Crc32);
+///
+/// carrier.conceal(payload.as_slice(), File::open("cover")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Checksum {
+    /// The 32-bit CRC checksum (ISO-HDLC), which takes 4 bytes.
+    Crc32,
+
+    /// The 64-bit xxHash checksum with a seed of zero, which takes 8 bytes.
+    XxHash64,
+
+    /// The SHA-256 digest, which takes 32 bytes.
+    Sha256,
+}
+
+impl Checksum {
+    /// Returns the size of the checksum in bytes.
+    #[must_use]
+    pub fn size(self) -> usize {
+        match self {
+            Self::Crc32 => 4,
+            Self::XxHash64 => 8,
+            Self::Sha256 => 32,
+        }
+    }
+
+    fn hasher(self) -> Hasher {
+        match self {
+            Self::Crc32 => Hasher::Crc32(crc32fast::Hasher::new()),
+            Self::XxHash64 => Hasher::XxHash64(Xxh64::new(0)),
+            Self::Sha256 => Hasher::Sha256(Sha256::new()),
+        }
+    }
+}
+
+enum Hasher {
+    Crc32(crc32fast::Hasher),
+    XxHash64(Xxh64),
+    Sha256(Sha256),
+}
+
+impl Hasher {
+    fn update(&mut self, bytes: &[u8]) {
+        match self {
+            Self::Crc32(hasher) => hasher.update(bytes),
+            Self::XxHash64(hasher) => hasher.update(bytes),
+            Self::Sha256(hasher) => hasher.update(bytes),
+        }
+    }
+
+    fn finalize(self) -> Vec<u8> {
+        match self {
+            Self::Crc32(hasher) => hasher.finalize().to_be_bytes().to_vec(),
+            Self::XxHash64(hasher) => hasher.digest().to_be_bytes().to_vec(),
+            Self::Sha256(hasher) => hasher.finalize().to_vec(),
+        }
+    }
+}
+
+/// The reason the checksum of a revealed message does not match, either because the
+/// pattern is wrong or the package is corrupted.
+///
+/// It is returned wrapped in [`Error::Verification`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerificationError {
+    /// The checksum concealed in the package.
+    pub expected: Vec<u8>,
+
+    /// The checksum of the revealed message.
+    pub actual: Vec<u8>,
+}
+
+impl fmt::Display for VerificationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("checksum of the revealed message does not match")
+    }
+}
+
+impl error::Error for VerificationError {}
+
+/// An iterator adapter that yields the checksum of the bytes after the bytes themselves.
+pub(super) struct Checksummed<I> {
+    bytes: I,
+    hasher: Option<Hasher>,
+    digest: Option<std::vec::IntoIter<u8>>,
+}
+
+impl<I> Checksummed<I> {
+    pub(super) fn new(checksum: Option<Checksum>, bytes: I) -> Self {
+        Self {
+            bytes,
+            hasher: checksum.map(Checksum::hasher),
+            digest: None,
+        }
+    }
+}
+
+impl<I> Iterator for Checksummed<I>
+where
+    I: Iterator<Item = io::Result<u8>>,
+{
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(digest) = self.digest.as_mut() {
+            return digest.next().map(Ok);
+        }
+
+        match self.bytes.next() {
+            Some(Ok(byte)) => {
+                if let Some(hasher) = self.hasher.as_mut() {
+                    hasher.update(&[byte]);
+                }
+
+                Some(Ok(byte))
+            }
+            Some(Err(error)) => Some(Err(error)),
+            None => {
+                let digest = self.hasher.take()?.finalize();
+                self.digest.insert(digest.into_iter()).next().map(Ok)
+            }
+        }
+    }
+}
+
+/// A verifier that accepts the revealed message followed by its checksum.
+pub(super) struct Verifier {
+    hasher: Option<Hasher>,
+    expected: Vec<u8>,
+    len: usize,
+}
+
+impl Verifier {
+    pub(super) fn new(checksum: Checksum) -> Self {
+        Self {
+            hasher: Some(checksum.hasher()),
+            expected: Vec::with_capacity(checksum.size()),
+            len: checksum.size(),
+        }
+    }
+
+    /// Accepts the next byte of the message.
+    pub(super) fn update(&mut self, byte: u8) {
+        if let Some(hasher) = self.hasher.as_mut() {
+            hasher.update(&[byte]);
+        }
+    }
+
+    /// Accepts the next byte of the checksum, returning whether the checksum is complete.
+    pub(super) fn push(&mut self, byte: u8) -> Result<bool, Error> {
+        self.expected.push(byte);
+
+        if self.expected.len() < self.len {
+            return Ok(false);
+        }
+
+        let actual = self.hasher.take().map(Hasher::finalize).unwrap_or_default();
+
+        if actual != self.expected {
+            return Err(Error::Verification(VerificationError {
+                expected: std::mem::take(&mut self.expected),
+                actual,
+            }));
+        }
+
+        Ok(true)
+    }
+
+    /// Returns the error to report if the message ends before its checksum is complete.
+    pub(super) fn into_error(self) -> Error {
+        Error::Verification(VerificationError {
+            expected: self.expected,
+            actual: self.hasher.map(Hasher::finalize).unwrap_or_default(),
+        })
+    }
+}
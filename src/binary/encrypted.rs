@@ -0,0 +1,389 @@
+use crate::{
+    binary::{
+        Carrier, Fec, MatrixStatistics, Package, Pattern, Regions, Report, TrellisStatistics,
+    },
+    Conceal, Error, Reveal,
+};
+use chacha20poly1305::{
+    aead::{rand_core::RngCore, Aead, AeadCore, KeyInit, OsRng},
+    ChaCha20Poly1305, Key, Nonce,
+};
+use sha2::Sha256;
+use std::{
+    error, fmt,
+    io::{Read, Write},
+};
+
+const SALT_LEN: usize = 16;
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+const ROUNDS: u32 = 100_000;
+
+fn derive_key(password: &[u8], salt: &[u8]) -> Key {
+    let mut key = Key::default();
+    pbkdf2::pbkdf2_hmac::<Sha256>(password, salt, ROUNDS, &mut key);
+    key
+}
+
+/// The reason an [`EncryptedPackage`] does not reveal its message, which is that the
+/// message fails to authenticate.
+///
+/// It is returned wrapped in [`Error::Authentication`] and signals that either the password
+/// or the pattern is wrong, or that the package was tampered with.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary, Error, Reveal};
+/// use std::fs::File;
+///
+/// let mut package = binary::EncryptedPackage::new(
+///     "a wrong password",
+///     |_| Some(0b1),
+///     File::open("package")?,
+/// );
+///
+/// assert!(matches!(
+///     package.reveal(Vec::new()),
+///     Err(Error::Authentication(binary::AuthenticationError)),
+/// ));
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AuthenticationError;
+
+impl fmt::Display for AuthenticationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("message authentication failed")
+    }
+}
+
+impl error::Error for AuthenticationError {}
+
+/// A binary carrier that encrypts the payload before concealing it.
+///
+/// The payload is encrypted with ChaCha20-Poly1305 under a key derived from the password
+/// with PBKDF2-HMAC-SHA256 and a random salt. The salt, the nonce, and the ciphertext are
+/// then concealed by the underlying [`Carrier`] behind a [`Header`][crate::binary::Header]
+/// with the encryption flag set.
+///
+/// The payload is read into memory in its entirety before being encrypted.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary, Conceal};
+/// use std::fs::File;
+///
+/// let mut carrier = binary::EncryptedCarrier::new(
+///     "a password",
+///     |i| Some(1u8 << (i % 3)),
+///     File::create("package")?,
+/// );
+///
+/// carrier.conceal(
+///     b"a very secret message".as_slice(),
+///     File::open("cover")?,
+/// )?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+pub struct EncryptedCarrier<P, W>
+where
+    P: Pattern,
+    W: Write,
+{
+    carrier: Carrier<P, W>,
+    password: Vec<u8>,
+}
+
+impl<P, W> fmt::Debug for EncryptedCarrier<P, W>
+where
+    P: Pattern + fmt::Debug,
+    W: Write + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedCarrier")
+            .field("carrier", &self.carrier)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P, W> EncryptedCarrier<P, W>
+where
+    P: Pattern,
+    W: Write,
+{
+    /// Creates a new [`EncryptedCarrier<P, W>`] with the supplied password, pattern, and writer.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut carrier = binary::EncryptedCarrier::new(
+    ///     "a password",
+    ///     |_| Some(0b11),
+    ///     File::create("package")?,
+    /// );
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn new(password: impl AsRef<[u8]>, pattern: P, writer: W) -> Self {
+        Self {
+            carrier: Carrier::with_header(0, pattern, writer),
+            password: password.as_ref().to_vec(),
+        }
+    }
+
+    /// Makes the carrier scatter the payload over a pseudo-random permutation of cover bytes.
+    ///
+    /// See [`Carrier::permuted`] for details.
+    #[must_use]
+    pub fn permuted(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.carrier = self.carrier.permuted(seed);
+        self
+    }
+
+    /// Makes the carrier change cover samples by LSB matching instead of bit replacement.
+    ///
+    /// See [`Carrier::lsb_matching`] for details.
+    #[must_use]
+    pub fn lsb_matching(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.carrier = self.carrier.lsb_matching(seed);
+        self
+    }
+
+    /// Makes the carrier conceal the data by matrix embedding, which changes fewer cover bits
+    /// than bit replacement.
+    ///
+    /// See [`Carrier::matrix_embedding`] for details.
+    #[must_use]
+    pub fn matrix_embedding(mut self) -> Self {
+        self.carrier = self.carrier.matrix_embedding();
+        self
+    }
+
+    /// Returns the statistics of the last matrix embedding, if any.
+    ///
+    /// See [`Carrier::matrix_statistics`] for details.
+    #[must_use]
+    pub fn matrix_statistics(&self) -> Option<&MatrixStatistics> {
+        self.carrier.matrix_statistics()
+    }
+
+    /// Makes the carrier conceal the data by syndrome-trellis coding, which minimizes the
+    /// total cost of the changed cover bits.
+    ///
+    /// See [`Carrier::syndrome_trellis`] for details.
+    #[must_use]
+    pub fn syndrome_trellis<F>(mut self, seed: impl AsRef<[u8]>, costs: F) -> Self
+    where
+        F: FnMut(usize) -> f64 + Send + Sync + 'static,
+    {
+        self.carrier = self.carrier.syndrome_trellis(seed, costs);
+        self
+    }
+
+    /// Returns the statistics of the last syndrome-trellis embedding, if any.
+    ///
+    /// See [`Carrier::trellis_statistics`] for details.
+    #[must_use]
+    pub fn trellis_statistics(&self) -> Option<&TrellisStatistics> {
+        self.carrier.trellis_statistics()
+    }
+
+    /// Makes the carrier report how the cover was changed by every
+    /// [`conceal`][crate::Conceal::conceal].
+    ///
+    /// See [`Carrier::reporting`] for details.
+    #[must_use]
+    pub fn reporting(mut self) -> Self {
+        self.carrier = self.carrier.reporting();
+        self
+    }
+
+    /// Returns the report of the last embedding, if any.
+    ///
+    /// See [`Carrier::report`] for details.
+    #[must_use]
+    pub fn report(&self) -> Option<&Report> {
+        self.carrier.report()
+    }
+
+    /// Makes the carrier leave the cover bytes within the supplied regions unchanged.
+    ///
+    /// See [`Carrier::skip_regions`] for details.
+    #[must_use]
+    pub fn skip_regions(mut self, regions: Regions) -> Self {
+        self.carrier = self.carrier.skip_regions(regions);
+        self
+    }
+
+    /// Makes the carrier encode the concealed data with the supplied forward error correction
+    /// code.
+    ///
+    /// See [`Carrier::fec`] for details.
+    #[must_use]
+    pub fn fec(mut self, fec: Fec) -> Self {
+        self.carrier = self.carrier.fec(fec);
+        self
+    }
+}
+
+impl<M, W> Conceal for &mut EncryptedCarrier<M, W>
+where
+    M: Pattern,
+    W: Write,
+{
+    type Err = Error;
+
+    fn conceal<P: Read, C: Read>(self, mut payload: P, cover: C) -> Result<usize, Error> {
+        let mut plaintext = Vec::new();
+        payload.read_to_end(&mut plaintext)?;
+
+        let mut salt = [0; SALT_LEN];
+        OsRng.fill_bytes(&mut salt);
+
+        let nonce = ChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+        let ciphertext = ChaCha20Poly1305::new(&derive_key(&self.password, &salt))
+            .encrypt(&nonce, plaintext.as_slice())
+            // ChaCha20-Poly1305 only rejects plaintexts of 256 GiB or more, at least 2 TiB of
+            // cover at one bit per byte, so the message would never fit anyway.
+            .expect("plaintext exceeds the ChaCha20-Poly1305 limit");
+
+        let sealed = [salt.as_slice(), nonce.as_slice(), ciphertext.as_slice()].concat();
+
+        self.carrier.set_encrypted_len(sealed.len());
+        self.carrier.conceal(sealed.as_slice(), cover)
+    }
+}
+
+/// A binary package that decrypts a message concealed by an [`EncryptedCarrier`].
+///
+/// The message is only written to the output if it authenticates; otherwise,
+/// [`Error::Authentication`] is returned. The error correction code, if any, is read from
+/// the header.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary, Reveal};
+/// use std::fs::File;
+///
+/// let mut package = binary::EncryptedPackage::new(
+///     "a password",
+///     |i| Some(1u8 << (i % 3)),
+///     File::open("package")?,
+/// );
+///
+/// package.reveal(File::create("message")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+pub struct EncryptedPackage<P, R>
+where
+    P: Pattern,
+    R: Read,
+{
+    package: Package<P, R>,
+    password: Vec<u8>,
+}
+
+impl<P, R> fmt::Debug for EncryptedPackage<P, R>
+where
+    P: Pattern + fmt::Debug,
+    R: Read + fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EncryptedPackage")
+            .field("package", &self.package)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<P, R> EncryptedPackage<P, R>
+where
+    P: Pattern,
+    R: Read,
+{
+    /// Creates a new [`EncryptedPackage<P, R>`] with the supplied password, pattern, and reader.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::EncryptedPackage::new(
+    ///     "a password",
+    ///     |_| Some(0b11),
+    ///     File::open("package")?,
+    /// );
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn new(password: impl AsRef<[u8]>, pattern: P, reader: R) -> Self {
+        Self {
+            package: Package::with_header(pattern, reader),
+            password: password.as_ref().to_vec(),
+        }
+    }
+
+    /// Makes the package extract the message from a pseudo-random permutation of its bytes.
+    ///
+    /// See [`Package::permuted`] for details.
+    #[must_use]
+    pub fn permuted(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.package = self.package.permuted(seed);
+        self
+    }
+
+    /// Makes the package extract the message concealed by syndrome-trellis coding with the
+    /// supplied seed.
+    ///
+    /// See [`Package::syndrome_trellis`] for details.
+    #[must_use]
+    pub fn syndrome_trellis(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.package = self.package.syndrome_trellis(seed);
+        self
+    }
+
+    /// Makes the package ignore the bytes within the supplied regions.
+    ///
+    /// See [`Package::skip_regions`] for details.
+    #[must_use]
+    pub fn skip_regions(mut self, regions: Regions) -> Self {
+        self.package = self.package.skip_regions(regions);
+        self
+    }
+}
+
+impl<M, R> Reveal for &mut EncryptedPackage<M, R>
+where
+    M: Pattern,
+    R: Read,
+{
+    type Err = Error;
+
+    fn reveal<W: Write>(self, mut output: W) -> Result<usize, Error> {
+        let mut sealed = Vec::new();
+        self.package.reveal(&mut sealed)?;
+
+        if sealed.len() < SALT_LEN + NONCE_LEN + TAG_LEN {
+            return Err(AuthenticationError.into());
+        }
+
+        let (salt, sealed) = sealed.split_at(SALT_LEN);
+        let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+
+        let plaintext = ChaCha20Poly1305::new(&derive_key(&self.password, salt))
+            .decrypt(Nonce::from_slice(nonce), ciphertext)
+            .map_err(|_| AuthenticationError)?;
+
+        output.write_all(&plaintext)?;
+        output.flush()?;
+
+        Ok(plaintext.len())
+    }
+}
@@ -0,0 +1,508 @@
+use crate::Error;
+use std::{cmp::Ordering, collections::VecDeque, error, fmt, io};
+
+/// A forward error correction code applied to the concealed data.
+///
+/// The data is split into blocks which are encoded independently, and the last block is
+/// padded with zeros. The codes differ in their overhead and in how much corruption they
+/// can correct:
+///
+/// | Code                | Block                          | Corrects                           |
+/// |---------------------|--------------------------------|------------------------------------|
+/// | [`Fec::Repetition`] | 1 → `n` bytes                  | less than `n / 2` flips of each bit |
+/// | [`Fec::Hamming`]    | 4 → 7 bytes                    | 1 bit in every 7 bits              |
+/// | [`Fec::ReedSolomon`] | `data` → `data + parity` bytes | `parity / 2` bytes per block       |
+///
+/// Only [`Fec::ReedSolomon`] and [`Fec::Repetition`] with an even number of copies detect
+/// corruption they cannot correct, in which case [`Error::Uncorrectable`] is returned.
+/// Other codes silently produce wrong data if corruption is too heavy.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary::{self, Fec}, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut carrier = binary::Carrier::with_embedded_len(
+///     payload.len(),
+///     |_| Some(0b1),
+///     File::create("package")?,
+/// )
+/// .fec(Fec::ReedSolomon { data: 32, parity: 8 });
+///
+/// carrier.conceal(payload.as_slice(), File::open("cover")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Fec {
+    /// Every byte is repeated `n` times and decoded by a bitwise majority vote.
+    Repetition(u8),
+
+    /// Every nibble is encoded as a Hamming(7,4) codeword.
+    Hamming,
+
+    /// Every `data` bytes are encoded as a Reed–Solomon codeword over GF(2⁸) with `parity`
+    /// parity bytes. The sum of `data` and `parity` may not exceed 255.
+    ReedSolomon {
+        /// The number of data bytes per block.
+        data: u8,
+
+        /// The number of parity bytes per block.
+        parity: u8,
+    },
+}
+
+impl Fec {
+    /// Returns the number of data and coded bytes per block.
+    fn block_len(self) -> (usize, usize) {
+        match self {
+            Self::Repetition(n) => (1, n as usize),
+            Self::Hamming => (4, 7),
+            Self::ReedSolomon { data, parity } => (data as usize, data as usize + parity as usize),
+        }
+    }
+
+    /// Returns the number of coded bytes the supplied number of data bytes is encoded into.
+    pub(super) fn encoded_len(self, len: u64) -> u64 {
+        let (data, coded) = self.block_len();
+        len.div_ceil(data as u64) * coded as u64
+    }
+
+    pub(super) fn validate(self) {
+        match self {
+            Self::Repetition(n) => assert!(n > 0, "repetition count must be positive"),
+            Self::Hamming => {}
+            Self::ReedSolomon { data, parity } => {
+                assert!(data > 0, "Reed-Solomon data length must be positive");
+                assert!(parity > 0, "Reed-Solomon parity length must be positive");
+                assert!(
+                    data as usize + parity as usize <= 255,
+                    "Reed-Solomon block length must not exceed 255"
+                );
+            }
+        }
+    }
+
+    fn encode_block(self, block: &[u8], output: &mut VecDeque<u8>) {
+        match self {
+            Self::Repetition(n) => {
+                output.extend(std::iter::repeat_n(block[0], n as usize));
+            }
+            Self::Hamming => {
+                let mut bits = 0u64;
+                for (index, nibble) in block
+                    .iter()
+                    .flat_map(|byte| [byte & 0xf, byte >> 4])
+                    .enumerate()
+                {
+                    bits |= u64::from(hamming::encode(nibble)) << (index * 7);
+                }
+
+                output.extend(&bits.to_le_bytes()[..7]);
+            }
+            Self::ReedSolomon { parity, .. } => {
+                output.extend(block);
+                output.extend(reed_solomon::parity(block, parity as usize));
+            }
+        }
+    }
+
+    fn decode_block(self, block: &[u8], output: &mut Vec<u8>) -> Result<(), UncorrectableError> {
+        match self {
+            Self::Repetition(n) => {
+                let mut byte = 0;
+                for pow in 0..8 {
+                    let ones = block.iter().filter(|&&copy| copy & (1 << pow) != 0).count();
+                    match (2 * ones).cmp(&(n as usize)) {
+                        Ordering::Greater => byte |= 1 << pow,
+                        Ordering::Equal => return Err(UncorrectableError),
+                        Ordering::Less => {}
+                    }
+                }
+
+                output.push(byte);
+            }
+            Self::Hamming => {
+                let mut bytes = [0; 8];
+                bytes[..7].copy_from_slice(block);
+                let bits = u64::from_le_bytes(bytes);
+
+                output.extend((0..4).map(|index| {
+                    let low = hamming::decode((bits >> (index * 14)) as u8 & 0x7f);
+                    let high = hamming::decode((bits >> (index * 14 + 7)) as u8 & 0x7f);
+                    low | high << 4
+                }));
+            }
+            Self::ReedSolomon { data, parity } => {
+                let mut block = block.to_vec();
+                reed_solomon::correct(&mut block, parity as usize)?;
+                output.extend(&block[..data as usize]);
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// The reason the concealed data cannot be decoded, which is that it is too corrupted to
+/// be corrected.
+///
+/// It is returned wrapped in [`Error::Uncorrectable`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct UncorrectableError;
+
+impl fmt::Display for UncorrectableError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("concealed data is too corrupted to be corrected")
+    }
+}
+
+impl error::Error for UncorrectableError {}
+
+/// An iterator adapter encoding the bytes of the underlying iterator.
+pub(super) struct Encoder<I> {
+    fec: Option<Fec>,
+    bytes: I,
+    block: Vec<u8>,
+    encoded: VecDeque<u8>,
+    consumed: u64,
+}
+
+impl<I> Encoder<I> {
+    /// Creates a new encoder that yields the prefix as is before the encoded bytes.
+    pub(super) fn with_prefix(prefix: Vec<u8>, fec: Option<Fec>, bytes: I) -> Self {
+        Self {
+            fec,
+            bytes,
+            block: Vec::new(),
+            encoded: prefix.into(),
+            consumed: 0,
+        }
+    }
+
+    /// Returns how many bytes were consumed from the underlying iterator.
+    pub(super) fn consumed(&self) -> u64 {
+        self.consumed
+    }
+}
+
+impl<I> Iterator for Encoder<I>
+where
+    I: Iterator<Item = io::Result<u8>>,
+{
+    type Item = io::Result<u8>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(byte) = self.encoded.pop_front() {
+            return Some(Ok(byte));
+        }
+
+        let Some(fec) = self.fec else {
+            let byte = self.bytes.next()?;
+            self.consumed += 1;
+            return Some(byte);
+        };
+
+        let (data, _) = fec.block_len();
+        while self.block.len() < data {
+            match self.bytes.next() {
+                Some(Ok(byte)) => {
+                    self.block.push(byte);
+                    self.consumed += 1;
+                }
+                Some(Err(error)) => return Some(Err(error)),
+                None => break,
+            }
+        }
+
+        if self.block.is_empty() {
+            return None;
+        }
+
+        self.block.resize(data, 0);
+        fec.encode_block(&self.block, &mut self.encoded);
+        self.block.clear();
+
+        self.encoded.pop_front().map(Ok)
+    }
+}
+
+/// A decoder that accepts coded bytes one by one and decodes them block by block.
+pub(super) struct Decoder {
+    fec: Option<Fec>,
+    block: Vec<u8>,
+    decoded: Vec<u8>,
+}
+
+impl Decoder {
+    pub(super) fn new(fec: Option<Fec>) -> Self {
+        Self {
+            fec,
+            block: Vec::new(),
+            decoded: Vec::new(),
+        }
+    }
+
+    /// Accepts the next coded byte and returns the bytes decoded so far, if any.
+    pub(super) fn push(&mut self, byte: u8) -> Result<&[u8], Error> {
+        self.decoded.clear();
+
+        let Some(fec) = self.fec else {
+            self.decoded.push(byte);
+            return Ok(&self.decoded);
+        };
+
+        self.block.push(byte);
+
+        if self.block.len() == fec.block_len().1 {
+            fec.decode_block(&self.block, &mut self.decoded)?;
+            self.block.clear();
+        }
+
+        Ok(&self.decoded)
+    }
+}
+
+mod hamming {
+    /// Encodes a nibble as a codeword with bits `p1 p2 d1 p3 d2 d3 d4`, least significant first.
+    pub(super) fn encode(nibble: u8) -> u8 {
+        let d = |pow: u8| (nibble >> pow) & 1;
+
+        let p1 = d(0) ^ d(1) ^ d(3);
+        let p2 = d(0) ^ d(2) ^ d(3);
+        let p3 = d(1) ^ d(2) ^ d(3);
+
+        p1 | p2 << 1 | d(0) << 2 | p3 << 3 | d(1) << 4 | d(2) << 5 | d(3) << 6
+    }
+
+    /// Decodes a codeword, correcting at most one flipped bit.
+    pub(super) fn decode(mut codeword: u8) -> u8 {
+        let syndrome = (1..=7)
+            .filter(|position| codeword & (1 << (position - 1)) != 0)
+            .fold(0, |syndrome, position| syndrome ^ position);
+
+        if syndrome != 0 {
+            codeword ^= 1 << (syndrome - 1);
+        }
+
+        let c = |pow: u8| (codeword >> pow) & 1;
+        c(2) | c(4) << 1 | c(5) << 2 | c(6) << 3
+    }
+}
+
+mod reed_solomon {
+    use super::UncorrectableError;
+
+    const fn tables() -> ([u8; 512], [u8; 256]) {
+        let mut exp = [0; 512];
+        let mut log = [0; 256];
+
+        let mut x = 1u16;
+        let mut i = 0;
+        while i < 255 {
+            exp[i] = x as u8;
+            log[x as usize] = i as u8;
+
+            x <<= 1;
+            if x & 0x100 != 0 {
+                x ^= 0x11d;
+            }
+
+            i += 1;
+        }
+
+        while i < 512 {
+            exp[i] = exp[i - 255];
+            i += 1;
+        }
+
+        (exp, log)
+    }
+
+    const EXP: [u8; 512] = tables().0;
+    const LOG: [u8; 256] = tables().1;
+
+    fn mul(a: u8, b: u8) -> u8 {
+        if a == 0 || b == 0 {
+            return 0;
+        }
+
+        EXP[LOG[a as usize] as usize + LOG[b as usize] as usize]
+    }
+
+    fn div(a: u8, b: u8) -> u8 {
+        assert!(b != 0, "division by zero");
+
+        if a == 0 {
+            return 0;
+        }
+
+        EXP[(LOG[a as usize] as usize + 255 - LOG[b as usize] as usize) % 255]
+    }
+
+    fn pow(x: u8, power: i32) -> u8 {
+        EXP[(LOG[x as usize] as i32 * power).rem_euclid(255) as usize]
+    }
+
+    fn inverse(x: u8) -> u8 {
+        div(1, x)
+    }
+
+    /// Polynomials are stored with the highest degree coefficient first.
+    fn poly_scale(p: &[u8], x: u8) -> Vec<u8> {
+        p.iter().map(|&c| mul(c, x)).collect()
+    }
+
+    fn poly_add(p: &[u8], q: &[u8]) -> Vec<u8> {
+        let len = p.len().max(q.len());
+        let mut r = vec![0; len];
+
+        for (i, &c) in p.iter().enumerate() {
+            r[i + len - p.len()] = c;
+        }
+
+        for (i, &c) in q.iter().enumerate() {
+            r[i + len - q.len()] ^= c;
+        }
+
+        r
+    }
+
+    fn poly_mul(p: &[u8], q: &[u8]) -> Vec<u8> {
+        let mut r = vec![0; p.len() + q.len() - 1];
+
+        for (j, &b) in q.iter().enumerate() {
+            for (i, &a) in p.iter().enumerate() {
+                r[i + j] ^= mul(a, b);
+            }
+        }
+
+        r
+    }
+
+    fn poly_eval(p: &[u8], x: u8) -> u8 {
+        p.iter().fold(0, |y, &c| mul(y, x) ^ c)
+    }
+
+    fn generator(parity: usize) -> Vec<u8> {
+        (0..parity).fold(vec![1], |g, i| poly_mul(&g, &[1, pow(2, i as i32)]))
+    }
+
+    /// Returns the parity bytes of a systematic codeword for the given data.
+    pub(super) fn parity(data: &[u8], parity: usize) -> Vec<u8> {
+        let generator = generator(parity);
+
+        let mut remainder = data.to_vec();
+        remainder.resize(data.len() + parity, 0);
+
+        for i in 0..data.len() {
+            let coefficient = remainder[i];
+            if coefficient != 0 {
+                for (j, &g) in generator.iter().enumerate().skip(1) {
+                    remainder[i + j] ^= mul(g, coefficient);
+                }
+            }
+        }
+
+        remainder.split_off(data.len())
+    }
+
+    fn syndromes(codeword: &[u8], parity: usize) -> Vec<u8> {
+        (0..parity)
+            .map(|i| poly_eval(codeword, pow(2, i as i32)))
+            .collect()
+    }
+
+    /// Finds the error locator polynomial with the Berlekamp–Massey algorithm.
+    fn error_locator(syndromes: &[u8]) -> Vec<u8> {
+        let mut locator = vec![1];
+        let mut old_locator = vec![1];
+
+        for i in 0..syndromes.len() {
+            let mut delta = syndromes[i];
+            for j in 1..locator.len().min(i + 1) {
+                delta ^= mul(locator[locator.len() - 1 - j], syndromes[i - j]);
+            }
+
+            old_locator.push(0);
+
+            if delta != 0 {
+                if old_locator.len() > locator.len() {
+                    let new_locator = poly_scale(&old_locator, delta);
+                    old_locator = poly_scale(&locator, inverse(delta));
+                    locator = new_locator;
+                }
+
+                locator = poly_add(&locator, &poly_scale(&old_locator, delta));
+            }
+        }
+
+        let leading_zeros = locator.iter().take_while(|&&c| c == 0).count();
+        locator.split_off(leading_zeros)
+    }
+
+    /// Corrects the codeword in place, returning an error if it cannot be corrected.
+    pub(super) fn correct(codeword: &mut [u8], parity: usize) -> Result<(), UncorrectableError> {
+        let syndromes = syndromes(codeword, parity);
+        if syndromes.iter().all(|&s| s == 0) {
+            return Ok(());
+        }
+
+        let locator = error_locator(&syndromes);
+        let error_count = locator.len() - 1;
+        if error_count * 2 > parity {
+            return Err(UncorrectableError);
+        }
+
+        let len = codeword.len();
+
+        let reversed_locator: Vec<_> = locator.iter().rev().copied().collect();
+        let positions: Vec<_> = (0..len)
+            .filter(|&i| poly_eval(&reversed_locator, pow(2, i as i32)) == 0)
+            .map(|i| len - 1 - i)
+            .collect();
+
+        if positions.len() != error_count {
+            return Err(UncorrectableError);
+        }
+
+        let coefficient_positions: Vec<_> = positions.iter().map(|&p| len - 1 - p).collect();
+
+        let errata_locator = coefficient_positions.iter().fold(vec![1], |locator, &i| {
+            poly_mul(&locator, &poly_add(&[1], &[pow(2, i as i32), 0]))
+        });
+
+        // The syndromes are padded with a zero constant term, as the evaluator is defined
+        // in terms of the syndrome polynomial multiplied by `x`.
+        let reversed_syndromes: Vec<_> = syndromes.iter().rev().copied().chain([0]).collect();
+        let product = poly_mul(&reversed_syndromes, &errata_locator);
+        let evaluator = &product[product.len() - errata_locator.len()..];
+
+        let x: Vec<_> = coefficient_positions
+            .iter()
+            .map(|&p| pow(2, -(255 - p as i32)))
+            .collect();
+
+        for (i, &xi) in x.iter().enumerate() {
+            let xi_inverse = inverse(xi);
+
+            let locator_derivative = x
+                .iter()
+                .enumerate()
+                .filter(|&(j, _)| j != i)
+                .fold(1, |product, (_, &xj)| mul(product, 1 ^ mul(xi_inverse, xj)));
+
+            let y = mul(xi, poly_eval(evaluator, xi_inverse));
+
+            codeword[positions[i]] ^= div(y, locator_derivative);
+        }
+
+        if self::syndromes(codeword, parity).iter().any(|&s| s != 0) {
+            return Err(UncorrectableError);
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,495 @@
+use crate::binary::{matrix, Checksum, Fec};
+use std::{error, fmt};
+
+const MAGIC: [u8; 2] = [0xa5, 0xb5];
+const VERSION: u8 = 1;
+const COPIES: usize = 3;
+
+const COMPRESSED: u8 = 1 << 0;
+const ENCRYPTED: u8 = 1 << 1;
+const FEC_SHIFT: u8 = 2;
+const FEC_MASK: u8 = 0b11 << FEC_SHIFT;
+const CHECKSUM_SHIFT: u8 = 4;
+const CHECKSUM_MASK: u8 = 0b11 << CHECKSUM_SHIFT;
+const KNOWN_FLAGS: u8 = COMPRESSED | ENCRYPTED | FEC_MASK | CHECKSUM_MASK;
+
+/// The maximum length of the extension data, which bounds the size of the header.
+const MAX_EXTENSION_LEN: u64 = 255;
+
+const REGIONS_TAG: u8 = 1;
+const MATRIX_TAG: u8 = 2;
+const TRELLIS_TAG: u8 = 3;
+
+/// A self-describing header written in front of the concealed data.
+///
+/// The header is written by carriers created with [`Carrier::with_header`] and read by
+/// packages created with [`Package::with_header`], so that the receiver does not have to
+/// know the length of the message or the options it was concealed with.
+///
+/// It consists of the following fields:
+///
+/// 1. Two magic bytes, which allow telling whether a package contains a message at all,
+/// 2. The format version,
+/// 3. The flags describing the compression, encryption, error correction, and checksum,
+///    see below,
+/// 4. The parameters of the error correction code, if any,
+/// 5. The payload length as an unsigned LEB128 varint,
+/// 6. The length of the extension data as an unsigned LEB128 varint, followed by the data
+///    itself, which is reserved for backward compatible additions and skipped by readers
+///    that do not understand it. The data is a sequence of entries, each consisting of a
+///    tag byte, the length of the entry as an unsigned LEB128 varint, and the entry itself.
+///    The entry of tag `1` is the fingerprint of the skipped [`Regions`] in big-endian byte
+///    order, the entry of tag `2` is the single byte `k` of matrix embedding, and the entry
+///    of tag `3` is the width of syndrome-trellis coding in big-endian byte order. The
+///    extension data is at most 255 bytes long,
+/// 7. The lower 16 bits of the CRC-32 checksum of the preceding fields in big-endian
+///    byte order.
+///
+/// The flags consist of the following bits, starting from the least significant one:
+///
+/// | Bits | Meaning                                                                 |
+/// |------|-------------------------------------------------------------------------|
+/// | 0    | The payload is compressed                                               |
+/// | 1    | The payload is encrypted                                                |
+/// | 2-3  | The error correction code: none, repetition, Hamming, or Reed-Solomon    |
+/// | 4-5  | The checksum: none, CRC-32, XXH64, or SHA-256                           |
+/// | 6-7  | Reserved                                                                |
+///
+/// All four values of the error correction bits are taken, so further codes will set
+/// bit 6 and identify themselves with an extension entry. Readers of this version reject
+/// the reserved bits with [`HeaderError::UnsupportedFlags`] instead of misreading such
+/// payloads.
+///
+/// Every byte of the header is repeated three times and decoded by a bitwise majority vote,
+/// so that the header survives moderate corruption regardless of the error correction code
+/// used for the message itself.
+///
+/// The header itself is always concealed by bit replacement or LSB matching, even if the
+/// message is concealed by matrix embedding or syndrome-trellis coding.
+///
+/// [`Carrier::with_header`]: crate::binary::Carrier::with_header
+/// [`Package::with_header`]: crate::binary::Package::with_header
+/// [`Regions`]: crate::binary::Regions
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Header {
+    /// The format version.
+    pub version: u8,
+
+    /// The length of the payload in bytes.
+    pub len: u64,
+
+    /// The error correction code the payload is encoded with.
+    pub fec: Option<Fec>,
+
+    /// The checksum appended to the payload.
+    pub checksum: Option<Checksum>,
+
+    /// Whether the payload is encrypted.
+    pub encrypted: bool,
+
+    /// Whether the payload is compressed.
+    ///
+    /// No carrier of this version sets the flag, which is reserved for a future compression
+    /// option. It is recognized so that compressed payloads produced elsewhere can be told
+    /// apart.
+    pub compressed: bool,
+
+    /// The [`fingerprint`][crate::binary::Regions::fingerprint] of the regions skipped by
+    /// the carrier, if any.
+    pub regions: Option<u32>,
+
+    /// The number of bits embedded into each group of cover bits if the message is
+    /// concealed by [`matrix embedding`][crate::binary::Carrier::matrix_embedding].
+    pub matrix: Option<u8>,
+
+    /// The number of cover bits each bit is embedded into if the message is concealed by
+    /// [`syndrome-trellis coding`][crate::binary::Carrier::syndrome_trellis].
+    pub trellis: Option<u32>,
+}
+
+impl Header {
+    pub(super) fn new(len: u64) -> Self {
+        Self {
+            version: VERSION,
+            len,
+            fec: None,
+            checksum: None,
+            encrypted: false,
+            compressed: false,
+            regions: None,
+            matrix: None,
+            trellis: None,
+        }
+    }
+
+    fn to_bytes(self) -> Vec<u8> {
+        let mut flags = 0;
+        if self.compressed {
+            flags |= COMPRESSED;
+        }
+        if self.encrypted {
+            flags |= ENCRYPTED;
+        }
+
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(self.version);
+
+        let flags_index = bytes.len();
+        bytes.push(flags);
+
+        let fec_kind = match self.fec {
+            None => 0,
+            Some(Fec::Repetition(n)) => {
+                bytes.push(n);
+                1
+            }
+            Some(Fec::Hamming) => 2,
+            Some(Fec::ReedSolomon { data, parity }) => {
+                bytes.extend([data, parity]);
+                3
+            }
+        };
+        bytes[flags_index] |= fec_kind << FEC_SHIFT;
+
+        let checksum_kind = match self.checksum {
+            None => 0,
+            Some(Checksum::Crc32) => 1,
+            Some(Checksum::XxHash64) => 2,
+            Some(Checksum::Sha256) => 3,
+        };
+        bytes[flags_index] |= checksum_kind << CHECKSUM_SHIFT;
+
+        write_varint(&mut bytes, self.len);
+
+        let mut extension = Vec::new();
+        if let Some(fingerprint) = self.regions {
+            extension.push(REGIONS_TAG);
+            write_varint(&mut extension, 4);
+            extension.extend(fingerprint.to_be_bytes());
+        }
+
+        if let Some(k) = self.matrix {
+            extension.push(MATRIX_TAG);
+            write_varint(&mut extension, 1);
+            extension.push(k);
+        }
+
+        if let Some(width) = self.trellis {
+            extension.push(TRELLIS_TAG);
+            write_varint(&mut extension, 4);
+            extension.extend(width.to_be_bytes());
+        }
+
+        write_varint(&mut bytes, extension.len() as u64);
+        bytes.extend(extension);
+
+        let checksum = crc32fast::hash(&bytes) as u16;
+        bytes.extend(checksum.to_be_bytes());
+
+        bytes
+    }
+
+    /// Returns the header in its concealed form.
+    pub(super) fn encode(self) -> Vec<u8> {
+        self.to_bytes()
+            .into_iter()
+            .flat_map(|byte| [byte; COPIES])
+            .collect()
+    }
+
+    /// Parses the header from the beginning of the bytes, or tells how many bytes are
+    /// required before it can be parsed.
+    fn parse(bytes: &[u8]) -> Result<Parsed, HeaderError> {
+        let mut reader = ByteReader { bytes, position: 0 };
+        let more = Parsed::Incomplete {
+            needed: bytes.len() + 1,
+        };
+
+        for magic in MAGIC {
+            match reader.next() {
+                Some(byte) if byte != magic => return Err(HeaderError::Missing),
+                Some(_) => {}
+                None => return Ok(more),
+            }
+        }
+
+        let Some(version) = reader.next() else {
+            return Ok(more);
+        };
+
+        if version != VERSION {
+            return Err(HeaderError::UnsupportedVersion(version));
+        }
+
+        let Some(flags) = reader.next() else {
+            return Ok(more);
+        };
+
+        if flags & !KNOWN_FLAGS != 0 {
+            return Err(HeaderError::UnsupportedFlags(flags & !KNOWN_FLAGS));
+        }
+
+        let fec = match (flags & FEC_MASK) >> FEC_SHIFT {
+            0 => None,
+            1 => match reader.next() {
+                Some(0) => return Err(HeaderError::Malformed),
+                Some(n) => Some(Fec::Repetition(n)),
+                None => return Ok(more),
+            },
+            2 => Some(Fec::Hamming),
+            _ => match (reader.next(), reader.next()) {
+                (Some(data), Some(parity))
+                    if data == 0 || parity == 0 || data as usize + parity as usize > 255 =>
+                {
+                    return Err(HeaderError::Malformed)
+                }
+                (Some(data), Some(parity)) => Some(Fec::ReedSolomon { data, parity }),
+                _ => return Ok(more),
+            },
+        };
+
+        let checksum = match (flags & CHECKSUM_MASK) >> CHECKSUM_SHIFT {
+            0 => None,
+            1 => Some(Checksum::Crc32),
+            2 => Some(Checksum::XxHash64),
+            _ => Some(Checksum::Sha256),
+        };
+
+        let Some(len) = reader.varint()? else {
+            return Ok(more);
+        };
+
+        let Some(extension_len) = reader.varint()? else {
+            return Ok(more);
+        };
+
+        if extension_len > MAX_EXTENSION_LEN {
+            return Err(HeaderError::Malformed);
+        }
+
+        let start = reader.position;
+        let end = start + extension_len as usize;
+
+        let Some(&[high, low]) = bytes.get(end..end + 2) else {
+            return Ok(Parsed::Incomplete { needed: end + 2 });
+        };
+
+        if crc32fast::hash(&bytes[..end]) as u16 != u16::from_be_bytes([high, low]) {
+            return Err(HeaderError::ChecksumMismatch);
+        }
+
+        let Extension {
+            regions,
+            matrix,
+            trellis,
+        } = parse_extension(&bytes[start..end])?;
+
+        Ok(Parsed::Complete(Self {
+            version,
+            len,
+            fec,
+            checksum,
+            encrypted: flags & ENCRYPTED != 0,
+            compressed: flags & COMPRESSED != 0,
+            regions,
+            matrix,
+            trellis,
+        }))
+    }
+}
+
+/// The outcome of parsing a header from the bytes received so far.
+#[derive(Clone, Copy)]
+enum Parsed {
+    /// The header is complete.
+    Complete(Header),
+
+    /// At least `needed` bytes are required to parse the header.
+    Incomplete { needed: usize },
+}
+
+/// The fields of the header stored in the extension data.
+#[derive(Default)]
+struct Extension {
+    regions: Option<u32>,
+    matrix: Option<u8>,
+    trellis: Option<u32>,
+}
+
+/// Parses the entries of the extension data.
+fn parse_extension(bytes: &[u8]) -> Result<Extension, HeaderError> {
+    let mut reader = ByteReader { bytes, position: 0 };
+    let mut extension = Extension::default();
+
+    while let Some(tag) = reader.next() {
+        let Some(len) = reader.varint()? else {
+            return Err(HeaderError::Malformed);
+        };
+
+        let start = reader.position;
+        let end = usize::try_from(len)
+            .ok()
+            .and_then(|len| start.checked_add(len))
+            .filter(|&end| end <= bytes.len())
+            .ok_or(HeaderError::Malformed)?;
+
+        reader.position = end;
+
+        match (tag, &bytes[start..end]) {
+            (REGIONS_TAG, entry) => {
+                let fingerprint = entry.try_into().map_err(|_| HeaderError::Malformed)?;
+                extension.regions = Some(u32::from_be_bytes(fingerprint));
+            }
+            (MATRIX_TAG, &[k]) if (1..=matrix::MAX_K).contains(&k) => extension.matrix = Some(k),
+            (MATRIX_TAG, _) => return Err(HeaderError::Malformed),
+            (TRELLIS_TAG, entry) => {
+                let width = entry.try_into().map_err(|_| HeaderError::Malformed)?;
+                extension.trellis = Some(u32::from_be_bytes(width)).filter(|&width| width > 0);
+
+                if extension.trellis.is_none() {
+                    return Err(HeaderError::Malformed);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    Ok(extension)
+}
+
+/// The reason a package does not contain a valid [`Header`].
+///
+/// It is returned wrapped in [`Error::Header`][crate::Error::Header].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HeaderError {
+    /// The magic bytes are missing, so the package most likely contains no message, or the
+    /// pattern is wrong.
+    Missing,
+
+    /// The header is of a newer, unsupported version.
+    UnsupportedVersion(u8),
+
+    /// The header has flags set that are not supported by this version.
+    UnsupportedFlags(u8),
+
+    /// The header contains invalid values.
+    Malformed,
+
+    /// The header checksum does not match.
+    ChecksumMismatch,
+}
+
+impl fmt::Display for HeaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Missing => f.write_str("package does not contain a header"),
+            Self::UnsupportedVersion(version) => {
+                write!(f, "header version {version} is not supported")
+            }
+            Self::UnsupportedFlags(flags) => {
+                write!(f, "header flags {flags:#010b} are not supported")
+            }
+            Self::Malformed => f.write_str("header is malformed"),
+            Self::ChecksumMismatch => f.write_str("header checksum does not match"),
+        }
+    }
+}
+
+impl error::Error for HeaderError {}
+
+/// A parser that accepts concealed header bytes one by one.
+pub(super) struct Parser {
+    copies: Vec<u8>,
+    bytes: Vec<u8>,
+    needed: usize,
+}
+
+impl Parser {
+    pub(super) fn new() -> Self {
+        Self {
+            copies: Vec::with_capacity(COPIES),
+            bytes: Vec::new(),
+            needed: 1,
+        }
+    }
+
+    /// Accepts the next concealed byte and returns the header once it is complete.
+    pub(super) fn push(&mut self, byte: u8) -> Result<Option<Header>, HeaderError> {
+        self.copies.push(byte);
+
+        if self.copies.len() < COPIES {
+            return Ok(None);
+        }
+
+        let byte = (0..8)
+            .filter(|pow| {
+                let ones = self.copies.iter().filter(|&&copy| copy & (1 << pow) != 0);
+                ones.count() * 2 > COPIES
+            })
+            .fold(0, |byte, pow| byte | 1 << pow);
+
+        self.copies.clear();
+        self.bytes.push(byte);
+
+        // The header is parsed again only once the fields it stopped at may be complete,
+        // so that the extension data is not parsed with every byte.
+        if self.bytes.len() < self.needed {
+            return Ok(None);
+        }
+
+        match Header::parse(&self.bytes)? {
+            Parsed::Complete(header) => Ok(Some(header)),
+            Parsed::Incomplete { needed } => {
+                self.needed = needed;
+                Ok(None)
+            }
+        }
+    }
+}
+
+struct ByteReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl ByteReader<'_> {
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.bytes.get(self.position).copied()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn varint(&mut self) -> Result<Option<u64>, HeaderError> {
+        let mut value = 0u64;
+
+        for shift in (0..64).step_by(7) {
+            let Some(byte) = self.next() else {
+                return Ok(None);
+            };
+
+            let bits = u64::from(byte & 0x7f);
+            if bits << shift >> shift != bits {
+                return Err(HeaderError::Malformed);
+            }
+
+            value |= bits << shift;
+
+            if byte & 0x80 == 0 {
+                return Ok(Some(value));
+            }
+        }
+
+        Err(HeaderError::Malformed)
+    }
+}
+
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+
+    bytes.push(value as u8);
+}
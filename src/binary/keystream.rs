@@ -0,0 +1,126 @@
+use sha2::{Digest, Sha256};
+use std::fmt;
+
+/// A deterministic stream of pseudo-random blocks derived from a secret seed.
+///
+/// Every block is computed as `SHA-256(key || index)`, where the key itself is a SHA-256
+/// digest of a domain separation tag and the seed. This allows random access to any
+/// position of the stream, which is required by patterns that may be queried out of order.
+#[derive(Clone)]
+pub(super) struct Keystream {
+    key: [u8; 32],
+    block_index: Option<u64>,
+    block: [u8; 32],
+    position: u64,
+}
+
+impl fmt::Debug for Keystream {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Keystream").finish_non_exhaustive()
+    }
+}
+
+impl Keystream {
+    pub(super) fn new(domain: &[u8], seed: &[u8]) -> Self {
+        let key = Sha256::new()
+            .chain_update((domain.len() as u64).to_be_bytes())
+            .chain_update(domain)
+            .chain_update(seed)
+            .finalize()
+            .into();
+
+        Self {
+            key,
+            block_index: None,
+            block: [0; 32],
+            position: 0,
+        }
+    }
+
+    fn block(&mut self, index: u64) -> &[u8; 32] {
+        if self.block_index != Some(index) {
+            self.block = Sha256::new()
+                .chain_update(self.key)
+                .chain_update(index.to_be_bytes())
+                .finalize()
+                .into();
+            self.block_index = Some(index);
+        }
+
+        &self.block
+    }
+
+    /// Returns the 64-bit word at the given position of the stream.
+    pub(super) fn word_at(&mut self, position: u64) -> u64 {
+        let offset = (position % 4) as usize * 8;
+        let block = self.block(position / 4);
+
+        u64::from_le_bytes(*block[offset..].first_chunk::<8>().unwrap())
+    }
+
+    /// Moves the stream to the given position, from which [`Keystream::below`] reads.
+    pub(super) fn seek(&mut self, position: u64) {
+        self.position = position;
+    }
+
+    /// Returns the next 64-bit word of the stream.
+    pub(super) fn next_u64(&mut self) -> u64 {
+        let word = self.word_at(self.position);
+        self.position += 1;
+        word
+    }
+
+    /// Returns a uniformly distributed integer in `0..bound`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bound` is zero.
+    pub(super) fn below(&mut self, bound: u64) -> u64 {
+        assert!(bound > 0, "bound must be positive");
+
+        let zone = u64::MAX - u64::MAX % bound;
+        loop {
+            let word = self.next_u64();
+            if word < zone {
+                return word % bound;
+            }
+        }
+    }
+}
+
+/// A lazily generated pseudo-random permutation of `0..len`.
+///
+/// The permutation is produced by the Fisher–Yates shuffle driven by a [`Keystream`], so
+/// that consumers which stop early do not pay for shuffling the whole range.
+pub(super) struct Permutation {
+    keystream: Keystream,
+    positions: Vec<usize>,
+    next: usize,
+}
+
+impl Permutation {
+    pub(super) fn new(keystream: Keystream, len: usize) -> Self {
+        Self {
+            keystream,
+            positions: (0..len).collect(),
+            next: 0,
+        }
+    }
+}
+
+impl Iterator for Permutation {
+    type Item = usize;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let remaining = self.positions.len().checked_sub(self.next)?;
+        if remaining == 0 {
+            return None;
+        }
+
+        let other = self.next + self.keystream.below(remaining as u64) as usize;
+        self.positions.swap(self.next, other);
+        self.next += 1;
+
+        Some(self.positions[self.next - 1])
+    }
+}
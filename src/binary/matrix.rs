@@ -0,0 +1,86 @@
+/// The largest number of payload bits embedded into a single group of cover bits.
+pub(super) const MAX_K: u8 = 16;
+
+/// Statistics of a matrix embedding performed by a [`Carrier`].
+///
+/// Matrix embedding splits the cover bits selected by the pattern into groups of
+/// 2<sup>k</sup> − 1 bits and embeds `k` bits of the concealed data into each group as its
+/// syndrome under a Hamming code, so that at most one cover bit per group has to change.
+/// Bit replacement changes half of the cover bits it uses on average, whereas matrix
+/// embedding changes at most one in `k` bits of the concealed data, at the expense of
+/// using more cover bits.
+///
+/// See [`Carrier::matrix_embedding`] for details.
+///
+/// [`Carrier`]: crate::binary::Carrier
+/// [`Carrier::matrix_embedding`]: crate::binary::Carrier::matrix_embedding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct MatrixStatistics {
+    /// The number of bits of the concealed data embedded into each group of cover bits.
+    pub k: u8,
+
+    /// The number of bits of the concealed data, including the [`Header`], if any.
+    ///
+    /// [`Header`]: crate::binary::Header
+    pub embedded_bits: u64,
+
+    /// The number of cover bits selected by the pattern that the data occupies.
+    pub cover_bits: u64,
+
+    /// The number of cover bits selected by the pattern that were changed.
+    pub changed_bits: u64,
+}
+
+/// Returns the largest `k` with which the supplied number of data bits fits into the
+/// supplied number of cover bits, if any.
+pub(super) fn choose_k(data_bits: u64, cover_bits: u64) -> Option<u8> {
+    (1..=MAX_K)
+        .rev()
+        .find(|&k| data_bits.div_ceil(u64::from(k)) * group_len(k) <= cover_bits)
+}
+
+/// Returns the number of cover bits in each group.
+pub(super) fn group_len(k: u8) -> u64 {
+    (1 << k) - 1
+}
+
+/// A decoder that computes the syndromes of consecutive groups of cover bits.
+pub(super) struct Syndromes {
+    k: u8,
+    position: u64,
+    syndrome: u32,
+}
+
+impl Syndromes {
+    pub(super) fn new(k: u8) -> Self {
+        Self {
+            k,
+            position: 0,
+            syndrome: 0,
+        }
+    }
+
+    /// Accepts the next cover bit and returns the syndrome once the group is complete.
+    ///
+    /// The syndrome is the exclusive or of the one-based positions of the set bits within
+    /// the group, whose `k` bits are the embedded ones, starting from the least
+    /// significant bit.
+    pub(super) fn push(&mut self, bit: u8) -> Option<u32> {
+        self.position += 1;
+
+        if bit == 1 {
+            self.syndrome ^= self.position as u32;
+        }
+
+        if self.position < group_len(self.k) {
+            return None;
+        }
+
+        let syndrome = self.syndrome;
+        self.position = 0;
+        self.syndrome = 0;
+
+        Some(syndrome)
+    }
+}
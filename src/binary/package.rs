@@ -1,14 +1,94 @@
-use crate::{binary::bits, Reveal};
+use crate::{
+    binary::{
+        bits,
+        checksum::Verifier,
+        fec::Decoder,
+        header::{self, HeaderError},
+        keystream::{Keystream, Permutation},
+        matrix::{self, Syndromes},
+        regions::Skipping,
+        sample::{self, Sample},
+        trellis, Checksum, Fec, Header, Pattern, Regions,
+    },
+    Error, Reveal,
+};
 use std::{
+    cell::Cell,
     io::{self, BufReader, BufWriter, Read, Write},
     ops::ControlFlow,
 };
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, Clone, Copy)]
+enum Code {
+    Matrix(u8),
+    Trellis(u32),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
 enum PayloadLength {
     Bound(u64),
     Unbound,
     Embedded,
+    Header,
+}
+
+struct Output<W>
+where
+    W: Write,
+{
+    writer: BufWriter<W>,
+    len: PayloadLength,
+    len_bytes: Vec<u8>,
+    bytes_written: usize,
+    verifier: Option<Verifier>,
+}
+
+impl<W> Output<W>
+where
+    W: Write,
+{
+    fn write(&mut self, byte: u8) -> Result<ControlFlow<()>, Error> {
+        if self.len == PayloadLength::Embedded {
+            self.len_bytes.push(byte);
+
+            if let Some(bytes) = self.len_bytes.first_chunk::<8>() {
+                self.len = PayloadLength::Bound(u64::from_be_bytes(*bytes));
+                return Ok(self.flow());
+            }
+
+            return Ok(ControlFlow::Continue(()));
+        }
+
+        let is_complete = self.is_complete();
+        if let Some(verifier) = self.verifier.as_mut() {
+            if is_complete {
+                if !verifier.push(byte)? {
+                    return Ok(ControlFlow::Continue(()));
+                }
+
+                self.verifier = None;
+                return Ok(ControlFlow::Break(()));
+            }
+
+            verifier.update(byte);
+        }
+
+        self.bytes_written += self.writer.write(&[byte])?;
+
+        Ok(self.flow())
+    }
+
+    fn is_complete(&self) -> bool {
+        matches!(self.len, PayloadLength::Bound(len) if self.bytes_written as u64 >= len)
+    }
+
+    fn flow(&self) -> ControlFlow<()> {
+        if self.is_complete() && self.verifier.is_none() {
+            ControlFlow::Break(())
+        } else {
+            ControlFlow::Continue(())
+        }
+    }
 }
 
 /// A binary package that contains a steganographic message.
@@ -20,6 +100,9 @@ enum PayloadLength {
 /// 2. The package no longer accepts writes, or
 /// 3. The required length of bytes was written.
 ///
+/// If the message length is embedded and the reader is empty before the length is read,
+/// [`Error::TruncatedLength`] is returned.
+///
 /// # Examples
 ///
 /// Revealing a secret message of known length hidden within the package:
@@ -35,7 +118,7 @@ enum PayloadLength {
 /// );
 ///
 /// package.reveal(File::open("message")?)?;
-/// # Ok::<(), std::io::Error>(())
+/// # Ok::<(), asbs::Error>(())
 /// ```
 ///
 /// Revealing a secret message hidden within the package with embedded length:
@@ -50,22 +133,29 @@ enum PayloadLength {
 /// );
 ///
 /// package.reveal(File::open("message")?)?;
-/// # Ok::<(), std::io::Error>(())
+/// # Ok::<(), asbs::Error>(())
 /// ```
 #[derive(Debug)]
 pub struct Package<P, R>
 where
-    P: FnMut(usize) -> Option<u8>,
+    P: Pattern,
     R: Read,
 {
     pattern: P,
     reader: BufReader<R>,
     len: PayloadLength,
+    order: Option<Keystream>,
+    matrix: Option<u8>,
+    trellis: Option<Keystream>,
+    regions: Option<Regions>,
+    fec: Option<Fec>,
+    checksum: Option<Checksum>,
+    header: Option<Header>,
 }
 
 impl<P, R> Package<P, R>
 where
-    P: FnMut(usize) -> Option<u8>,
+    P: Pattern,
     R: Read,
 {
     /// Creates a new [`Package<P, R>`] with the supplied message length, pattern, and reader.
@@ -83,7 +173,7 @@ where
     ///     |i| Some(1u8 << (i % 3)),
     ///     File::open("package")?,
     /// );
-    /// # Ok::<(), std::io::Error>(())
+    /// # Ok::<(), asbs::Error>(())
     /// ```
     #[must_use]
     pub fn with_len(len: usize, pattern: P, reader: R) -> Self {
@@ -91,6 +181,13 @@ where
             pattern,
             reader: BufReader::new(reader),
             len: PayloadLength::Bound(len as u64),
+            order: None,
+            matrix: None,
+            trellis: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            header: None,
         }
     }
 
@@ -109,7 +206,7 @@ where
     ///     |i| Some(1u8 << (i % 4)),
     ///     File::open("package")?,
     /// );
-    /// # Ok::<(), std::io::Error>(())
+    /// # Ok::<(), asbs::Error>(())
     /// ```
     #[must_use]
     pub fn with_embedded_len(pattern: P, reader: R) -> Self {
@@ -117,6 +214,48 @@ where
             pattern,
             reader: BufReader::new(reader),
             len: PayloadLength::Embedded,
+            order: None,
+            matrix: None,
+            trellis: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            header: None,
+        }
+    }
+
+    /// Creates a new [`Package<P, R>`] with the supplied pattern and reader.
+    ///
+    /// This function is useful if the message was concealed with a [`Header`], i.e. with
+    /// [`Carrier::with_header`][crate::binary::Carrier::with_header]. The message length and
+    /// the error correction code are then read from the header, and [`Error::Header`] is
+    /// returned if the package contains no valid header.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_header(
+    ///     |i| Some(1u8 << (i % 4)),
+    ///     File::open("package")?,
+    /// );
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn with_header(pattern: P, reader: R) -> Self {
+        Self {
+            pattern,
+            reader: BufReader::new(reader),
+            len: PayloadLength::Header,
+            order: None,
+            matrix: None,
+            trellis: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            header: None,
         }
     }
 
@@ -139,7 +278,7 @@ where
     ///     |_| Some(0b1101),
     ///     File::open("package")?,
     /// );
-    /// # Ok::<(), std::io::Error>(())
+    /// # Ok::<(), asbs::Error>(())
     /// ```
     #[must_use]
     pub fn new(pattern: P, reader: R) -> Self {
@@ -147,89 +286,422 @@ where
             pattern,
             reader: BufReader::new(reader),
             len: PayloadLength::Unbound,
+            order: None,
+            matrix: None,
+            trellis: None,
+            regions: None,
+            fec: None,
+            checksum: None,
+            header: None,
         }
     }
-}
 
-impl<M, R> Reveal for &mut Package<M, R>
-where
-    M: FnMut(usize) -> Option<u8>,
-    R: Read,
-{
-    type Err = io::Error;
+    /// Makes the package extract the message from a pseudo-random permutation of its bytes.
+    ///
+    /// The seed has to match the one used with
+    /// [`Carrier::permuted`][crate::binary::Carrier::permuted]. The whole package is buffered
+    /// in memory during [`reveal`][crate::Reveal::reveal].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_embedded_len(
+    ///     |_| Some(0b1),
+    ///     File::open("package")?,
+    /// )
+    /// .permuted("a shared seed");
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn permuted(mut self, seed: impl AsRef<[u8]>) -> Self {
+        self.order = Some(Keystream::new(b"asbs permutation", seed.as_ref()));
+        self
+    }
 
-    fn reveal<W: Write>(self, output: W) -> io::Result<usize> {
-        let mut output = BufWriter::new(output);
+    /// Makes the package extract the message concealed by matrix embedding with the supplied
+    /// number of bits per group of cover bits, which has to match the `k` reported by
+    /// [`Carrier::matrix_statistics`][crate::binary::Carrier::matrix_statistics].
+    ///
+    /// Packages created with [`Package::with_header`] read `k` from the header instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `k` is zero or greater than 16.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_embedded_len(
+    ///     |_| Some(0b1),
+    ///     File::open("package")?,
+    /// )
+    /// .matrix_embedding(4);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn matrix_embedding(mut self, k: u8) -> Self {
+        assert!(
+            (1..=matrix::MAX_K).contains(&k),
+            "matrix embedding requires 1 to {} bits per group",
+            matrix::MAX_K,
+        );
 
-        let mut len_bytes = (self.len == PayloadLength::Embedded).then(|| Vec::with_capacity(8));
+        self.matrix = Some(k);
+        self
+    }
 
-        let mut bytes_written = 0usize;
-        let mut write_byte = |byte| -> Result<ControlFlow<()>, io::Error> {
-            if let Some(bytes) = len_bytes.as_mut() {
-                bytes.push(byte);
+    /// Makes the package extract the message concealed by syndrome-trellis coding with the
+    /// supplied seed, which has to match the one used with
+    /// [`Carrier::syndrome_trellis`][crate::binary::Carrier::syndrome_trellis].
+    ///
+    /// The width of the blocks is read from the header. If the header describes
+    /// syndrome-trellis coding, but no seed was supplied, [`reveal`][crate::Reveal::reveal]
+    /// returns [`Error::MissingSeed`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the package was not created with [`Package::with_header`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_header(
+    ///     |_| Some(0b1),
+    ///     File::open("package")?,
+    /// )
+    /// .syndrome_trellis("a shared seed");
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn syndrome_trellis(mut self, seed: impl AsRef<[u8]>) -> Self {
+        assert!(
+            self.len == PayloadLength::Header,
+            "syndrome-trellis coding requires a header",
+        );
 
-                if bytes.len() == 8 {
-                    self.len = PayloadLength::Bound(u64::from_be_bytes(
-                        *bytes.first_chunk::<8>().unwrap(),
-                    ));
+        self.trellis = Some(Keystream::new(b"asbs trellis", seed.as_ref()));
+        self
+    }
 
-                    if let PayloadLength::Bound(0) = self.len {
-                        return Ok(ControlFlow::Break(()));
-                    }
+    /// Makes the package ignore the bytes within the supplied regions, which have to match
+    /// the ones used with [`Carrier::skip_regions`][crate::binary::Carrier::skip_regions].
+    ///
+    /// Packages created with [`Package::with_header`] compare the regions with the
+    /// [`fingerprint`][Regions::fingerprint] stored in the header, and
+    /// [`reveal`][crate::Reveal::reveal] returns [`Error::RegionMismatch`] if they differ.
+    /// Other packages cannot tell mismatching regions apart from a wrong pattern.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Regions};
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_header(
+    ///     |_| Some(0b1),
+    ///     File::open("package")?,
+    /// )
+    /// .skip_regions(Regions::new().skip(..512));
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn skip_regions(mut self, regions: Regions) -> Self {
+        self.regions = Some(regions).filter(|regions| !regions.is_empty());
+        self
+    }
+
+    /// Makes the package decode the concealed data with the supplied forward error correction
+    /// code, which has to match the one used with [`Carrier::fec`][crate::binary::Carrier::fec].
+    ///
+    /// Packages created with [`Package::with_header`] read the code from the header instead.
+    ///
+    /// If the data turns out to be too corrupted to be corrected, [`reveal`][crate::Reveal::reveal]
+    /// returns [`Error::Uncorrectable`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the parameters of the code are invalid. See [`Fec`] for details.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Fec};
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_embedded_len(
+    ///     |_| Some(0b11),
+    ///     File::open("package")?,
+    /// )
+    /// .fec(Fec::Hamming);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn fec(mut self, fec: Fec) -> Self {
+        fec.validate();
+        self.fec = Some(fec);
+        self
+    }
+
+    /// Makes the package verify the revealed message against the supplied checksum, which has
+    /// to match the one used with [`Carrier::checksum`][crate::binary::Carrier::checksum].
+    ///
+    /// If the checksum does not match, [`reveal`][crate::Reveal::reveal] returns
+    /// [`Error::Verification`]. Note that the message is written to the output as it is
+    /// revealed, so the output should be discarded in that case.
+    ///
+    /// The message length has to be known for the checksum to be located, so packages
+    /// created with [`Package::new`] fail to reveal with [`Error::UnknownLength`]. Packages
+    /// created with [`Package::with_header`] read the checksum from the header instead.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary::{self, Checksum};
+    /// use std::fs::File;
+    ///
+    /// let mut package = binary::Package::with_embedded_len(
+    ///     |_| Some(0b11),
+    ///     File::open("package")?,
+    /// )
+    /// .checksum(Checksum::Sha256);
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    #[must_use]
+    pub fn checksum(mut self, checksum: Checksum) -> Self {
+        self.checksum = Some(checksum);
+        self
+    }
+
+    /// Returns the [`Header`] read from the package, if any.
+    ///
+    /// The header is only available after [`reveal`][crate::Reveal::reveal] of a package
+    /// created with [`Package::with_header`].
+    #[must_use]
+    pub fn header(&self) -> Option<&Header> {
+        self.header.as_ref()
+    }
 
-                    len_bytes = None;
+    /// Checks whether the package contains a message with a valid [`Header`], returning
+    /// the header without revealing the message.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading the package, as well as
+    /// [`Error::Header`] if the header is valid but not supported by this version of the
+    /// library.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use asbs::binary;
+    /// use std::fs::File;
+    ///
+    /// let package = binary::Package::with_header(
+    ///     |i| Some(1u8 << (i % 4)),
+    ///     File::open("package")?,
+    /// );
+    ///
+    /// if let Some(header) = package.detect()? {
+    ///     println!("found a message of {} bytes", header.len);
+    /// }
+    /// # Ok::<(), asbs::Error>(())
+    /// ```
+    pub fn detect(mut self) -> Result<Option<Header>, Error> {
+        self.len = PayloadLength::Header;
+
+        match self.extract(io::sink(), true) {
+            Ok(_) => Ok(self.header),
+            Err(
+                Error::TruncatedLength
+                | Error::Header(
+                    HeaderError::Missing | HeaderError::Malformed | HeaderError::ChecksumMismatch,
+                ),
+            ) => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn extract<W: Write>(&mut self, output: W, header_only: bool) -> Result<usize, Error> {
+        let mut output = Output {
+            writer: BufWriter::new(output),
+            len: self.len,
+            len_bytes: Vec::with_capacity(8),
+            bytes_written: 0,
+            verifier: self.checksum.map(Verifier::new),
+        };
+
+        if self.len == PayloadLength::Unbound && self.checksum.is_some() {
+            return Err(Error::UnknownLength);
+        }
+
+        if output.flow().is_break() {
+            return Ok(0);
+        }
+
+        let mut parser = (self.len == PayloadLength::Header).then(header::Parser::new);
+        let mut decoder = Decoder::new(self.fec);
+        let regions = self.regions.as_ref().map(Regions::fingerprint);
+        let code = Cell::new(self.matrix.filter(|_| parser.is_none()).map(Code::Matrix));
+
+        // Returns whether the package accepts no more bytes.
+        let mut accept = |byte| -> Result<bool, Error> {
+            if let Some(header_parser) = parser.as_mut() {
+                let Some(header) = header_parser.push(byte)? else {
+                    return Ok(false);
+                };
+
+                if header.regions != regions {
+                    return Err(Error::RegionMismatch);
+                }
+
+                parser = None;
+                decoder = Decoder::new(header.fec);
+                output.len = PayloadLength::Bound(header.len);
+                output.verifier = header.checksum.map(Verifier::new);
+                self.header = Some(header);
+
+                if header_only {
+                    return Ok(true);
                 }
 
-                return Ok(ControlFlow::Continue(()));
+                // The seed is only needed to extract the payload, not to detect the header.
+                code.set(match (header.matrix, header.trellis) {
+                    (Some(k), _) => Some(Code::Matrix(k)),
+                    (None, Some(_)) if self.trellis.is_none() => return Err(Error::MissingSeed),
+                    (None, Some(width)) => Some(Code::Trellis(width)),
+                    (None, None) => None,
+                });
+
+                return Ok(output.flow().is_break());
             }
 
-            bytes_written += output.write(&[byte])?;
+            for &byte in decoder.push(byte)? {
+                if output.write(byte)?.is_break() {
+                    return Ok(true);
+                }
+            }
 
-            Ok(match self.len {
-                PayloadLength::Embedded => unreachable!("`PayloadLength::Embedded` is replaced with `PayloadLength::Known(n)` before reaching this"),
-                PayloadLength::Unbound => ControlFlow::Continue(()),
-                PayloadLength::Bound(len) => {
-                    if (bytes_written as u64) < len {
-                        ControlFlow::Continue(())
-                    } else {
-                        ControlFlow::Break(())
+            Ok(false)
+        };
+
+        let len = P::Sample::LEN;
+        let package_words: Box<dyn Iterator<Item = (usize, io::Result<u32>)>> = match &self.order {
+            None => {
+                let reader = &mut self.reader;
+                let mut sample = Vec::with_capacity(len);
+
+                Box::new((0..).map_while(move |index| {
+                    sample.clear();
+                    match reader.take(len as u64).read_to_end(&mut sample) {
+                        Ok(_) if sample.len() < len => None,
+                        result => Some((index, result.map(|_| P::Sample::read(&sample)))),
                     }
-                }
-            })
+                }))
+            }
+            Some(keystream) => {
+                let mut buffer = Vec::new();
+                self.reader.read_to_end(&mut buffer)?;
+
+                Box::new(
+                    Permutation::new(keystream.clone(), buffer.len() / len)
+                        .map(move |index| (index, Ok(P::Sample::read(&buffer[index * len..])))),
+                )
+            }
         };
 
+        let mut pattern = Skipping::new(&mut self.pattern, self.regions.as_ref());
+
+        let mut syndromes = None;
+        let mut trellis_syndromes = None;
+
+        let mut is_finished = false;
         let mut payload_byte = 0;
         let mut bit_count = 0usize;
-        for (index, package_byte) in self.reader.by_ref().bytes().enumerate() {
-            let Some(mask) = (self.pattern)(index) else {
+        'extract: for (index, package_word) in package_words {
+            let Some(mask) = pattern.mask(index) else {
                 break;
             };
 
-            let package_byte = package_byte?;
-            for pow in bits::Ones::from(mask) {
-                payload_byte |= ((package_byte >> pow) & 1) << bit_count;
-                bit_count += 1;
+            let package_word = package_word?;
+            for pow in bits::Ones::from(sample::mask_bits::<P::Sample>(mask)) {
+                let bit = (package_word >> pow) as u8 & 1;
 
-                if bit_count < 8 {
-                    continue;
-                }
+                let (value, len) = match code.get() {
+                    None => (u32::from(bit), 1),
+                    Some(Code::Matrix(k)) => {
+                        match syndromes.get_or_insert_with(|| Syndromes::new(k)).push(bit) {
+                            Some(syndrome) => (syndrome, k),
+                            None => continue,
+                        }
+                    }
+                    Some(Code::Trellis(width)) => {
+                        let syndromes = trellis_syndromes.get_or_insert_with(|| {
+                            let keystream = self.trellis.clone();
+                            let keystream = keystream.expect("seed is checked with the header");
+                            trellis::Syndromes::new(keystream, width as usize)
+                        });
 
-                if write_byte(payload_byte)?.is_break() {
-                    output.flush()?;
-                    return Ok(bytes_written);
-                }
+                        match syndromes.push(bit) {
+                            Some(bit) => (u32::from(bit), 1),
+                            None => continue,
+                        }
+                    }
+                };
 
-                bit_count = 0;
-                payload_byte = 0;
+                for pow in 0..len {
+                    payload_byte |= ((value >> pow) as u8 & 1) << bit_count;
+                    bit_count += 1;
+
+                    if bit_count < 8 {
+                        continue;
+                    }
+
+                    if accept(payload_byte)? {
+                        is_finished = true;
+                        break 'extract;
+                    }
+
+                    bit_count = 0;
+                    payload_byte = 0;
+                }
             }
         }
 
-        if bit_count > 0 {
-            write_byte(payload_byte)?;
+        if !is_finished && bit_count > 0 {
+            accept(payload_byte)?;
         }
 
-        output.flush()?;
+        output.writer.flush()?;
+
+        if matches!(output.len, PayloadLength::Embedded | PayloadLength::Header) {
+            return Err(Error::TruncatedLength);
+        }
+
+        if let Some(verifier) = output.verifier.filter(|_| !header_only) {
+            return Err(verifier.into_error());
+        }
+
+        Ok(output.bytes_written)
+    }
+}
+
+impl<M, R> Reveal for &mut Package<M, R>
+where
+    M: Pattern,
+    R: Read,
+{
+    type Err = Error;
 
-        Ok(bytes_written)
+    fn reveal<W: Write>(self, output: W) -> Result<usize, Error> {
+        self.extract(output, false)
     }
 }
@@ -0,0 +1,212 @@
+//! Bit patterns used by [`Carrier`][super::Carrier] and [`Package`][super::Package].
+//!
+//! Any closure of type `FnMut(usize) -> Option<u8>` is a [`Pattern`], which makes it easy to
+//! experiment with hand-written patterns. For real use, [`KeyedPattern`] derives the masks
+//! from a shared secret, so that the sender and the receiver only have to agree upon a
+//! passphrase.
+//!
+//! Patterns apply to cover bytes by default. [`Words`] makes a pattern apply to wider
+//! [samples][super::sample] instead.
+
+use super::{keystream::Keystream, sample::Sample};
+use std::{fmt, marker::PhantomData};
+
+/// A bit pattern, i.e. a function mapping sample indices to bit masks.
+///
+/// Returning [`None`] signals that no more samples may be used.
+///
+/// This trait is implemented for all closures of type `FnMut(usize) -> Option<u8>`, whose
+/// samples are single bytes.
+///
+/// The mask must be a pure function of the index. Permuted carriers query the indices out
+/// of order, and [`Carrier::capacity_for`] and [`Carrier::dry_run`] query them before the
+/// payload is concealed, so a closure that keeps state between calls, such as a counter,
+/// conceals the payload with masks its receiver does not reproduce.
+///
+/// [`Carrier::capacity_for`]: super::Carrier::capacity_for
+/// [`Carrier::dry_run`]: super::Carrier::dry_run
+pub trait Pattern {
+    /// The type of cover samples the masks apply to.
+    type Sample: Sample;
+
+    /// Returns the bit mask for the sample at the given index.
+    fn mask(&mut self, index: usize) -> Option<<Self::Sample as Sample>::Mask>;
+}
+
+impl<F> Pattern for F
+where
+    F: FnMut(usize) -> Option<u8>,
+{
+    type Sample = u8;
+
+    fn mask(&mut self, index: usize) -> Option<u8> {
+        self(index)
+    }
+}
+
+/// A bit pattern over samples wider than a byte, made of a closure that returns masks
+/// as wide as the samples.
+///
+/// # Examples
+///
+/// Concealing a message in the least significant bits of 16-bit little-endian samples:
+///
+/// ```
+/// use asbs::{
+///     binary::{self, pattern::Words, sample::Le},
+///     Conceal,
+/// };
+///
+/// let cover = [0xffu8; 64];
+/// let mut package = Vec::new();
+///
+/// binary::Carrier::new(Words::<Le<u16>, _>::new(|_| Some(0b1)), &mut package)
+///     .conceal(b"abc".as_slice(), cover.as_slice())?;
+///
+/// assert_eq!(package[..4], [0xff, 0xff, 0xfe, 0xff]);
+/// # Ok::<(), asbs::Error>(())
+/// ```
+pub struct Words<S, F> {
+    mask: F,
+    sample: PhantomData<S>,
+}
+
+impl<S, F> Words<S, F>
+where
+    S: Sample,
+    F: FnMut(usize) -> Option<S::Mask>,
+{
+    /// Creates a new [`Words<S, F>`] with the supplied closure.
+    #[must_use]
+    pub fn new(mask: F) -> Self {
+        Self {
+            mask,
+            sample: PhantomData,
+        }
+    }
+}
+
+impl<S, F> Pattern for Words<S, F>
+where
+    S: Sample,
+    F: FnMut(usize) -> Option<S::Mask>,
+{
+    type Sample = S;
+
+    fn mask(&mut self, index: usize) -> Option<S::Mask> {
+        (self.mask)(index)
+    }
+}
+
+impl<S, F> fmt::Debug for Words<S, F> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Words").finish_non_exhaustive()
+    }
+}
+
+/// A pseudo-random bit pattern derived from a secret seed.
+///
+/// Each byte is assigned a mask with exactly `density` bits set, chosen among the
+/// `2 * density` least significant bits (or all eight bits if `density` is greater
+/// than four). The choice is made by a keystream built on SHA-256, so the same seed
+/// always produces the same pattern, while different seeds produce unrelated ones.
+///
+/// The pattern never ends, so the amount of concealed data is limited only by the cover.
+///
+/// The seed is hashed with a single round of SHA-256, which is not a key derivation
+/// function: a passphrase of low entropy can be guessed by anyone who suspects a package
+/// of concealing a message. Use a long random seed, or encrypt the payload with
+/// `binary::EncryptedCarrier`, which derives its key with PBKDF2.
+///
+/// # Examples
+///
+/// ```no_run
+/// use asbs::{binary::{self, pattern::KeyedPattern}, Conceal};
+/// use std::fs::File;
+///
+/// let mut carrier = binary::Carrier::new(
+///     KeyedPattern::new("correct horse battery staple"),
+///     File::create("package")?,
+/// );
+///
+/// carrier.conceal(
+///     b"a very secret message".as_slice(),
+///     File::open("cover")?,
+/// )?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Clone)]
+pub struct KeyedPattern {
+    keystream: Keystream,
+    masks: Vec<u8>,
+}
+
+impl KeyedPattern {
+    /// Creates a new [`KeyedPattern`] with the supplied seed and density of one bit per byte.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::binary::pattern::{KeyedPattern, Pattern};
+    ///
+    /// let mut pattern = KeyedPattern::new("passphrase");
+    ///
+    /// assert_eq!(pattern.mask(0).map(u8::count_ones), Some(1));
+    /// ```
+    #[must_use]
+    pub fn new(seed: impl AsRef<[u8]>) -> Self {
+        Self::with_density(seed, 1)
+    }
+
+    /// Creates a new [`KeyedPattern`] with the supplied seed and number of bits per byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `density` is not in the range `1..=8`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::binary::pattern::{KeyedPattern, Pattern};
+    ///
+    /// let mut pattern = KeyedPattern::with_density("passphrase", 2);
+    ///
+    /// assert!((0..64).all(|i| pattern.mask(i).is_some_and(|mask| {
+    ///     mask.count_ones() == 2 && mask < 0b1_0000
+    /// })));
+    /// ```
+    #[must_use]
+    pub fn with_density(seed: impl AsRef<[u8]>, density: u32) -> Self {
+        assert!((1..=8).contains(&density), "density must be in 1..=8");
+
+        let planes = (density * 2).min(8);
+        let masks = (0..1u16 << planes)
+            .map(|mask| mask as u8)
+            .filter(|mask| mask.count_ones() == density)
+            .collect();
+
+        Self {
+            keystream: Keystream::new(b"asbs keyed pattern", seed.as_ref()),
+            masks,
+        }
+    }
+}
+
+impl Pattern for KeyedPattern {
+    type Sample = u8;
+
+    fn mask(&mut self, index: usize) -> Option<u8> {
+        // The rare rejected words fall through to the words of the following indices, so the
+        // mask is still a function of the index alone.
+        self.keystream.seek(index as u64);
+        Some(self.masks[self.keystream.below(self.masks.len() as u64) as usize])
+    }
+}
+
+impl fmt::Debug for KeyedPattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("KeyedPattern")
+            .field("masks", &self.masks)
+            .finish_non_exhaustive()
+    }
+}
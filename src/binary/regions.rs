@@ -0,0 +1,166 @@
+use crate::binary::{sample::Sample, Pattern};
+use std::ops::{Bound, Range, RangeBounds};
+
+/// A set of cover byte ranges that are excluded from embedding.
+///
+/// Covers often have parts that must stay intact, such as magic numbers and headers of
+/// files. Bytes within the regions are copied through unchanged by
+/// [`Carrier::skip_regions`] and ignored by [`Package::skip_regions`], regardless of what
+/// the pattern returns for them. Samples wider than a byte are skipped as a whole if any
+/// of their bytes is within the regions.
+///
+/// The sender and the receiver have to use the same regions. If the message is concealed
+/// with a [`Header`], a [`fingerprint`][Regions::fingerprint] of the regions is stored in
+/// it, and the package returns [`Error::RegionMismatch`] if the regions differ.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::binary::Regions;
+///
+/// let regions = Regions::new().skip(..54).skip(1024..2048);
+///
+/// assert!(regions.contains(0));
+/// assert!(!regions.contains(54));
+/// assert!(regions.contains(2047));
+/// ```
+///
+/// [`Carrier::skip_regions`]: crate::binary::Carrier::skip_regions
+/// [`Package::skip_regions`]: crate::binary::Package::skip_regions
+/// [`Header`]: crate::binary::Header
+/// [`Error::RegionMismatch`]: crate::Error::RegionMismatch
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct Regions {
+    ranges: Vec<Range<usize>>,
+}
+
+impl Regions {
+    /// Creates a new [`Regions`] that excludes nothing.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Excludes the supplied range of cover byte indices.
+    ///
+    /// Overlapping and adjacent ranges are merged, so the order in which the ranges are
+    /// added does not matter.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::binary::Regions;
+    ///
+    /// assert_eq!(
+    ///     Regions::new().skip(4..8).skip(..4),
+    ///     Regions::new().skip(0..8),
+    /// );
+    /// ```
+    #[must_use]
+    pub fn skip(mut self, range: impl RangeBounds<usize>) -> Self {
+        let start = match range.start_bound() {
+            Bound::Included(&start) => start,
+            Bound::Excluded(&start) => start.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+
+        let end = match range.end_bound() {
+            Bound::Included(&end) => end.saturating_add(1),
+            Bound::Excluded(&end) => end,
+            Bound::Unbounded => usize::MAX,
+        };
+
+        if start >= end {
+            return self;
+        }
+
+        let first = self.ranges.partition_point(|range| range.end < start);
+        let last = self.ranges.partition_point(|range| range.start <= end);
+
+        let merged = self.ranges[first..last]
+            .iter()
+            .fold(start..end, |merged, range| {
+                merged.start.min(range.start)..merged.end.max(range.end)
+            });
+
+        self.ranges.splice(first..last, [merged]);
+        self
+    }
+
+    /// Returns `true` if the byte at the supplied index is excluded.
+    #[must_use]
+    pub fn contains(&self, index: usize) -> bool {
+        self.intersects(index..index.saturating_add(1))
+    }
+
+    /// Returns `true` if any byte within the range is excluded.
+    fn intersects(&self, range: Range<usize>) -> bool {
+        let position = self
+            .ranges
+            .partition_point(|excluded| excluded.end <= range.start);
+
+        self.ranges
+            .get(position)
+            .is_some_and(|excluded| excluded.start < range.end)
+    }
+
+    /// Returns `true` if nothing is excluded.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Returns the CRC-32 checksum of the excluded ranges, which identifies them in a
+    /// [`Header`][crate::binary::Header].
+    #[must_use]
+    pub fn fingerprint(&self) -> u32 {
+        let mut hasher = crc32fast::Hasher::new();
+
+        for range in &self.ranges {
+            // Unbounded ranges end at the same value regardless of the width of `usize`.
+            let end = if range.end == usize::MAX {
+                u64::MAX
+            } else {
+                range.end as u64
+            };
+
+            hasher.update(&(range.start as u64).to_be_bytes());
+            hasher.update(&end.to_be_bytes());
+        }
+
+        hasher.finalize()
+    }
+}
+
+/// A pattern that returns an empty mask for the samples within the regions.
+pub(super) struct Skipping<'a, P> {
+    pattern: &'a mut P,
+    regions: Option<&'a Regions>,
+}
+
+impl<'a, P> Skipping<'a, P> {
+    pub(super) fn new(pattern: &'a mut P, regions: Option<&'a Regions>) -> Self {
+        Self { pattern, regions }
+    }
+}
+
+impl<P> Pattern for Skipping<'_, P>
+where
+    P: Pattern,
+{
+    type Sample = P::Sample;
+
+    fn mask(&mut self, index: usize) -> Option<<P::Sample as Sample>::Mask> {
+        let len = P::Sample::LEN;
+        let bytes = index * len..(index + 1) * len;
+
+        if self
+            .regions
+            .is_some_and(|regions| regions.intersects(bytes))
+        {
+            return Some(Default::default());
+        }
+
+        self.pattern.mask(index)
+    }
+}
@@ -0,0 +1,99 @@
+use crate::binary::{bits::Ones, sample::Sample};
+
+/// A report of an embedding performed by a [`Carrier`], which describes how the cover
+/// was changed.
+///
+/// The report is available from [`Carrier::report`] after every successful
+/// [`conceal`][crate::Conceal::conceal] with a carrier that uses [`Carrier::reporting`],
+/// regardless of the embedding mode, and can be used to tune patterns and to audit
+/// embeddings.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{binary, Conceal};
+///
+/// let mut package = Vec::new();
+/// let mut carrier = binary::Carrier::new(|_| Some(0b11), &mut package).reporting();
+///
+/// carrier.conceal([0xff].as_slice(), [0u8; 8].as_slice())?;
+///
+/// let report = carrier.report().unwrap();
+/// assert_eq!(report.embedded_bits, 8);
+/// assert_eq!(report.available_bits, 16);
+/// assert_eq!(report.touched_bytes, 4);
+/// assert_eq!(report.changed_bits, 8);
+/// assert_eq!(report.plane_changes, [4, 4, 0, 0, 0, 0, 0, 0]);
+/// assert_eq!(report.last_byte, Some(3));
+/// assert_eq!(report.utilization(), 0.5);
+/// # Ok::<(), asbs::Error>(())
+/// ```
+///
+/// [`Carrier`]: crate::binary::Carrier
+/// [`Carrier::report`]: crate::binary::Carrier::report
+/// [`Carrier::reporting`]: crate::binary::Carrier::reporting
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Report {
+    /// The number of bits of the concealed data, including any framing, checksum, and error
+    /// correction.
+    pub embedded_bits: u64,
+
+    /// The number of bits the cover and the pattern provide.
+    pub available_bits: u64,
+
+    /// The number of cover bytes within the samples that the data was embedded into.
+    pub touched_bytes: u64,
+
+    /// The number of cover bits that were changed, including the bits that LSB matching
+    /// carries into or borrows from.
+    pub changed_bits: u64,
+
+    /// The number of changed bits at every bit position of the samples, starting from the
+    /// least significant bit.
+    pub plane_changes: Vec<u64>,
+
+    /// The index of the last cover byte within the samples that the data was embedded into,
+    /// if any.
+    pub last_byte: Option<usize>,
+}
+
+impl Report {
+    pub(super) fn new<S: Sample>() -> Self {
+        Self {
+            embedded_bits: 0,
+            available_bits: 0,
+            touched_bytes: 0,
+            changed_bits: 0,
+            plane_changes: vec![0; 8 * S::LEN],
+            last_byte: None,
+        }
+    }
+
+    /// Returns the fraction of the available bits that the concealed data occupies.
+    ///
+    /// Codes such as [`Carrier::matrix_embedding`] use more cover bits than they embed,
+    /// which this fraction does not account for.
+    ///
+    /// [`Carrier::matrix_embedding`]: crate::binary::Carrier::matrix_embedding
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        if self.available_bits == 0 {
+            return 0.0;
+        }
+
+        self.embedded_bits as f64 / self.available_bits as f64
+    }
+
+    /// Records the embedding of data into the sample at the supplied index, given its words
+    /// before and after the embedding.
+    pub(super) fn record<S: Sample>(&mut self, index: usize, cover: u32, package: u32) {
+        self.touched_bytes += S::LEN as u64;
+        self.last_byte = self.last_byte.max(Some((index + 1) * S::LEN - 1));
+
+        for pow in Ones::from(cover ^ package) {
+            self.plane_changes[pow] += 1;
+            self.changed_bits += 1;
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! Sample types that covers are split into by [`Carrier`][super::Carrier] and
+//! [`Package`][super::Package].
+//!
+//! By default, every cover byte is a sample of its own. Audio and high bit depth images
+//! consist of wider samples, which are better addressed as a whole, so that a pattern can
+//! target the least significant bits of every sample by its index. The sample type of a
+//! [`Pattern`][super::Pattern] is its [`Pattern::Sample`][super::Pattern::Sample], and
+//! [`Words`][super::pattern::Words] turns a closure into a pattern over any sample type.
+//!
+//! Samples wider than a byte are available in both little-endian ([`Le`]) and big-endian
+//! ([`Be`]) byte order. Trailing cover bytes that do not form a whole sample are copied
+//! through unchanged.
+
+use std::marker::PhantomData;
+
+mod sealed {
+    pub trait Sealed {}
+}
+
+/// A type of cover samples.
+///
+/// This trait is sealed and implemented for [`u8`] as well as [`Le`] and [`Be`] of [`u16`],
+/// [`I24`], [`u32`], and [`f32`].
+pub trait Sample: sealed::Sealed {
+    /// The type of bit masks of the samples, which is as wide as the samples themselves.
+    ///
+    /// Masks of 24-bit samples are [`u32`], of which only the lower 24 bits are used.
+    /// Masks of [`f32`] samples apply to the bits of their IEEE 754 representation.
+    type Mask: Copy + Default + Into<u32>;
+
+    /// The size of each sample in bytes.
+    const LEN: usize;
+
+    /// Whether the samples are signed integers in two's complement representation.
+    const SIGNED: bool;
+
+    /// Whether the samples are IEEE 754 floating-point numbers, which store their sign
+    /// apart from their magnitude.
+    const FLOAT: bool;
+
+    /// Returns the bits of the sample stored in the first [`Sample::LEN`] bytes.
+    fn read(bytes: &[u8]) -> u32;
+
+    /// Stores the bits of the sample into the first [`Sample::LEN`] bytes.
+    fn write(bits: u32, bytes: &mut [u8]);
+}
+
+/// Samples stored in little-endian byte order.
+pub struct Le<T>(PhantomData<T>);
+
+/// Samples stored in big-endian byte order.
+pub struct Be<T>(PhantomData<T>);
+
+/// Signed 24-bit integer samples, which are common in audio files.
+pub enum I24 {}
+
+impl sealed::Sealed for u8 {}
+
+impl Sample for u8 {
+    type Mask = u8;
+
+    const LEN: usize = 1;
+
+    const SIGNED: bool = false;
+
+    const FLOAT: bool = false;
+
+    fn read(bytes: &[u8]) -> u32 {
+        u32::from(bytes[0])
+    }
+
+    fn write(bits: u32, bytes: &mut [u8]) {
+        bytes[0] = bits as u8;
+    }
+}
+
+macro_rules! impl_sample {
+    ($($word:ty => $mask:ty, $len:literal, $signed:literal, $float:literal;)*) => {$(
+        impl sealed::Sealed for Le<$word> {}
+
+        impl Sample for Le<$word> {
+            type Mask = $mask;
+
+            const LEN: usize = $len;
+
+            const SIGNED: bool = $signed;
+
+            const FLOAT: bool = $float;
+
+            fn read(bytes: &[u8]) -> u32 {
+                bytes[..$len]
+                    .iter()
+                    .rev()
+                    .fold(0, |bits, &byte| bits << 8 | u32::from(byte))
+            }
+
+            fn write(bits: u32, bytes: &mut [u8]) {
+                for (pow, byte) in bytes[..$len].iter_mut().enumerate() {
+                    *byte = (bits >> (pow * 8)) as u8;
+                }
+            }
+        }
+
+        impl sealed::Sealed for Be<$word> {}
+
+        impl Sample for Be<$word> {
+            type Mask = $mask;
+
+            const LEN: usize = $len;
+
+            const SIGNED: bool = $signed;
+
+            const FLOAT: bool = $float;
+
+            fn read(bytes: &[u8]) -> u32 {
+                bytes[..$len]
+                    .iter()
+                    .fold(0, |bits, &byte| bits << 8 | u32::from(byte))
+            }
+
+            fn write(bits: u32, bytes: &mut [u8]) {
+                for (pow, byte) in bytes[..$len].iter_mut().rev().enumerate() {
+                    *byte = (bits >> (pow * 8)) as u8;
+                }
+            }
+        }
+    )*};
+}
+
+impl_sample! {
+    u16 => u16, 2, false, false;
+    I24 => u32, 3, true, false;
+    u32 => u32, 4, false, false;
+    f32 => u32, 4, false, true;
+}
+
+/// Returns the bits of the mask that lie within a sample.
+pub(super) fn mask_bits<S: Sample>(mask: S::Mask) -> u32 {
+    mask.into() & (u32::MAX >> (32 - 8 * S::LEN))
+}
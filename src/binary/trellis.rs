@@ -0,0 +1,196 @@
+use crate::binary::keystream::Keystream;
+use std::fmt;
+
+/// The constraint height of the code, i.e. the number of rows of its submatrix.
+const HEIGHT: u32 = 7;
+const STATES: usize = 1 << HEIGHT;
+
+/// The largest width of the blocks, which bounds the memory of [`embed`] to 16 bytes per
+/// cover bit, i.e. 512 bytes per bit of the data.
+pub(super) const MAX_WIDTH: usize = 32;
+
+/// Statistics of a syndrome-trellis embedding performed by a [`Carrier`].
+///
+/// Syndrome-trellis codes embed every bit of the concealed data into a block of `width`
+/// cover bits selected by the pattern, as the syndrome of the cover bits under a
+/// parity-check matrix derived from a seed. The Viterbi algorithm finds the cover bits with
+/// the required syndrome whose changes have the least total cost.
+///
+/// See [`Carrier::syndrome_trellis`] for details.
+///
+/// [`Carrier`]: crate::binary::Carrier
+/// [`Carrier::syndrome_trellis`]: crate::binary::Carrier::syndrome_trellis
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[non_exhaustive]
+pub struct TrellisStatistics {
+    /// The number of cover bits each bit of the concealed data is embedded into.
+    pub width: u32,
+
+    /// The number of bits of the concealed data, including the [`Header`].
+    ///
+    /// [`Header`]: crate::binary::Header
+    pub embedded_bits: u64,
+
+    /// The number of cover bits selected by the pattern that the data occupies.
+    pub cover_bits: u64,
+
+    /// The number of cover bits selected by the pattern that were changed.
+    pub changed_bits: u64,
+
+    /// The total cost of the changed bits of the data, excluding the header.
+    pub cost: f64,
+}
+
+/// The seed of the parity-check matrix and the costs of changing cover samples.
+pub(super) struct Trellis {
+    pub(super) keystream: Keystream,
+    pub(super) costs: Box<dyn FnMut(usize) -> f64 + Send + Sync>,
+}
+
+impl fmt::Debug for Trellis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Trellis").finish_non_exhaustive()
+    }
+}
+
+/// Returns the column of the submatrix at the supplied offset within a block.
+///
+/// The first and the last rows are always set, which makes every syndrome reachable and
+/// keeps the code from degenerating into one of a lower height.
+fn column(keystream: &mut Keystream, offset: usize) -> u8 {
+    let bits = keystream.word_at(offset as u64) as u8 & (STATES - 1) as u8;
+    bits | 1 | 1 << (HEIGHT - 1)
+}
+
+/// Returns the cover bits whose syndrome is the message and whose changes have the least
+/// total cost, considering the first `message.len() * width` cover bits.
+pub(super) fn embed(
+    keystream: &mut Keystream,
+    width: usize,
+    cover: &[u8],
+    costs: &[f64],
+    message: &[u8],
+) -> Vec<u8> {
+    let columns: Vec<u8> = (0..width).map(|offset| column(keystream, offset)).collect();
+
+    let mut totals = [f64::INFINITY; STATES];
+    totals[0] = 0.0;
+
+    // The bit of each state that tells whether the column is set on the cheapest path.
+    let mut paths = Vec::with_capacity(message.len() * width);
+
+    for (block, &bit) in message.iter().enumerate() {
+        for (offset, &column) in columns.iter().enumerate() {
+            let position = block * width + offset;
+            let (unset_cost, set_cost) = match cover[position] {
+                0 => (0.0, costs[position]),
+                _ => (costs[position], 0.0),
+            };
+
+            let mut next = [f64::INFINITY; STATES];
+            let mut path = 0u128;
+
+            for (state, total) in next.iter_mut().enumerate() {
+                let unset = totals[state] + unset_cost;
+                let set = totals[state ^ usize::from(column)] + set_cost;
+
+                if set < unset {
+                    *total = set;
+                    path |= 1 << state;
+                } else {
+                    *total = unset;
+                }
+            }
+
+            totals = next;
+            paths.push(path);
+        }
+
+        // The lowest row is complete once the block ends, so it has to match the message.
+        let mut next = [f64::INFINITY; STATES];
+        for (state, total) in next.iter_mut().take(STATES / 2).enumerate() {
+            *total = totals[state << 1 | usize::from(bit)];
+        }
+
+        totals = next;
+    }
+
+    let mut state = (0..STATES)
+        .min_by(|&a, &b| totals[a].total_cmp(&totals[b]))
+        .unwrap_or_default();
+
+    let mut stego = vec![0; message.len() * width];
+    for (block, &bit) in message.iter().enumerate().rev() {
+        state = state << 1 | usize::from(bit);
+
+        for (offset, &column) in columns.iter().enumerate().rev() {
+            let position = block * width + offset;
+            let is_set = paths[position] >> state & 1 == 1;
+
+            if is_set {
+                stego[position] = 1;
+                state ^= usize::from(column);
+            }
+        }
+    }
+
+    stego
+}
+
+/// A decoder that computes the syndromes of consecutive blocks of cover bits.
+pub(super) struct Syndromes {
+    keystream: Keystream,
+    width: usize,
+    columns: Vec<u8>,
+    offset: usize,
+    state: u8,
+}
+
+impl Syndromes {
+    pub(super) fn new(keystream: Keystream, width: usize) -> Self {
+        Self {
+            keystream,
+            width,
+            columns: Vec::new(),
+            offset: 0,
+            state: 0,
+        }
+    }
+
+    /// Accepts the next cover bit and returns the next bit of the message once the block
+    /// is complete.
+    pub(super) fn push(&mut self, bit: u8) -> Option<u8> {
+        // Columns are generated as they are needed, so that a forged width costs nothing.
+        if self.offset == self.columns.len() {
+            let column = column(&mut self.keystream, self.offset);
+            self.columns.push(column);
+        }
+
+        if bit == 1 {
+            self.state ^= self.columns[self.offset];
+        }
+
+        self.offset += 1;
+        if self.offset < self.width {
+            return None;
+        }
+
+        self.offset = 0;
+
+        let bit = self.state & 1;
+        self.state >>= 1;
+
+        Some(bit)
+    }
+}
+
+/// Returns the largest width up to [`MAX_WIDTH`] with which the supplied number of data bits
+/// fits into the supplied number of cover bits, if any.
+pub(super) fn choose_width(data_bits: usize, cover_bits: usize) -> Option<usize> {
+    let width = match data_bits {
+        0 => 1,
+        _ => cover_bits / data_bits,
+    };
+
+    Some(width.min(MAX_WIDTH)).filter(|&width| width > 0)
+}
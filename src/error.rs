@@ -0,0 +1,188 @@
+#[cfg(feature = "encrypt")]
+use crate::binary::AuthenticationError;
+use crate::binary::{HeaderError, UncorrectableError, VerificationError};
+use std::{error, fmt, io};
+
+/// The error type of the [`Conceal`][crate::Conceal] and [`Reveal`][crate::Reveal]
+/// implementations of this library.
+///
+/// It converts into an [`io::Error`] of the closest kind, so that the `?` operator can be
+/// used in functions returning [`io::Result`].
+///
+/// # Examples
+///
+/// Telling a payload that does not fit apart from other errors:
+///
+/// ```
+/// use asbs::{binary, Conceal, Error};
+///
+/// let mut package = Vec::new();
+///
+/// let result = binary::Carrier::with_embedded_len(21, |_| Some(1), &mut package)
+///     .conceal(b"a very secret message".as_slice(), [0u8; 16].as_slice());
+///
+/// assert!(matches!(
+///     result,
+///     Err(Error::InsufficientCapacity {
+///         needed_bits: 232,
+///         available_bits: 16,
+///     }),
+/// ));
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Error {
+    /// The cover ended before the payload was concealed entirely.
+    InsufficientCapacity {
+        /// The number of bits required to conceal the payload, including any framing and
+        /// error correction.
+        ///
+        /// If the carrier was not given the payload length, the rest of the payload is not
+        /// read, and this is only a lower bound.
+        needed_bits: u64,
+
+        /// The number of bits the cover and the pattern provide.
+        available_bits: u64,
+    },
+
+    /// The pattern ended before the payload was concealed entirely.
+    PatternExhausted,
+
+    /// The package ended before the message length could be read.
+    TruncatedLength,
+
+    /// The payload is shorter than the length it was declared with.
+    TruncatedPayload {
+        /// The declared length of the payload in bytes.
+        expected: u64,
+
+        /// The actual length of the payload in bytes.
+        actual: u64,
+    },
+
+    /// The package contains no valid [`Header`][crate::binary::Header].
+    Header(HeaderError),
+
+    /// The concealed data is too corrupted to be corrected by the error correction code.
+    Uncorrectable(UncorrectableError),
+
+    /// The checksum of the revealed message does not match the concealed one, which means
+    /// that either the pattern is wrong or the package is corrupted.
+    Verification(VerificationError),
+
+    /// The encrypted message failed to authenticate, which means that either the password
+    /// or the pattern is wrong, or that the package was tampered with.
+    #[cfg(feature = "encrypt")]
+    Authentication(AuthenticationError),
+
+    /// The message length is required, but not known.
+    UnknownLength,
+
+    /// The [`Regions`][crate::binary::Regions] skipped by the package differ from the ones
+    /// the message was concealed with.
+    RegionMismatch,
+
+    /// The message was concealed by syndrome-trellis coding, but the package was not
+    /// supplied the seed of its parity-check matrix.
+    MissingSeed,
+
+    /// The cover or the package is not a valid file of the expected format.
+    Format(Box<dyn error::Error + Send + Sync>),
+
+    /// An I/O error occurred while reading or writing data.
+    Io(io::Error),
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::InsufficientCapacity {
+                needed_bits,
+                available_bits,
+            } => write!(
+                f,
+                "cover provides {available_bits} bits, but {needed_bits} bits are needed"
+            ),
+            Self::PatternExhausted => f.write_str("pattern ended before the payload was concealed"),
+            Self::TruncatedLength => f.write_str("package ended before the length was read"),
+            Self::TruncatedPayload { expected, actual } => write!(
+                f,
+                "payload is {actual} bytes long, but {expected} bytes were declared"
+            ),
+            Self::Header(error) => error.fmt(f),
+            Self::Uncorrectable(error) => error.fmt(f),
+            Self::Verification(error) => error.fmt(f),
+            #[cfg(feature = "encrypt")]
+            Self::Authentication(error) => error.fmt(f),
+            Self::UnknownLength => f.write_str("message length is not known"),
+            Self::RegionMismatch => {
+                f.write_str("skipped regions differ from the ones the message was concealed with")
+            }
+            Self::MissingSeed => f.write_str("syndrome-trellis seed was not supplied"),
+            Self::Format(error) => write!(f, "invalid format: {error}"),
+            Self::Io(error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Self::Header(error) => Some(error),
+            Self::Uncorrectable(error) => Some(error),
+            Self::Verification(error) => Some(error),
+            #[cfg(feature = "encrypt")]
+            Self::Authentication(error) => Some(error),
+            Self::Format(error) => Some(error.as_ref()),
+            Self::Io(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        Self::Io(error)
+    }
+}
+
+impl From<HeaderError> for Error {
+    fn from(error: HeaderError) -> Self {
+        Self::Header(error)
+    }
+}
+
+impl From<UncorrectableError> for Error {
+    fn from(error: UncorrectableError) -> Self {
+        Self::Uncorrectable(error)
+    }
+}
+
+impl From<VerificationError> for Error {
+    fn from(error: VerificationError) -> Self {
+        Self::Verification(error)
+    }
+}
+
+#[cfg(feature = "encrypt")]
+impl From<AuthenticationError> for Error {
+    fn from(error: AuthenticationError) -> Self {
+        Self::Authentication(error)
+    }
+}
+
+impl From<Error> for io::Error {
+    fn from(error: Error) -> Self {
+        let kind = match error {
+            Error::Io(error) => return error,
+            Error::InsufficientCapacity { .. } | Error::PatternExhausted => {
+                io::ErrorKind::WriteZero
+            }
+            Error::TruncatedLength | Error::TruncatedPayload { .. } => io::ErrorKind::UnexpectedEof,
+            Error::UnknownLength | Error::MissingSeed => io::ErrorKind::InvalidInput,
+            _ => io::ErrorKind::InvalidData,
+        };
+
+        io::Error::new(kind, error)
+    }
+}
@@ -0,0 +1,124 @@
+//! Adapters that let [`binary`][crate::binary] carriers and packages work with the samples
+//! of structured file formats instead of their raw bytes.
+//!
+//! Concealing a message in the raw bytes of a file destroys it as soon as the file is
+//! compressed or has headers. The adapters decode a file, expose the low-order byte of each
+//! of its samples as the cover, and encode the file back once the samples are modified.
+//! The samples of [`jpeg`] images are their quantized DCT coefficients rather than pixels,
+//! and those of [`gif`] images are palette indices.
+//!
+//! Every adapter provides the same methods:
+//!
+//! 1. `decode`, which reads the file,
+//! 2. `samples`, which returns the cover bytes to pass to a carrier or a package,
+//! 3. `set_samples`, which replaces the cover bytes with the package written by a carrier,
+//! 4. `encode`, which writes the modified file.
+//!
+//! Adapters of formats with channels also provide `channel_samples`, which returns the
+//! samples of a single channel for [`analysis`][crate::analysis].
+//!
+//! ## Examples
+//!
+//! Concealing a message in a PNG image:
+//!
+//! ```no_run
+//! # #[cfg(feature = "png")]
+//! # {
+//! use asbs::{binary, formats::png, Conceal};
+//! use std::fs::File;
+//!
+//! let payload = b"a very secret message";
+//!
+//! let mut image = png::Image::decode(File::open("cover.png")?)?.skip_alpha();
+//!
+//! let mut package = Vec::new();
+//! binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+//!     .conceal(payload.as_slice(), image.samples().as_slice())?;
+//!
+//! image.set_samples(&package);
+//! image.encode(File::create("package.png")?)?;
+//! # }
+//! # Ok::<(), asbs::Error>(())
+//! ```
+
+pub mod bmp;
+pub mod gif;
+pub mod jpeg;
+pub mod netpbm;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod wav;
+
+/// The location of the samples in the bytes of a decoded file.
+#[derive(Debug, Clone)]
+struct Layout {
+    /// The offset of the first row.
+    offset: usize,
+
+    /// The number of rows.
+    rows: usize,
+
+    /// The number of samples in each row.
+    row_len: usize,
+
+    /// The number of bytes between the starts of consecutive rows.
+    stride: usize,
+
+    /// The size of each sample in bytes.
+    sample_len: usize,
+
+    /// The offset of the least significant byte within each sample.
+    low_byte: usize,
+
+    /// Whether each channel is excluded from the cover.
+    skipped: Vec<bool>,
+}
+
+impl Layout {
+    fn channels(&self) -> usize {
+        self.skipped.len()
+    }
+
+    fn skip_channel(&mut self, channel: usize) {
+        assert!(channel < self.channels(), "channel index is out of range");
+        self.skipped[channel] = true;
+    }
+
+    /// Returns the offsets of the least significant bytes of the samples in the cover.
+    fn offsets(&self) -> impl Iterator<Item = usize> + '_ {
+        (0..self.rows).flat_map(move |row| {
+            (0..self.row_len)
+                .filter(|sample| !self.skipped[sample % self.channels()])
+                .map(move |sample| {
+                    self.offset + row * self.stride + sample * self.sample_len + self.low_byte
+                })
+        })
+    }
+
+    fn samples(&self, bytes: &[u8]) -> Vec<u8> {
+        self.offsets().map(|offset| bytes[offset]).collect()
+    }
+
+    /// Returns the least significant bytes of the samples of the channel, regardless of
+    /// whether it is excluded from the cover.
+    fn channel_samples(&self, bytes: &[u8], channel: usize) -> Vec<u8> {
+        assert!(channel < self.channels(), "channel index is out of range");
+
+        (0..self.rows)
+            .flat_map(|row| {
+                (channel..self.row_len)
+                    .step_by(self.channels())
+                    .map(move |sample| {
+                        self.offset + row * self.stride + sample * self.sample_len + self.low_byte
+                    })
+            })
+            .map(|offset| bytes[offset])
+            .collect()
+    }
+
+    fn set_samples(&self, bytes: &mut [u8], samples: &[u8]) {
+        for (offset, &sample) in self.offsets().zip(samples) {
+            bytes[offset] = sample;
+        }
+    }
+}
@@ -0,0 +1,216 @@
+//! Uncompressed BMP images with 24 or 32 bits per pixel.
+//!
+//! The file and information headers, the color masks, and the row padding are written back
+//! untouched, and only the pixel data is exposed as the cover.
+
+use crate::{formats::Layout, Error};
+use std::io::{Read, Write};
+
+const FILE_HEADER_LEN: usize = 14;
+const CORE_HEADER_LEN: usize = 12;
+
+const BI_RGB: u32 = 0;
+const BI_BITFIELDS: u32 = 3;
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// A decoded BMP image whose pixel channels act as the cover.
+///
+/// Channels are ordered as stored in the file, i.e. blue, green, red, and, in 32-bit images,
+/// alpha or an unused byte. Rows are visited in the order they are stored in, which is
+/// bottom to top for most images.
+///
+/// # Examples
+///
+/// Concealing a message in a BMP image:
+///
+/// ```no_run
+/// use asbs::{binary, formats::bmp, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut image = bmp::Image::decode(File::open("cover.bmp")?)?.skip_alpha();
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+///     .conceal(payload.as_slice(), image.samples().as_slice())?;
+///
+/// image.set_samples(&package);
+/// image.encode(File::create("package.bmp")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Image {
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+    layout: Layout,
+}
+
+impl Image {
+    /// Decodes a BMP image from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the image is not a valid uncompressed BMP image with 24 or 32
+    /// bits per pixel.
+    pub fn decode(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < FILE_HEADER_LEN + CORE_HEADER_LEN || &bytes[..2] != b"BM" {
+            return Err(Error::Format("missing BMP header".into()));
+        }
+
+        let offset = read_u32(&bytes, 10) as usize;
+        let header_len = read_u32(&bytes, FILE_HEADER_LEN) as usize;
+
+        let (width, height, bits_per_pixel, compression) = if header_len == CORE_HEADER_LEN {
+            (
+                i64::from(read_u16(&bytes, 18)),
+                i64::from(read_u16(&bytes, 20)),
+                read_u16(&bytes, 24),
+                BI_RGB,
+            )
+        } else if header_len >= 40 && bytes.len() >= FILE_HEADER_LEN + 40 {
+            (
+                i64::from(read_u32(&bytes, 18) as i32),
+                i64::from(read_u32(&bytes, 22) as i32),
+                read_u16(&bytes, 28),
+                read_u32(&bytes, 30),
+            )
+        } else {
+            return Err(Error::Format("unsupported BMP information header".into()));
+        };
+
+        if compression != BI_RGB && !(compression == BI_BITFIELDS && bits_per_pixel == 32) {
+            return Err(Error::Format(
+                "compressed BMP images are not supported".into(),
+            ));
+        }
+
+        if bits_per_pixel != 24 && bits_per_pixel != 32 {
+            return Err(Error::Format(
+                format!("unsupported BMP depth of {bits_per_pixel} bits").into(),
+            ));
+        }
+
+        let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height.abs())) else {
+            return Err(Error::Format("invalid BMP dimensions".into()));
+        };
+
+        let channels = bits_per_pixel as usize / 8;
+
+        let Some((stride, len)) = (width as usize)
+            .checked_mul(bits_per_pixel.into())
+            .map(|bits| bits.div_ceil(32) * 4)
+            .and_then(|stride| Some((stride, stride.checked_mul(height as usize)?)))
+        else {
+            return Err(Error::Format("invalid BMP dimensions".into()));
+        };
+
+        if offset.checked_add(len).is_none_or(|end| end > bytes.len()) {
+            return Err(Error::Format("BMP pixel data is truncated".into()));
+        }
+
+        let layout = Layout {
+            offset,
+            rows: height as usize,
+            row_len: width as usize * channels,
+            stride,
+            sample_len: 1,
+            low_byte: 0,
+            skipped: vec![false; channels],
+        };
+
+        Ok(Self {
+            bytes,
+            width,
+            height,
+            layout,
+        })
+    }
+
+    /// Excludes the fourth byte of 32-bit pixels, which holds either alpha or nothing at
+    /// all, from the cover.
+    #[must_use]
+    pub fn skip_alpha(self) -> Self {
+        match self.channels() {
+            4 => self.skip_channel(3),
+            _ => self,
+        }
+    }
+
+    /// Excludes the channel with the supplied index from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn skip_channel(mut self, channel: usize) -> Self {
+        self.layout.skip_channel(channel);
+        self
+    }
+
+    /// Returns the width of the image in pixels.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of channels of each pixel.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.layout.channels()
+    }
+
+    /// Returns the samples of the channels in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        self.layout.samples(&self.bytes)
+    }
+
+    /// Returns the samples of the channel with the supplied index, including the channels
+    /// excluded from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: usize) -> Vec<u8> {
+        self.layout.channel_samples(&self.bytes, channel)
+    }
+
+    /// Replaces the samples returned by [`Image::samples`] with the supplied ones.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        self.layout.set_samples(&mut self.bytes, samples);
+    }
+
+    /// Encodes the image into the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing.
+    pub fn encode(&self, mut writer: impl Write) -> Result<(), Error> {
+        writer.write_all(&self.bytes)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
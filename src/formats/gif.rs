@@ -0,0 +1,876 @@
+//! GIF images, whose palette indices or palette orders act as the cover.
+//!
+//! The LZW-compressed frames are decoded into palette indices and compressed again when the
+//! image is encoded. All extensions, such as the animation and comment ones, are written
+//! back untouched, apart from the transparent color indices, which follow the palettes
+//! when they are reordered.
+//!
+//! There are two ways to conceal a message in a GIF image:
+//!
+//! 1. In the least significant bits of the palette indices of the pixels, see
+//!    [`Image::samples`]. Neighboring entries of a palette may have very different colors,
+//!    so the palettes should be sorted by luminance with [`Image::sort_palettes`] first.
+//! 2. In the order of the entries of the palettes, see [`Image::palette_samples`], which
+//!    leaves the pixels unchanged at the expense of a much smaller capacity.
+//!
+//! The two ways cannot be combined, as the latter destroys the order of the former.
+
+use crate::Error;
+use std::{
+    collections::HashMap,
+    io::{Read, Write},
+};
+
+const EXTENSION: u8 = 0x21;
+const IMAGE: u8 = 0x2c;
+const TRAILER: u8 = 0x3b;
+const GRAPHIC_CONTROL: u8 = 0xf9;
+
+/// The largest number of codes in an LZW dictionary.
+const MAX_CODES: usize = 1 << 12;
+
+type Color = [u8; 3];
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// Returns the luminance of the color, scaled by 1000.
+fn luminance([red, green, blue]: Color) -> u32 {
+    299 * u32::from(red) + 587 * u32::from(green) + 114 * u32::from(blue)
+}
+
+/// A frame, or an image, of a GIF file.
+#[derive(Debug, Clone)]
+struct Frame {
+    /// The image descriptor, including its separator.
+    descriptor: [u8; 10],
+
+    /// The index of the local palette of the frame, if any.
+    palette: Option<usize>,
+
+    /// The index of the block holding the graphic control extension of the frame, if any.
+    control: Option<usize>,
+
+    min_code_size: u8,
+    indices: Vec<u8>,
+}
+
+/// A part of a GIF file.
+#[derive(Debug, Clone)]
+enum Block {
+    Raw(Vec<u8>),
+    Frame(Frame),
+}
+
+/// A decoded GIF image whose palette indices or palette orders act as the cover.
+///
+/// The samples returned by [`Image::samples`] are the palette indices of the pixels of all
+/// frames, and only their least significant bits are written back by
+/// [`Image::set_samples`]. Pixels whose index differs from the transparent one only in the
+/// least significant bit are excluded from the cover, so that no pixel changes its
+/// transparency.
+///
+/// # Examples
+///
+/// Concealing a message in the palette indices of a GIF image:
+///
+/// ```no_run
+/// use asbs::{binary, formats::gif, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut image = gif::Image::decode(File::open("cover.gif")?)?.sort_palettes();
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+///     .conceal(payload.as_slice(), image.samples().as_slice())?;
+///
+/// image.set_samples(&package);
+/// image.encode(File::create("package.gif")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+///
+/// Concealing a message in the order of the palette entries:
+///
+/// ```no_run
+/// use asbs::{binary, formats::gif, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a secret";
+///
+/// let mut image = gif::Image::decode(File::open("cover.gif")?)?;
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0xff), &mut package)
+///     .conceal(payload.as_slice(), image.palette_samples().as_slice())?;
+///
+/// image.set_palette_samples(&package);
+/// image.encode(File::create("package.gif")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Image {
+    /// The header and the logical screen descriptor.
+    header: [u8; 13],
+
+    /// The global palette, if any, followed by the local palettes in the order of frames.
+    palettes: Vec<Vec<Color>>,
+
+    /// The index of the global palette, if any.
+    global: Option<usize>,
+
+    blocks: Vec<Block>,
+}
+
+impl Image {
+    /// Decodes a GIF image from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the image is not a valid GIF image.
+    pub fn decode(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 13 || !matches!(&bytes[..6], b"GIF87a" | b"GIF89a") {
+            return Err(Error::Format("missing GIF header".into()));
+        }
+
+        let mut image = Self {
+            header: bytes[..13].try_into().unwrap(),
+            palettes: Vec::new(),
+            global: None,
+            blocks: Vec::new(),
+        };
+
+        let mut offset = 13;
+
+        if bytes[10] & 0x80 != 0 {
+            let (palette, end) = read_palette(&bytes, offset, bytes[10])?;
+            image.global = Some(image.palettes.len());
+            image.palettes.push(palette);
+            offset = end;
+        }
+
+        let mut control = None;
+        loop {
+            match bytes.get(offset) {
+                Some(&EXTENSION) => {
+                    let end = skip_sub_blocks(&bytes, (offset + 2).min(bytes.len()))?;
+
+                    if bytes.get(offset + 1) == Some(&GRAPHIC_CONTROL) && end - offset == 8 {
+                        control = Some(image.blocks.len());
+                    }
+
+                    image.blocks.push(Block::Raw(bytes[offset..end].to_vec()));
+                    offset = end;
+                }
+                Some(&IMAGE) => {
+                    let Some(descriptor) = bytes.get(offset..offset + 10) else {
+                        return Err(Error::Format("GIF image descriptor is truncated".into()));
+                    };
+                    let descriptor: [u8; 10] = descriptor.try_into().unwrap();
+                    offset += 10;
+
+                    let mut palette = None;
+                    if descriptor[9] & 0x80 != 0 {
+                        let (local, end) = read_palette(&bytes, offset, descriptor[9])?;
+                        palette = Some(image.palettes.len());
+                        image.palettes.push(local);
+                        offset = end;
+                    }
+
+                    let Some(&min_code_size) = bytes.get(offset) else {
+                        return Err(Error::Format("GIF image data is truncated".into()));
+                    };
+
+                    if !(2..=8).contains(&min_code_size) {
+                        return Err(Error::Format("invalid GIF LZW code size".into()));
+                    }
+
+                    let end = skip_sub_blocks(&bytes, offset + 1)?;
+                    let data: Vec<u8> = SubBlocks::new(&bytes[offset + 1..end])
+                        .flatten()
+                        .copied()
+                        .collect();
+                    offset = end;
+
+                    let len = usize::from(read_u16(&descriptor, 5))
+                        * usize::from(read_u16(&descriptor, 7));
+                    let indices = lzw_decode(&data, min_code_size, len)?;
+                    if indices.len() < len {
+                        return Err(Error::Format("GIF image data is truncated".into()));
+                    }
+
+                    image.blocks.push(Block::Frame(Frame {
+                        descriptor,
+                        palette,
+                        control: control.take(),
+                        min_code_size,
+                        indices,
+                    }));
+                }
+                Some(&TRAILER) => {
+                    image.blocks.push(Block::Raw(bytes[offset..].to_vec()));
+                    break;
+                }
+                _ => return Err(Error::Format("invalid GIF block".into())),
+            }
+        }
+
+        Ok(image)
+    }
+
+    fn frames(&self) -> impl Iterator<Item = &Frame> + '_ {
+        self.blocks.iter().filter_map(|block| match block {
+            Block::Frame(frame) => Some(frame),
+            Block::Raw(_) => None,
+        })
+    }
+
+    /// Returns the index of the palette the frame uses, if any.
+    fn palette_of(&self, frame: &Frame) -> Option<usize> {
+        frame.palette.or(self.global)
+    }
+
+    /// Returns the transparent index of the frame, if any.
+    fn transparent_index(&self, frame: &Frame) -> Option<u8> {
+        match &self.blocks[frame.control?] {
+            Block::Raw(control) if control[3] & 1 != 0 => Some(control[6]),
+            _ => None,
+        }
+    }
+
+    /// Returns whether the pixel with the supplied palette index is in the cover.
+    fn is_usable(&self, frame: &Frame, index: u8) -> bool {
+        let palette_len = self
+            .palette_of(frame)
+            .map_or(0, |palette| self.palettes[palette].len());
+
+        usize::from(index | 1) < palette_len
+            && self
+                .transparent_index(frame)
+                .is_none_or(|transparent| transparent >> 1 != index >> 1)
+    }
+
+    /// Reorders the palette, where `order` holds the old index of every new entry, and
+    /// changes the indices that refer to it accordingly.
+    fn reorder(&mut self, palette: usize, order: &[usize]) {
+        let mut mapping = vec![0; order.len()];
+        for (new, &old) in order.iter().enumerate() {
+            mapping[old] = new as u8;
+        }
+
+        let colors = &self.palettes[palette];
+        self.palettes[palette] = order.iter().map(|&old| colors[old]).collect();
+
+        let map = |index: u8| mapping.get(usize::from(index)).copied().unwrap_or(index);
+
+        if self.global == Some(palette) {
+            self.header[11] = map(self.header[11]);
+        }
+
+        let mut controls = Vec::new();
+        for block in &mut self.blocks {
+            if let Block::Frame(frame) = block {
+                if frame.palette.or(self.global) == Some(palette) {
+                    frame
+                        .indices
+                        .iter_mut()
+                        .for_each(|index| *index = map(*index));
+                    controls.extend(frame.control);
+                }
+            }
+        }
+
+        for control in controls {
+            if let Block::Raw(control) = &mut self.blocks[control] {
+                control[6] = map(control[6]);
+            }
+        }
+    }
+
+    /// Sorts the entries of every palette by their luminance, so that entries that differ
+    /// only in the least significant bit of their index have similar colors.
+    ///
+    /// The pixels look the same, as their indices change along with the palettes.
+    #[must_use]
+    pub fn sort_palettes(mut self) -> Self {
+        for palette in 0..self.palettes.len() {
+            let colors = &self.palettes[palette];
+
+            let mut order: Vec<usize> = (0..colors.len()).collect();
+            order.sort_by_key(|&index| (luminance(colors[index]), colors[index]));
+
+            self.reorder(palette, &order);
+        }
+
+        self
+    }
+
+    /// Returns the width of the logical screen in pixels.
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        read_u16(&self.header, 6)
+    }
+
+    /// Returns the height of the logical screen in pixels.
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        read_u16(&self.header, 8)
+    }
+
+    /// Returns the number of frames of the image.
+    #[must_use]
+    pub fn frame_count(&self) -> usize {
+        self.frames().count()
+    }
+
+    /// Returns the colors of the pixels of the frame with the supplied index, or [`None`]
+    /// for the transparent pixels and the pixels whose index is not in the palette.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::frame_count`].
+    #[must_use]
+    pub fn colors(&self, frame: usize) -> Vec<Option<[u8; 3]>> {
+        let frame = self
+            .frames()
+            .nth(frame)
+            .expect("frame index is out of range");
+
+        let palette = self
+            .palette_of(frame)
+            .map_or(&[][..], |palette| &self.palettes[palette]);
+        let transparent = self.transparent_index(frame);
+
+        frame
+            .indices
+            .iter()
+            .map(|&index| {
+                (Some(index) != transparent)
+                    .then(|| palette.get(usize::from(index)).copied())
+                    .flatten()
+            })
+            .collect()
+    }
+
+    /// Returns the palette indices of the pixels of all frames that are in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        (0..self.frame_count())
+            .flat_map(|frame| self.frame_samples(frame))
+            .collect()
+    }
+
+    /// Returns the palette indices of the pixels of the frame with the supplied index that
+    /// are in the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::frame_count`].
+    #[must_use]
+    pub fn frame_samples(&self, frame: usize) -> Vec<u8> {
+        let frame = self
+            .frames()
+            .nth(frame)
+            .expect("frame index is out of range");
+
+        frame
+            .indices
+            .iter()
+            .copied()
+            .filter(|&index| self.is_usable(frame, index))
+            .collect()
+    }
+
+    /// Replaces the least significant bits of the palette indices returned by
+    /// [`Image::samples`] with those of the supplied samples.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        let mut samples = samples.iter();
+
+        for block in 0..self.blocks.len() {
+            let Block::Frame(frame) = &self.blocks[block] else {
+                continue;
+            };
+
+            let usable: Vec<bool> = frame
+                .indices
+                .iter()
+                .map(|&index| self.is_usable(frame, index))
+                .collect();
+
+            let Block::Frame(frame) = &mut self.blocks[block] else {
+                unreachable!();
+            };
+
+            for (index, is_usable) in frame.indices.iter_mut().zip(usable) {
+                if !is_usable {
+                    continue;
+                }
+
+                let Some(&sample) = samples.next() else {
+                    return;
+                };
+
+                *index = *index & !1 | sample & 1;
+            }
+        }
+    }
+
+    /// Returns the positions of the entries of the palette whose colors are unique, which
+    /// are the only ones whose order can be told.
+    fn unique_entries(&self, palette: usize) -> Vec<usize> {
+        let colors = &self.palettes[palette];
+
+        let mut counts = HashMap::new();
+        for &color in colors {
+            *counts.entry(color).or_insert(0) += 1;
+        }
+
+        (0..colors.len())
+            .filter(|&position| counts[&colors[position]] == 1)
+            .collect()
+    }
+
+    /// Returns how many bytes the order of the palette can hold.
+    fn palette_capacity(&self, palette: usize) -> usize {
+        let mut factorial = Natural::from_bytes(&[1]);
+        for radix in 2..=self.unique_entries(palette).len() {
+            factorial.mul_add(radix as u32, 0);
+        }
+
+        (factorial.bit_len() - 1) / 8
+    }
+
+    /// Returns the bytes held by the order of the entries of the palettes.
+    ///
+    /// Every palette holds as many bytes as fit into the number of the orders of its
+    /// entries with unique colors, i.e. 210 bytes for a palette of 256 unique colors.
+    /// The bytes are read from the global palette first, followed by the local palettes
+    /// in the order of frames.
+    #[must_use]
+    pub fn palette_samples(&self) -> Vec<u8> {
+        (0..self.palettes.len())
+            .flat_map(|palette| {
+                let entries = self.unique_entries(palette);
+
+                let colors = &self.palettes[palette];
+                let mut remaining: Vec<Color> =
+                    entries.iter().map(|&position| colors[position]).collect();
+                remaining.sort_unstable();
+
+                let digits: Vec<usize> = entries
+                    .iter()
+                    .map(|&position| {
+                        let digit = remaining.binary_search(&colors[position]).unwrap();
+                        remaining.remove(digit);
+                        digit
+                    })
+                    .collect();
+
+                let mut value = Natural::from_bytes(&[]);
+                for (radix, &digit) in (1..=entries.len()).zip(digits.iter().rev()) {
+                    value.mul_add(radix as u32, digit as u32);
+                }
+
+                value.to_bytes(self.palette_capacity(palette))
+            })
+            .collect()
+    }
+
+    /// Reorders the entries of the palettes so that they hold the supplied bytes, which
+    /// are then returned by [`Image::palette_samples`].
+    ///
+    /// The pixels look the same, as their indices change along with the palettes. Excess
+    /// samples are ignored, and the palettes whose bytes are not supplied are left
+    /// unchanged.
+    pub fn set_palette_samples(&mut self, samples: &[u8]) {
+        let mut samples = samples;
+
+        for palette in 0..self.palettes.len() {
+            let len = self.palette_capacity(palette);
+            if samples.len() < len {
+                return;
+            }
+
+            let (bytes, rest) = samples.split_at(len);
+            samples = rest;
+
+            let entries = self.unique_entries(palette);
+
+            let colors = &self.palettes[palette];
+            let mut remaining: Vec<usize> = entries.clone();
+            remaining.sort_unstable_by_key(|&position| colors[position]);
+
+            let mut value = Natural::from_bytes(bytes);
+
+            let mut order: Vec<usize> = (0..colors.len()).collect();
+            for (slot, radix) in entries.iter().zip((1..=entries.len()).rev()) {
+                let digit = value.div_rem(radix as u32) as usize;
+                order[*slot] = remaining.remove(digit);
+            }
+
+            self.reorder(palette, &order);
+        }
+    }
+
+    /// Encodes the image into the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing.
+    pub fn encode(&self, mut writer: impl Write) -> Result<(), Error> {
+        writer.write_all(&self.header)?;
+
+        if let Some(global) = self.global {
+            writer.write_all(self.palettes[global].as_flattened())?;
+        }
+
+        for block in &self.blocks {
+            match block {
+                Block::Raw(bytes) => writer.write_all(bytes)?,
+                Block::Frame(frame) => {
+                    writer.write_all(&frame.descriptor)?;
+
+                    if let Some(palette) = frame.palette {
+                        writer.write_all(self.palettes[palette].as_flattened())?;
+                    }
+
+                    writer.write_all(&[frame.min_code_size])?;
+
+                    let data = lzw_encode(&frame.indices, frame.min_code_size);
+                    for chunk in data.chunks(255) {
+                        writer.write_all(&[chunk.len() as u8])?;
+                        writer.write_all(chunk)?;
+                    }
+
+                    writer.write_all(&[0])?;
+                }
+            }
+        }
+
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reads the palette of the size given by the packed field, returning it along with the
+/// offset of its end.
+fn read_palette(bytes: &[u8], offset: usize, packed: u8) -> Result<(Vec<Color>, usize), Error> {
+    let end = offset + 3 * (2 << (packed & 0x7));
+
+    let Some(colors) = bytes.get(offset..end) else {
+        return Err(Error::Format("GIF palette is truncated".into()));
+    };
+
+    let palette = colors
+        .chunks_exact(3)
+        .map(|color| color.try_into().unwrap())
+        .collect();
+
+    Ok((palette, end))
+}
+
+/// Returns the offset of the end of the sub-blocks starting at the supplied offset.
+fn skip_sub_blocks(bytes: &[u8], mut offset: usize) -> Result<usize, Error> {
+    loop {
+        let Some(&len) = bytes.get(offset) else {
+            return Err(Error::Format("GIF data sub-blocks are truncated".into()));
+        };
+
+        offset += 1 + usize::from(len);
+
+        if len == 0 {
+            return Ok(offset);
+        }
+    }
+}
+
+/// An iterator over the contents of data sub-blocks.
+struct SubBlocks<'a> {
+    bytes: &'a [u8],
+}
+
+impl<'a> SubBlocks<'a> {
+    fn new(bytes: &'a [u8]) -> Self {
+        Self { bytes }
+    }
+}
+
+impl<'a> Iterator for SubBlocks<'a> {
+    type Item = &'a [u8];
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (&len, rest) = self.bytes.split_first()?;
+        let len = usize::from(len).min(rest.len());
+
+        if len == 0 {
+            return None;
+        }
+
+        let (block, rest) = rest.split_at(len);
+        self.bytes = rest;
+
+        Some(block)
+    }
+}
+
+/// Decodes the LZW-compressed palette indices, stopping once the supplied number of indices
+/// is reached, as every code can expand to thousands of them.
+fn lzw_decode(data: &[u8], min_code_size: u8, limit: usize) -> Result<Vec<u8>, Error> {
+    let clear = 1 << min_code_size;
+    let end = clear + 1;
+
+    // Every entry is stored as its prefix code and its last index.
+    let mut entries: Vec<(Option<usize>, u8)> =
+        (0..=end).map(|index| (None, index as u8)).collect();
+    let mut code_size = min_code_size + 1;
+    let mut previous: Option<usize> = None;
+
+    let mut indices = Vec::new();
+    let mut string = Vec::new();
+
+    let (mut bits, mut len, mut position) = (0u32, 0, 0);
+    loop {
+        while len < code_size {
+            let Some(&byte) = data.get(position) else {
+                return Ok(indices);
+            };
+
+            bits |= u32::from(byte) << len;
+            len += 8;
+            position += 1;
+        }
+
+        let code = (bits & ((1 << code_size) - 1)) as usize;
+        bits >>= code_size;
+        len -= code_size;
+
+        if code == clear {
+            entries.truncate(end + 1);
+            code_size = min_code_size + 1;
+            previous = None;
+            continue;
+        }
+
+        if code == end {
+            return Ok(indices);
+        }
+
+        string.clear();
+        let first = match previous {
+            _ if code < entries.len() => {
+                let mut next = Some(code);
+                while let Some(code) = next {
+                    let (prefix, index) = entries[code];
+                    string.push(index);
+                    next = prefix;
+                }
+
+                string.reverse();
+                string[0]
+            }
+            Some(previous) if code == entries.len() => {
+                let mut next = Some(previous);
+                while let Some(code) = next {
+                    let (prefix, index) = entries[code];
+                    string.push(index);
+                    next = prefix;
+                }
+
+                string.reverse();
+                let first = string[0];
+                string.push(first);
+                first
+            }
+            _ => return Err(Error::Format("invalid LZW code in GIF image data".into())),
+        };
+
+        indices.extend_from_slice(&string);
+
+        if indices.len() >= limit {
+            indices.truncate(limit);
+            return Ok(indices);
+        }
+
+        if let Some(previous) = previous {
+            if entries.len() < MAX_CODES {
+                entries.push((Some(previous), first));
+
+                if entries.len() == 1 << code_size && code_size < 12 {
+                    code_size += 1;
+                }
+            }
+        }
+
+        previous = Some(code);
+    }
+}
+
+/// A writer of variable-length LZW codes, starting from the least significant bit.
+struct CodeWriter {
+    bytes: Vec<u8>,
+    bits: u32,
+    len: u8,
+}
+
+impl CodeWriter {
+    fn write(&mut self, code: usize, code_size: u8) {
+        self.bits |= (code as u32) << self.len;
+        self.len += code_size;
+
+        while self.len >= 8 {
+            self.bytes.push(self.bits as u8);
+            self.bits >>= 8;
+            self.len -= 8;
+        }
+    }
+
+    fn finish(mut self) -> Vec<u8> {
+        if self.len > 0 {
+            self.bytes.push(self.bits as u8);
+        }
+
+        self.bytes
+    }
+}
+
+/// Compresses the palette indices with LZW.
+fn lzw_encode(indices: &[u8], min_code_size: u8) -> Vec<u8> {
+    let clear = 1 << min_code_size;
+    let end = clear + 1;
+
+    let mut writer = CodeWriter {
+        bytes: Vec::new(),
+        bits: 0,
+        len: 0,
+    };
+
+    let mut entries: HashMap<(usize, u8), usize> = HashMap::new();
+    let mut next_code = end + 1;
+    let mut code_size = min_code_size + 1;
+
+    writer.write(clear, code_size);
+
+    // Indices that do not fit into the code size are masked, as decoders would.
+    let mask = (clear - 1) as u8;
+
+    let mut indices = indices.iter().map(|&index| index & mask);
+    let Some(first) = indices.next() else {
+        writer.write(end, code_size);
+        return writer.finish();
+    };
+
+    let mut prefix = usize::from(first);
+    for index in indices {
+        if let Some(&code) = entries.get(&(prefix, index)) {
+            prefix = code;
+            continue;
+        }
+
+        writer.write(prefix, code_size);
+
+        if next_code < MAX_CODES {
+            if next_code == 1 << code_size && code_size < 12 {
+                code_size += 1;
+            }
+
+            entries.insert((prefix, index), next_code);
+            next_code += 1;
+        } else {
+            writer.write(clear, code_size);
+            entries.clear();
+            next_code = end + 1;
+            code_size = min_code_size + 1;
+        }
+
+        prefix = usize::from(index);
+    }
+
+    writer.write(prefix, code_size);
+
+    // The decoder adds an entry for the last code, which may increase the code size.
+    if next_code < MAX_CODES && next_code == 1 << code_size && code_size < 12 {
+        code_size += 1;
+    }
+
+    writer.write(end, code_size);
+
+    writer.finish()
+}
+
+/// An arbitrarily large natural number, which the orders of palettes are converted to.
+struct Natural {
+    /// The 32-bit digits of the number, starting from the least significant one.
+    digits: Vec<u32>,
+}
+
+impl Natural {
+    /// Creates the number from its bytes in little-endian order.
+    fn from_bytes(bytes: &[u8]) -> Self {
+        let digits = bytes
+            .chunks(4)
+            .map(|chunk| {
+                chunk
+                    .iter()
+                    .rev()
+                    .fold(0, |digit, &byte| digit << 8 | u32::from(byte))
+            })
+            .collect();
+
+        Self { digits }
+    }
+
+    /// Returns the supplied number of the least significant bytes in little-endian order.
+    fn to_bytes(&self, len: usize) -> Vec<u8> {
+        self.digits
+            .iter()
+            .flat_map(|digit| digit.to_le_bytes())
+            .chain(std::iter::repeat(0))
+            .take(len)
+            .collect()
+    }
+
+    /// Returns the number of significant bits.
+    fn bit_len(&self) -> usize {
+        self.digits
+            .iter()
+            .rposition(|&digit| digit != 0)
+            .map_or(0, |top| {
+                32 * top + 32 - self.digits[top].leading_zeros() as usize
+            })
+    }
+
+    /// Multiplies the number by the factor and adds the term.
+    fn mul_add(&mut self, factor: u32, term: u32) {
+        let mut carry = u64::from(term);
+        for digit in &mut self.digits {
+            let value = u64::from(*digit) * u64::from(factor) + carry;
+            *digit = value as u32;
+            carry = value >> 32;
+        }
+
+        if carry > 0 {
+            self.digits.push(carry as u32);
+        }
+    }
+
+    /// Divides the number by the divisor, returning the remainder.
+    fn div_rem(&mut self, divisor: u32) -> u32 {
+        let mut remainder = 0u64;
+        for digit in self.digits.iter_mut().rev() {
+            let value = remainder << 32 | u64::from(*digit);
+            *digit = (value / u64::from(divisor)) as u32;
+            remainder = value % u64::from(divisor);
+        }
+
+        remainder as u32
+    }
+}
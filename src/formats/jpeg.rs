@@ -0,0 +1,866 @@
+//! Baseline JPEG images, whose quantized DCT coefficients act as the cover.
+//!
+//! The entropy-coded scans are Huffman-decoded into the quantized coefficients of every
+//! block, and encoded back with the Huffman tables of the file, so that an image whose
+//! coefficients are unchanged is written back byte for byte. All markers and segments
+//! outside the scans, such as the quantization tables and the metadata, are written back
+//! untouched.
+//!
+//! As in JSteg and F5, only the AC coefficients whose magnitude is at least two are part of
+//! the cover, and only the least significant bit of their magnitude is altered. This keeps
+//! every coefficient within its magnitude category, so that the changed coefficients are
+//! encoded with the same Huffman codes and the cover of the package is the same as that of
+//! the image. DC coefficients, which are visible as the average brightness of each block,
+//! as well as zeros and ones, which are the majority of the coefficients and whose changes
+//! are easily detected, stay unchanged.
+//!
+//! Progressive, lossless, arithmetic-coded, and 12-bit images are not supported.
+
+use crate::Error;
+use std::{
+    io::{Read, Write},
+    ops::Range,
+};
+
+const SOI: u8 = 0xd8;
+const EOI: u8 = 0xd9;
+const SOS: u8 = 0xda;
+const DHT: u8 = 0xc4;
+const DRI: u8 = 0xdd;
+const RST0: u8 = 0xd0;
+
+/// The length of a block in coefficients.
+const BLOCK_LEN: usize = 64;
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_be_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+/// A canonical Huffman table as defined by a DHT segment.
+#[derive(Debug, Clone)]
+struct Huffman {
+    /// The number of codes of every length from 1 to 16 bits.
+    counts: [u8; 16],
+
+    /// The symbols in the order of their codes.
+    symbols: Vec<u8>,
+
+    /// The code and its length for every symbol, if the symbol has one.
+    codes: Vec<Option<(u16, u8)>>,
+}
+
+impl Huffman {
+    fn new(counts: [u8; 16], symbols: Vec<u8>) -> Self {
+        let mut codes = vec![None; 256];
+
+        let mut code = 0u32;
+        let mut position = 0;
+        for (len, &count) in (1..=16).zip(&counts) {
+            for &symbol in &symbols[position..position + usize::from(count)] {
+                codes[usize::from(symbol)] = Some((code as u16, len));
+                code += 1;
+            }
+
+            position += usize::from(count);
+            code <<= 1;
+        }
+
+        Self {
+            counts,
+            symbols,
+            codes,
+        }
+    }
+
+    fn decode(&self, reader: &mut BitReader<'_>) -> Result<u8, Error> {
+        let (mut code, mut first, mut position) = (0, 0, 0);
+        for &count in &self.counts {
+            code = code << 1 | reader.bit()?;
+
+            let count = u32::from(count);
+            if code.wrapping_sub(first) < count {
+                return Ok(self.symbols[position + (code - first) as usize]);
+            }
+
+            position += count as usize;
+            first = (first + count) << 1;
+        }
+
+        Err(Error::Format("invalid Huffman code in JPEG scan".into()))
+    }
+
+    fn encode(&self, writer: &mut BitWriter, symbol: u8) -> Result<(), Error> {
+        let Some((code, len)) = self.codes[usize::from(symbol)] else {
+            return Err(Error::Format(
+                format!("JPEG Huffman table has no code for symbol {symbol:#04x}").into(),
+            ));
+        };
+
+        writer.write(code.into(), len);
+
+        Ok(())
+    }
+}
+
+/// A reader of the entropy-coded data of a scan, which removes the stuffed zero bytes.
+struct BitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    byte: u8,
+    remaining: u8,
+}
+
+impl<'a> BitReader<'a> {
+    fn new(bytes: &'a [u8], position: usize) -> Self {
+        Self {
+            bytes,
+            position,
+            byte: 0,
+            remaining: 0,
+        }
+    }
+
+    fn bit(&mut self) -> Result<u32, Error> {
+        if self.remaining == 0 {
+            let Some(&byte) = self.bytes.get(self.position) else {
+                return Err(Error::Format("JPEG scan is truncated".into()));
+            };
+
+            if byte == 0xff {
+                if self.bytes.get(self.position + 1) != Some(&0) {
+                    return Err(Error::Format("unexpected marker in JPEG scan".into()));
+                }
+
+                self.position += 1;
+            }
+
+            self.position += 1;
+            self.byte = byte;
+            self.remaining = 8;
+        }
+
+        self.remaining -= 1;
+
+        Ok(u32::from(self.byte >> self.remaining & 1))
+    }
+
+    fn bits(&mut self, len: u8) -> Result<u32, Error> {
+        (0..len).try_fold(0, |bits, _| Ok(bits << 1 | self.bit()?))
+    }
+
+    /// Discards the remaining bits of the current byte and reads the restart marker.
+    fn restart(&mut self, marker: u8) -> Result<(), Error> {
+        self.remaining = 0;
+
+        if self.bytes.get(self.position..self.position + 2) != Some(&[0xff, marker]) {
+            return Err(Error::Format("missing restart marker in JPEG scan".into()));
+        }
+
+        self.position += 2;
+
+        Ok(())
+    }
+}
+
+/// A writer of entropy-coded data, which stuffs a zero byte after every `0xff` byte.
+struct BitWriter {
+    bytes: Vec<u8>,
+    byte: u8,
+    len: u8,
+}
+
+impl BitWriter {
+    fn new() -> Self {
+        Self {
+            bytes: Vec::new(),
+            byte: 0,
+            len: 0,
+        }
+    }
+
+    fn write(&mut self, bits: u32, len: u8) {
+        for pow in (0..len).rev() {
+            self.byte = self.byte << 1 | (bits >> pow & 1) as u8;
+            self.len += 1;
+
+            if self.len == 8 {
+                self.bytes.push(self.byte);
+                if self.byte == 0xff {
+                    self.bytes.push(0);
+                }
+
+                self.byte = 0;
+                self.len = 0;
+            }
+        }
+    }
+
+    /// Pads the current byte with ones and writes the restart marker, if any.
+    fn align(&mut self, marker: Option<u8>) {
+        if self.len > 0 {
+            self.write(u32::MAX, 8 - self.len);
+        }
+
+        if let Some(marker) = marker {
+            self.bytes.extend([0xff, marker]);
+        }
+    }
+}
+
+/// Returns the number of bits needed to represent the magnitude of the value.
+fn category_of(value: i16) -> u8 {
+    (16 - value.unsigned_abs().leading_zeros()) as u8
+}
+
+/// Returns the value represented by the supplied bits of the supplied category.
+fn extend(bits: u32, category: u8) -> i16 {
+    if category == 0 {
+        return 0;
+    }
+
+    if bits < 1 << (category - 1) {
+        (bits as i32 - (1 << category) + 1) as i16
+    } else {
+        bits as i16
+    }
+}
+
+/// Returns the bits that represent the value within its category.
+fn bits(value: i16, category: u8) -> u32 {
+    if value < 0 {
+        (i32::from(value) + (1 << category) - 1) as u32
+    } else {
+        value as u32
+    }
+}
+
+/// Returns whether the coefficient at the supplied position within a block is in the cover.
+fn is_usable(position: usize, coefficient: i16) -> bool {
+    position > 0 && coefficient.unsigned_abs() > 1
+}
+
+/// An image component, or a channel, along with the coefficients of its blocks.
+#[derive(Debug, Clone)]
+struct Component {
+    id: u8,
+    horizontal: usize,
+    vertical: usize,
+
+    /// The number of blocks in each row of the component, including the padding blocks
+    /// that complete the last MCU.
+    blocks_wide: usize,
+
+    /// The number of blocks in each column, including the padding blocks.
+    blocks_high: usize,
+
+    /// The number of blocks in each row that cover the image.
+    width_in_blocks: usize,
+
+    /// The number of blocks in each column that cover the image.
+    height_in_blocks: usize,
+
+    /// The quantized coefficients of the blocks in row-major order, each of which is stored
+    /// in the zigzag order.
+    blocks: Vec<[i16; BLOCK_LEN]>,
+}
+
+/// A component of a scan along with its Huffman tables.
+#[derive(Debug, Clone)]
+struct ScanComponent {
+    component: usize,
+    dc: Huffman,
+    ac: Huffman,
+}
+
+/// An entropy-coded scan along with its location in the bytes of the file.
+#[derive(Debug, Clone)]
+struct Scan {
+    data: Range<usize>,
+    components: Vec<ScanComponent>,
+    restart_interval: usize,
+}
+
+/// A decoded baseline JPEG image whose quantized DCT coefficients act as the cover.
+///
+/// Every usable coefficient is represented by the low-order byte of its magnitude, and only
+/// the least significant bit of each sample is written back by [`Image::set_samples`].
+/// Patterns should therefore only select the least significant bit. The coefficients are
+/// ordered by component, then by block in row-major order, then in the zigzag order
+/// within each block.
+///
+/// The capacity of JPEG images is much smaller than that of uncompressed images, as most
+/// coefficients are zeros. [`Carrier::matrix_embedding`] makes the best of it by changing
+/// fewer coefficients, which is the approach of F5.
+///
+/// # Examples
+///
+/// Concealing a message in a JPEG image:
+///
+/// ```no_run
+/// use asbs::{binary, formats::jpeg, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut image = jpeg::Image::decode(File::open("cover.jpg")?)?;
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+///     .conceal(payload.as_slice(), image.samples().as_slice())?;
+///
+/// image.set_samples(&package);
+/// image.encode(File::create("package.jpg")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+///
+/// [`Carrier::matrix_embedding`]: crate::binary::Carrier::matrix_embedding
+#[derive(Debug, Clone)]
+pub struct Image {
+    bytes: Vec<u8>,
+    width: u16,
+    height: u16,
+    components: Vec<Component>,
+    scans: Vec<Scan>,
+    skipped: Vec<bool>,
+}
+
+impl Image {
+    /// Decodes a JPEG image from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the image is not a valid baseline or extended sequential JPEG
+    /// image with Huffman coding and 8-bit samples.
+    pub fn decode(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 4 || bytes[..2] != [0xff, SOI] {
+            return Err(Error::Format("missing JPEG start of image".into()));
+        }
+
+        let mut image = Self {
+            bytes: Vec::new(),
+            width: 0,
+            height: 0,
+            components: Vec::new(),
+            scans: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        let mut dc_tables: [Option<Huffman>; 4] = Default::default();
+        let mut ac_tables: [Option<Huffman>; 4] = Default::default();
+        let mut restart_interval = 0;
+
+        let mut offset = 2;
+        loop {
+            // Any number of fill bytes may precede a marker.
+            while bytes.get(offset) == Some(&0xff) && bytes.get(offset + 1) == Some(&0xff) {
+                offset += 1;
+            }
+
+            let Some(&[0xff, marker]) = bytes.get(offset..offset + 2) else {
+                return Err(Error::Format("missing JPEG marker".into()));
+            };
+
+            offset += 2;
+
+            if marker == EOI {
+                break;
+            }
+
+            // Restart markers and TEM stand alone, without a segment.
+            if marker == 0x01 || (RST0..RST0 + 8).contains(&marker) {
+                continue;
+            }
+
+            if offset + 2 > bytes.len() {
+                return Err(Error::Format("JPEG segment is truncated".into()));
+            }
+
+            let end = offset + usize::from(read_u16(&bytes, offset));
+            if end > bytes.len() || end < offset + 2 {
+                return Err(Error::Format("JPEG segment is truncated".into()));
+            }
+
+            let segment = &bytes[offset + 2..end];
+
+            match marker {
+                0xc0 | 0xc1 => image.read_frame(segment, bytes.len())?,
+                0xc2 | 0xc6 | 0xca | 0xce => {
+                    return Err(Error::Format(
+                        "progressive JPEG images are not supported".into(),
+                    ));
+                }
+                0xc3 | 0xc5 | 0xc7 | 0xc9 | 0xcb | 0xcd | 0xcf => {
+                    return Err(Error::Format(
+                        "lossless and arithmetic-coded JPEG images are not supported".into(),
+                    ));
+                }
+                DHT => read_tables(segment, &mut dc_tables, &mut ac_tables)?,
+                DRI if segment.len() >= 2 => restart_interval = usize::from(read_u16(segment, 0)),
+                SOS => {
+                    let components = image.read_scan_header(segment, &dc_tables, &ac_tables)?;
+
+                    let mut scan = Scan {
+                        data: end..end,
+                        components,
+                        restart_interval,
+                    };
+
+                    scan.data.end = image.decode_scan(&bytes, &scan)?;
+                    offset = scan.data.end;
+
+                    image.scans.push(scan);
+                    continue;
+                }
+                _ => {}
+            }
+
+            offset = end;
+        }
+
+        if image.scans.is_empty() {
+            return Err(Error::Format("JPEG image has no scans".into()));
+        }
+
+        image.bytes = bytes;
+
+        Ok(image)
+    }
+
+    /// Reads the frame header of an image of the supplied length in bytes.
+    fn read_frame(&mut self, segment: &[u8], len: usize) -> Result<(), Error> {
+        if !self.components.is_empty() {
+            return Err(Error::Format("JPEG image has multiple frames".into()));
+        }
+
+        if segment.len() < 6 || segment.len() < 6 + 3 * usize::from(segment[5]) {
+            return Err(Error::Format("JPEG frame header is truncated".into()));
+        }
+
+        if segment[0] != 8 {
+            return Err(Error::Format(
+                format!("unsupported JPEG precision of {} bits", segment[0]).into(),
+            ));
+        }
+
+        self.height = read_u16(segment, 1);
+        self.width = read_u16(segment, 3);
+
+        if self.width == 0 || self.height == 0 || segment[5] == 0 {
+            return Err(Error::Format("invalid JPEG dimensions".into()));
+        }
+
+        for component in segment[6..].chunks_exact(3).take(segment[5].into()) {
+            let (horizontal, vertical) = (component[1] >> 4, component[1] & 0xf);
+
+            if !(1..=4).contains(&horizontal) || !(1..=4).contains(&vertical) {
+                return Err(Error::Format("invalid JPEG sampling factors".into()));
+            }
+
+            self.components.push(Component {
+                id: component[0],
+                horizontal: horizontal.into(),
+                vertical: vertical.into(),
+                blocks_wide: 0,
+                blocks_high: 0,
+                width_in_blocks: 0,
+                height_in_blocks: 0,
+                blocks: Vec::new(),
+            });
+        }
+
+        let (max_horizontal, max_vertical) = self.max_sampling();
+
+        let (width, height) = (usize::from(self.width), usize::from(self.height));
+        let mcus_wide = width.div_ceil(8 * max_horizontal);
+        let mcus_high = height.div_ceil(8 * max_vertical);
+
+        for component in &mut self.components {
+            component.blocks_wide = mcus_wide * component.horizontal;
+            component.blocks_high = mcus_high * component.vertical;
+            component.width_in_blocks = (width * component.horizontal)
+                .div_ceil(max_horizontal)
+                .div_ceil(8);
+            component.height_in_blocks = (height * component.vertical)
+                .div_ceil(max_vertical)
+                .div_ceil(8);
+        }
+
+        // Every block takes at least two bits of entropy-coded data, so that the blocks are
+        // only allocated if the image is large enough to contain them.
+        let coded_blocks: usize = self
+            .components
+            .iter()
+            .map(|component| component.width_in_blocks * component.height_in_blocks)
+            .sum();
+
+        if coded_blocks > len.saturating_mul(4) {
+            return Err(Error::Format(
+                "JPEG dimensions exceed the image data".into(),
+            ));
+        }
+
+        for component in &mut self.components {
+            component.blocks = vec![[0; BLOCK_LEN]; component.blocks_wide * component.blocks_high];
+        }
+
+        self.skipped = vec![false; self.components.len()];
+
+        Ok(())
+    }
+
+    fn read_scan_header(
+        &self,
+        segment: &[u8],
+        dc_tables: &[Option<Huffman>; 4],
+        ac_tables: &[Option<Huffman>; 4],
+    ) -> Result<Vec<ScanComponent>, Error> {
+        if self.components.is_empty() {
+            return Err(Error::Format("JPEG scan precedes the frame header".into()));
+        }
+
+        let count = usize::from(*segment.first().unwrap_or(&0));
+        if count == 0 || segment.len() < 1 + 2 * count + 3 {
+            return Err(Error::Format("JPEG scan header is truncated".into()));
+        }
+
+        let mut components = Vec::with_capacity(count);
+        for selector in segment[1..].chunks_exact(2).take(count) {
+            let Some(component) = self.components.iter().position(|c| c.id == selector[0]) else {
+                return Err(Error::Format(
+                    "JPEG scan refers to an unknown component".into(),
+                ));
+            };
+
+            let (dc, ac) = (
+                usize::from(selector[1] >> 4),
+                usize::from(selector[1] & 0xf),
+            );
+            let (Some(Some(dc)), Some(Some(ac))) = (dc_tables.get(dc), ac_tables.get(ac)) else {
+                return Err(Error::Format(
+                    "JPEG scan refers to a missing Huffman table".into(),
+                ));
+            };
+
+            components.push(ScanComponent {
+                component,
+                dc: dc.clone(),
+                ac: ac.clone(),
+            });
+        }
+
+        Ok(components)
+    }
+
+    /// Returns the largest horizontal and vertical sampling factors of the components.
+    fn max_sampling(&self) -> (usize, usize) {
+        self.components
+            .iter()
+            .fold((1, 1), |(horizontal, vertical), component| {
+                (
+                    horizontal.max(component.horizontal),
+                    vertical.max(component.vertical),
+                )
+            })
+    }
+
+    /// Returns the indices of the blocks of every MCU of the scan, in the coding order.
+    fn mcus(&self, scan: &Scan) -> Vec<Vec<(usize, usize)>> {
+        if let [scan_component] = scan.components.as_slice() {
+            let component = &self.components[scan_component.component];
+
+            return (0..component.height_in_blocks)
+                .flat_map(|row| {
+                    (0..component.width_in_blocks)
+                        .map(move |column| vec![(0, row * component.blocks_wide + column)])
+                })
+                .collect();
+        }
+
+        let (max_horizontal, max_vertical) = self.max_sampling();
+
+        let mcus_wide = usize::from(self.width).div_ceil(8 * max_horizontal);
+        let mcus_high = usize::from(self.height).div_ceil(8 * max_vertical);
+
+        (0..mcus_high)
+            .flat_map(|mcu_row| (0..mcus_wide).map(move |mcu_column| (mcu_row, mcu_column)))
+            .map(|(mcu_row, mcu_column)| {
+                let mut blocks = Vec::new();
+                for (position, scan_component) in scan.components.iter().enumerate() {
+                    let component = &self.components[scan_component.component];
+                    for row in 0..component.vertical {
+                        for column in 0..component.horizontal {
+                            let row = mcu_row * component.vertical + row;
+                            let column = mcu_column * component.horizontal + column;
+                            blocks.push((position, row * component.blocks_wide + column));
+                        }
+                    }
+                }
+
+                blocks
+            })
+            .collect()
+    }
+
+    /// Decodes the coefficients of the scan, returning the offset of its end.
+    fn decode_scan(&mut self, bytes: &[u8], scan: &Scan) -> Result<usize, Error> {
+        let mut reader = BitReader::new(bytes, scan.data.start);
+        let mut predictions = vec![0i16; scan.components.len()];
+
+        for (index, mcu) in self.mcus(scan).into_iter().enumerate() {
+            if index > 0 && scan.restart_interval > 0 && index % scan.restart_interval == 0 {
+                let marker = RST0 + ((index / scan.restart_interval - 1) % 8) as u8;
+                reader.restart(marker)?;
+                predictions.fill(0);
+            }
+
+            for (position, block) in mcu {
+                let scan_component = &scan.components[position];
+
+                let mut coefficients = [0; BLOCK_LEN];
+
+                let category = scan_component.dc.decode(&mut reader)?;
+                if category > 11 {
+                    return Err(Error::Format("invalid DC coefficient in JPEG scan".into()));
+                }
+
+                let difference = extend(reader.bits(category)?, category);
+                predictions[position] = predictions[position].wrapping_add(difference);
+                coefficients[0] = predictions[position];
+
+                let mut k = 1;
+                while k < BLOCK_LEN {
+                    let symbol = scan_component.ac.decode(&mut reader)?;
+                    let (run, category) = (usize::from(symbol >> 4), symbol & 0xf);
+
+                    if category == 0 {
+                        if run != 15 {
+                            break;
+                        }
+
+                        k += 16;
+                        continue;
+                    }
+
+                    k += run;
+                    if k >= BLOCK_LEN || category > 10 {
+                        return Err(Error::Format("invalid AC coefficient in JPEG scan".into()));
+                    }
+
+                    coefficients[k] = extend(reader.bits(category)?, category);
+                    k += 1;
+                }
+
+                self.components[scan_component.component].blocks[block] = coefficients;
+            }
+        }
+
+        Ok(reader.position)
+    }
+
+    /// Encodes the coefficients of the scan into entropy-coded data.
+    fn encode_scan(&self, scan: &Scan) -> Result<Vec<u8>, Error> {
+        let mut writer = BitWriter::new();
+        let mut predictions = vec![0i16; scan.components.len()];
+
+        for (index, mcu) in self.mcus(scan).into_iter().enumerate() {
+            if index > 0 && scan.restart_interval > 0 && index % scan.restart_interval == 0 {
+                let marker = RST0 + ((index / scan.restart_interval - 1) % 8) as u8;
+                writer.align(Some(marker));
+                predictions.fill(0);
+            }
+
+            for (position, block) in mcu {
+                let scan_component = &scan.components[position];
+                let coefficients = &self.components[scan_component.component].blocks[block];
+
+                let difference = coefficients[0].wrapping_sub(predictions[position]);
+                predictions[position] = coefficients[0];
+
+                let category = category_of(difference);
+                scan_component.dc.encode(&mut writer, category)?;
+                writer.write(bits(difference, category), category);
+
+                let mut run = 0;
+                for &coefficient in &coefficients[1..] {
+                    if coefficient == 0 {
+                        run += 1;
+                        continue;
+                    }
+
+                    while run > 15 {
+                        scan_component.ac.encode(&mut writer, 0xf0)?;
+                        run -= 16;
+                    }
+
+                    let category = category_of(coefficient);
+                    scan_component.ac.encode(&mut writer, run << 4 | category)?;
+                    writer.write(bits(coefficient, category), category);
+                    run = 0;
+                }
+
+                if run > 0 {
+                    scan_component.ac.encode(&mut writer, 0x00)?;
+                }
+            }
+        }
+
+        writer.align(None);
+
+        Ok(writer.bytes)
+    }
+
+    /// Excludes the component with the supplied index, such as a chroma component, from
+    /// the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn skip_channel(mut self, channel: usize) -> Self {
+        assert!(channel < self.channels(), "channel index is out of range");
+        self.skipped[channel] = true;
+        self
+    }
+
+    /// Returns the width of the image in pixels.
+    #[must_use]
+    pub fn width(&self) -> u16 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    #[must_use]
+    pub fn height(&self) -> u16 {
+        self.height
+    }
+
+    /// Returns the number of components of the image, which is 1 for grayscale images and
+    /// usually 3 for color images.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.components.len()
+    }
+
+    /// Returns the usable coefficients of the component in the cover order.
+    fn coefficients(&self, channel: usize) -> impl Iterator<Item = &i16> + '_ {
+        self.components[channel].blocks.iter().flat_map(|block| {
+            block
+                .iter()
+                .enumerate()
+                .filter(|&(position, &coefficient)| is_usable(position, coefficient))
+                .map(|(_, coefficient)| coefficient)
+        })
+    }
+
+    /// Returns the samples of the components in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        (0..self.channels())
+            .filter(|&channel| !self.skipped[channel])
+            .flat_map(|channel| self.channel_samples(channel))
+            .collect()
+    }
+
+    /// Returns the samples of the component with the supplied index, including the
+    /// components excluded from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: usize) -> Vec<u8> {
+        assert!(channel < self.channels(), "channel index is out of range");
+
+        self.coefficients(channel)
+            .map(|coefficient| coefficient.unsigned_abs() as u8)
+            .collect()
+    }
+
+    /// Replaces the least significant bits of the magnitudes of the coefficients returned
+    /// by [`Image::samples`] with those of the supplied samples.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        let mut samples = samples.iter();
+
+        for (channel, component) in self.components.iter_mut().enumerate() {
+            if self.skipped[channel] {
+                continue;
+            }
+
+            for block in &mut component.blocks {
+                for (position, coefficient) in block.iter_mut().enumerate() {
+                    if !is_usable(position, *coefficient) {
+                        continue;
+                    }
+
+                    let Some(&sample) = samples.next() else {
+                        return;
+                    };
+
+                    let magnitude = coefficient.unsigned_abs() & !1 | u16::from(sample & 1);
+                    *coefficient = coefficient.signum() * magnitude as i16;
+                }
+            }
+        }
+    }
+
+    /// Encodes the image into the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing, as well as
+    /// [`Error::Format`] if a coefficient cannot be encoded with the Huffman tables of
+    /// the image.
+    pub fn encode(&self, mut writer: impl Write) -> Result<(), Error> {
+        let mut offset = 0;
+        for scan in &self.scans {
+            writer.write_all(&self.bytes[offset..scan.data.start])?;
+            writer.write_all(&self.encode_scan(scan)?)?;
+            offset = scan.data.end;
+        }
+
+        writer.write_all(&self.bytes[offset..])?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
+
+/// Reads the Huffman tables defined by a DHT segment.
+fn read_tables(
+    mut segment: &[u8],
+    dc_tables: &mut [Option<Huffman>; 4],
+    ac_tables: &mut [Option<Huffman>; 4],
+) -> Result<(), Error> {
+    while !segment.is_empty() {
+        if segment.len() < 17 {
+            return Err(Error::Format("JPEG Huffman table is truncated".into()));
+        }
+
+        let (class, id) = (segment[0] >> 4, usize::from(segment[0] & 0xf));
+        let counts: [u8; 16] = segment[1..17].try_into().unwrap();
+        let len = counts
+            .iter()
+            .map(|&count| usize::from(count))
+            .sum::<usize>();
+
+        if class > 1 || id > 3 || segment.len() < 17 + len || len > 256 {
+            return Err(Error::Format("invalid JPEG Huffman table".into()));
+        }
+
+        let table = Huffman::new(counts, segment[17..17 + len].to_vec());
+        match class {
+            0 => dc_tables[id] = Some(table),
+            _ => ac_tables[id] = Some(table),
+        }
+
+        segment = &segment[17 + len..];
+    }
+
+    Ok(())
+}
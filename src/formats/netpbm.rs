@@ -0,0 +1,293 @@
+//! Binary Netpbm images, i.e. PGM (`P5`), PPM (`P6`), and PAM (`P7`) files.
+//!
+//! The header is written back untouched, and only the raster is exposed as the cover.
+//! Samples with a maximum value above 255 take two bytes, of which only the less
+//! significant one is exposed.
+//!
+//! Note that concealing a message may produce samples above the maximum value of the image
+//! unless the maximum value is one less than a power of two and the pattern only uses the
+//! bits below it.
+
+use crate::{formats::Layout, Error};
+use std::io::{Read, Write};
+
+fn format_error(message: &str) -> Error {
+    Error::Format(message.into())
+}
+
+/// A reader of the whitespace-separated tokens of a header.
+struct Tokens<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl<'a> Tokens<'a> {
+    fn skip_whitespace(&mut self) {
+        while let Some(&byte) = self.bytes.get(self.position) {
+            match byte {
+                b'#' => {
+                    while self
+                        .bytes
+                        .get(self.position)
+                        .is_some_and(|&byte| byte != b'\n')
+                    {
+                        self.position += 1;
+                    }
+                }
+                byte if byte.is_ascii_whitespace() => self.position += 1,
+                _ => break,
+            }
+        }
+    }
+
+    fn next(&mut self) -> Option<&'a [u8]> {
+        self.skip_whitespace();
+
+        let start = self.position;
+        while self
+            .bytes
+            .get(self.position)
+            .is_some_and(|byte| !byte.is_ascii_whitespace())
+        {
+            self.position += 1;
+        }
+
+        (self.position > start).then(|| &self.bytes[start..self.position])
+    }
+
+    fn number(&mut self) -> Result<usize, Error> {
+        self.next()
+            .and_then(|token| std::str::from_utf8(token).ok()?.parse().ok())
+            .ok_or_else(|| format_error("invalid number in Netpbm header"))
+    }
+
+    /// Skips the single whitespace character that separates the header from the raster.
+    fn end(&mut self) -> Result<usize, Error> {
+        match self.bytes.get(self.position) {
+            Some(byte) if byte.is_ascii_whitespace() => Ok(self.position + 1),
+            _ => Err(format_error("Netpbm header is not terminated")),
+        }
+    }
+}
+
+/// A decoded Netpbm image whose samples act as the cover.
+///
+/// # Examples
+///
+/// Concealing a message in a PPM image:
+///
+/// ```no_run
+/// use asbs::{binary, formats::netpbm, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut image = netpbm::Image::decode(File::open("cover.ppm")?)?;
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+///     .conceal(payload.as_slice(), image.samples().as_slice())?;
+///
+/// image.set_samples(&package);
+/// image.encode(File::create("package.ppm")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Image {
+    bytes: Vec<u8>,
+    width: u32,
+    height: u32,
+    max_value: u16,
+    has_alpha: bool,
+    layout: Layout,
+}
+
+impl Image {
+    /// Decodes the first Netpbm image from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the image is not a valid binary PGM, PPM, or PAM image.
+    pub fn decode(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        let mut tokens = Tokens {
+            bytes: &bytes,
+            position: 0,
+        };
+
+        let (width, height, depth, max_value, has_alpha) = match tokens.next() {
+            Some(magic @ (b"P5" | b"P6")) => {
+                let width = tokens.number()?;
+                let height = tokens.number()?;
+                let max_value = tokens.number()?;
+                let depth = if magic == b"P5" { 1 } else { 3 };
+
+                (width, height, depth, max_value, false)
+            }
+            Some(b"P7") => {
+                let (mut width, mut height, mut depth, mut max_value) = (None, None, None, None);
+                let mut has_alpha = false;
+
+                loop {
+                    match tokens.next() {
+                        Some(b"WIDTH") => width = Some(tokens.number()?),
+                        Some(b"HEIGHT") => height = Some(tokens.number()?),
+                        Some(b"DEPTH") => depth = Some(tokens.number()?),
+                        Some(b"MAXVAL") => max_value = Some(tokens.number()?),
+                        Some(b"TUPLTYPE") => {
+                            has_alpha = tokens
+                                .next()
+                                .is_some_and(|tuple_type| tuple_type.ends_with(b"_ALPHA"));
+                        }
+                        Some(b"ENDHDR") => break,
+                        _ => return Err(format_error("invalid PAM header")),
+                    }
+                }
+
+                let (Some(width), Some(height), Some(depth), Some(max_value)) =
+                    (width, height, depth, max_value)
+                else {
+                    return Err(format_error("incomplete PAM header"));
+                };
+
+                (width, height, depth, max_value, has_alpha)
+            }
+            _ => return Err(format_error("unsupported Netpbm format")),
+        };
+
+        let offset = tokens.end()?;
+
+        let Ok(max_value @ 1..) = u16::try_from(max_value) else {
+            return Err(format_error("invalid maximum value"));
+        };
+
+        if depth == 0 {
+            return Err(format_error("invalid depth"));
+        }
+
+        let sample_len = if max_value > 255 { 2 } else { 1 };
+
+        let (Ok(width), Ok(height)) = (u32::try_from(width), u32::try_from(height)) else {
+            return Err(format_error("invalid Netpbm dimensions"));
+        };
+
+        let Some(stride) = (width as usize)
+            .checked_mul(depth)
+            .and_then(|len| len.checked_mul(sample_len))
+        else {
+            return Err(format_error("invalid Netpbm dimensions"));
+        };
+
+        if stride
+            .checked_mul(height as usize)
+            .and_then(|len| len.checked_add(offset))
+            .is_none_or(|end| end > bytes.len())
+        {
+            return Err(format_error("Netpbm raster is truncated"));
+        }
+
+        let layout = Layout {
+            offset,
+            rows: height as usize,
+            row_len: stride / sample_len,
+            stride,
+            sample_len,
+            low_byte: sample_len - 1,
+            skipped: vec![false; depth],
+        };
+
+        Ok(Self {
+            bytes,
+            width,
+            height,
+            max_value,
+            has_alpha: has_alpha && depth > 1,
+            layout,
+        })
+    }
+
+    /// Excludes the alpha channel of PAM images with a `_ALPHA` tuple type from the cover.
+    #[must_use]
+    pub fn skip_alpha(self) -> Self {
+        if self.has_alpha {
+            let alpha = self.channels() - 1;
+            self.skip_channel(alpha)
+        } else {
+            self
+        }
+    }
+
+    /// Excludes the channel with the supplied index from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn skip_channel(mut self, channel: usize) -> Self {
+        self.layout.skip_channel(channel);
+        self
+    }
+
+    /// Returns the width of the image in pixels.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    /// Returns the height of the image in pixels.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Returns the number of channels of each pixel.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.layout.channels()
+    }
+
+    /// Returns the maximum value of a sample.
+    #[must_use]
+    pub fn max_value(&self) -> u16 {
+        self.max_value
+    }
+
+    /// Returns the least significant byte of each sample of the channels in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        self.layout.samples(&self.bytes)
+    }
+
+    /// Returns the least significant byte of each sample of the channel with the supplied
+    /// index, including the channels excluded from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: usize) -> Vec<u8> {
+        self.layout.channel_samples(&self.bytes, channel)
+    }
+
+    /// Replaces the samples returned by [`Image::samples`] with the supplied ones.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        self.layout.set_samples(&mut self.bytes, samples);
+    }
+
+    /// Encodes the image into the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing.
+    pub fn encode(&self, mut writer: impl Write) -> Result<(), Error> {
+        writer.write_all(&self.bytes)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
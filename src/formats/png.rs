@@ -0,0 +1,245 @@
+//! PNG images, which are available with the `png` feature.
+//!
+//! Images are encoded back with their color type and bit depth, and with the ancillary
+//! chunks that affect how they are rendered or describe them: `gAMA`, `cHRM`, `sRGB`,
+//! `iCCP`, `pHYs`, `eXIf`, `bKGD`, `tRNS`, and the text chunks. The samples of palette
+//! images are their palette indices, and the samples of images with fewer than 8 bits per
+//! sample are unpacked into a byte each.
+//!
+//! The following are not preserved:
+//!
+//! - the `sBIT`, `cICP`, `mDCV`, and `cLLI` chunks, as well as unknown and private chunks,
+//! - interlacing, as images are always encoded without it,
+//! - the frames of animated images after the first one, whose animation chunks are dropped,
+//! - the compression level and the filters, so that the encoded image differs in size.
+
+use crate::{formats::Layout, Error};
+use png::{
+    chunk, BitDepth, ColorType, Decoder, DecodingError, Encoder, EncodingError, Info,
+    Transformations,
+};
+use std::io::{Read, Write};
+
+fn decoding_error(error: DecodingError) -> Error {
+    match error {
+        DecodingError::IoError(error) => Error::Io(error),
+        error => Error::Format(error.into()),
+    }
+}
+
+fn encoding_error(error: EncodingError) -> Error {
+    match error {
+        EncodingError::IoError(error) => Error::Io(error),
+        error => Error::Format(error.into()),
+    }
+}
+
+/// A decoded PNG image whose channel samples act as the cover.
+///
+/// Only the least significant byte of each sample is exposed by [`Image::samples`], so
+/// that patterns never touch the high byte of 16-bit samples. Samples of fewer than 8 bits
+/// only keep as many low-order bits when encoded, so patterns should not select more.
+/// Channels may be excluded from the cover with [`Image::skip_alpha`] and
+/// [`Image::skip_channel`].
+///
+/// Changing the least significant bit of a palette index selects a neighboring palette
+/// entry, which may have a very different color unless similar colors are adjacent.
+///
+/// # Examples
+///
+/// Revealing a message concealed in a PNG image:
+///
+/// ```no_run
+/// use asbs::{binary, formats::png, Reveal};
+/// use std::fs::File;
+///
+/// let image = png::Image::decode(File::open("package.png")?)?.skip_alpha();
+///
+/// binary::Package::with_header(|_| Some(0b1), image.samples().as_slice())
+///     .reveal(File::create("message")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Image {
+    info: Info<'static>,
+    data: Vec<u8>,
+    layout: Layout,
+}
+
+impl Image {
+    /// Decodes the first frame of a PNG image from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the image is not a valid PNG.
+    pub fn decode(reader: impl Read) -> Result<Self, Error> {
+        let mut decoder = Decoder::new(reader);
+        decoder.set_transformations(Transformations::IDENTITY);
+
+        let mut reader = decoder.read_info().map_err(decoding_error)?;
+
+        let mut data = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut data).map_err(decoding_error)?;
+        data.truncate(frame.buffer_size());
+
+        let info = reader.info().clone();
+        let row_len = frame.width as usize * frame.color_type.samples();
+
+        let sample_len = match frame.bit_depth {
+            BitDepth::Sixteen => 2,
+            BitDepth::One | BitDepth::Two | BitDepth::Four => {
+                data = unpack(&data, frame.line_size, row_len, frame.bit_depth as usize);
+                1
+            }
+            BitDepth::Eight => 1,
+        };
+
+        let layout = Layout {
+            offset: 0,
+            rows: frame.height as usize,
+            row_len,
+            stride: row_len * sample_len,
+            sample_len,
+            low_byte: sample_len - 1,
+            skipped: vec![false; frame.color_type.samples()],
+        };
+
+        Ok(Self { info, data, layout })
+    }
+
+    /// Excludes the alpha channel, if any, from the cover.
+    ///
+    /// Fully transparent pixels tend to have uniform color values, which makes any change
+    /// to them conspicuous.
+    #[must_use]
+    pub fn skip_alpha(self) -> Self {
+        match self.info.color_type {
+            ColorType::GrayscaleAlpha | ColorType::Rgba => {
+                let alpha = self.channels() - 1;
+                self.skip_channel(alpha)
+            }
+            _ => self,
+        }
+    }
+
+    /// Excludes the channel with the supplied index from the cover.
+    ///
+    /// Channels are ordered as in the PNG format, e.g. red, green, blue, and alpha.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn skip_channel(mut self, channel: usize) -> Self {
+        self.layout.skip_channel(channel);
+        self
+    }
+
+    /// Returns the width of the image in pixels.
+    #[must_use]
+    pub fn width(&self) -> u32 {
+        self.info.width
+    }
+
+    /// Returns the height of the image in pixels.
+    #[must_use]
+    pub fn height(&self) -> u32 {
+        self.info.height
+    }
+
+    /// Returns the number of channels of each pixel.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.layout.channels()
+    }
+
+    /// Returns the least significant byte of each sample of the channels in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        self.layout.samples(&self.data)
+    }
+
+    /// Returns the least significant byte of each sample of the channel with the supplied
+    /// index, including the channels excluded from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Image::channels`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: usize) -> Vec<u8> {
+        self.layout.channel_samples(&self.data, channel)
+    }
+
+    /// Replaces the samples returned by [`Image::samples`] with the supplied ones.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        self.layout.set_samples(&mut self.data, samples);
+    }
+
+    /// Encodes the image losslessly into the writer, along with the chunks listed in the
+    /// [module documentation](self).
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing.
+    pub fn encode(&self, writer: impl Write) -> Result<(), Error> {
+        let mut info = self.info.clone();
+        info.interlaced = false;
+        info.animation_control = None;
+        info.frame_control = None;
+
+        let encoder = Encoder::with_info(writer, info).map_err(encoding_error)?;
+        let mut writer = encoder.write_header().map_err(encoding_error)?;
+
+        // The encoder does not write the background color with the other chunks.
+        if let Some(bkgd) = &self.info.bkgd {
+            writer
+                .write_chunk(chunk::bKGD, bkgd)
+                .map_err(encoding_error)?;
+        }
+
+        let depth = self.info.bit_depth as usize;
+        let data = match depth {
+            1 | 2 | 4 => pack(&self.data, self.layout.row_len, depth),
+            _ => self.data.clone(),
+        };
+
+        writer.write_image_data(&data).map_err(encoding_error)?;
+
+        writer.finish().map_err(encoding_error)
+    }
+}
+
+/// Unpacks the samples of fewer than 8 bits of every row into a byte each.
+fn unpack(data: &[u8], line_size: usize, row_len: usize, depth: usize) -> Vec<u8> {
+    let mask = (1 << depth) - 1;
+
+    data.chunks(line_size)
+        .flat_map(|row| {
+            (0..row_len).map(move |sample| {
+                let bit = sample * depth;
+                row[bit / 8] >> (8 - depth - bit % 8) & mask
+            })
+        })
+        .collect()
+}
+
+/// Packs the low-order bits of the samples of every row, starting each row on a new byte.
+fn pack(samples: &[u8], row_len: usize, depth: usize) -> Vec<u8> {
+    let mask = (1 << depth) - 1;
+
+    samples
+        .chunks(row_len)
+        .flat_map(|row| {
+            let mut bytes = vec![0; (row_len * depth).div_ceil(8)];
+            for (sample, &value) in row.iter().enumerate() {
+                let bit = sample * depth;
+                bytes[bit / 8] |= (value & mask) << (8 - depth - bit % 8);
+            }
+
+            bytes
+        })
+        .collect()
+}
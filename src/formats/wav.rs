@@ -0,0 +1,209 @@
+//! WAV audio files with PCM samples.
+//!
+//! Samples of 8, 16, 24, and 32 bits with any number of channels are supported, both in the
+//! plain and in the extensible format. All chunks, including the RIFF and format headers,
+//! are written back untouched.
+
+use crate::{formats::Layout, Error};
+use std::io::{Read, Write};
+
+const PCM: u16 = 1;
+const EXTENSIBLE: u16 = 0xfffe;
+
+fn read_u16(bytes: &[u8], offset: usize) -> u16 {
+    u16::from_le_bytes([bytes[offset], bytes[offset + 1]])
+}
+
+fn read_u32(bytes: &[u8], offset: usize) -> u32 {
+    u32::from_le_bytes(bytes[offset..offset + 4].try_into().unwrap())
+}
+
+/// A decoded WAV file whose PCM samples act as the cover.
+///
+/// Only the least significant byte of each sample is exposed by [`Audio::samples`], so
+/// that patterns only ever alter the low-order bits of a sample. Channels may be excluded
+/// from the cover with [`Audio::skip_channel`].
+///
+/// # Examples
+///
+/// Concealing a message in a WAV file:
+///
+/// ```no_run
+/// use asbs::{binary, formats::wav, Conceal};
+/// use std::fs::File;
+///
+/// let payload = b"a very secret message";
+///
+/// let mut audio = wav::Audio::decode(File::open("cover.wav")?)?;
+///
+/// let mut package = Vec::new();
+/// binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+///     .conceal(payload.as_slice(), audio.samples().as_slice())?;
+///
+/// audio.set_samples(&package);
+/// audio.encode(File::create("package.wav")?)?;
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug, Clone)]
+pub struct Audio {
+    bytes: Vec<u8>,
+    sample_rate: u32,
+    bits_per_sample: u16,
+    layout: Layout,
+}
+
+impl Audio {
+    /// Decodes a WAV file from the reader.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while reading, as well as
+    /// [`Error::Format`] if the file is not a valid WAV file with PCM samples.
+    pub fn decode(mut reader: impl Read) -> Result<Self, Error> {
+        let mut bytes = Vec::new();
+        reader.read_to_end(&mut bytes)?;
+
+        if bytes.len() < 12 || &bytes[..4] != b"RIFF" || &bytes[8..12] != b"WAVE" {
+            return Err(Error::Format("missing RIFF/WAVE header".into()));
+        }
+
+        let mut format = None;
+        let mut data = None;
+
+        let mut offset = 12;
+        while offset + 8 <= bytes.len() {
+            let id = &bytes[offset..offset + 4];
+            let len = read_u32(&bytes, offset + 4) as usize;
+            let start = offset + 8;
+            let end = start.saturating_add(len).min(bytes.len());
+
+            match id {
+                b"fmt " if end - start >= 16 => format = Some((start, end)),
+                b"fmt " => return Err(Error::Format("format chunk is too short".into())),
+                b"data" => data = Some((start, end)),
+                _ => {}
+            }
+
+            offset = end.saturating_add(len % 2);
+        }
+
+        let Some((format, format_end)) = format else {
+            return Err(Error::Format("missing format chunk".into()));
+        };
+
+        let Some((start, end)) = data else {
+            return Err(Error::Format("missing data chunk".into()));
+        };
+
+        let mut format_tag = read_u16(&bytes, format);
+        if format_tag == EXTENSIBLE && format_end - format >= 40 {
+            format_tag = read_u16(&bytes, format + 24);
+        }
+
+        let channels = read_u16(&bytes, format + 2) as usize;
+        let sample_rate = read_u32(&bytes, format + 4);
+        let block_align = read_u16(&bytes, format + 12) as usize;
+        let bits_per_sample = read_u16(&bytes, format + 14);
+
+        if format_tag != PCM {
+            return Err(Error::Format(
+                format!("unsupported format tag {format_tag:#06x}").into(),
+            ));
+        }
+
+        if ![8, 16, 24, 32].contains(&bits_per_sample) {
+            return Err(Error::Format(
+                format!("unsupported sample size of {bits_per_sample} bits").into(),
+            ));
+        }
+
+        if channels == 0 || block_align != channels * bits_per_sample as usize / 8 {
+            return Err(Error::Format("invalid block alignment".into()));
+        }
+
+        let sample_len = block_align / channels;
+        let frames = (end - start) / block_align;
+
+        let layout = Layout {
+            offset: start,
+            rows: 1,
+            row_len: frames * channels,
+            stride: frames * block_align,
+            sample_len,
+            low_byte: 0,
+            skipped: vec![false; channels],
+        };
+
+        Ok(Self {
+            bytes,
+            sample_rate,
+            bits_per_sample,
+            layout,
+        })
+    }
+
+    /// Excludes the channel with the supplied index from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Audio::channels`].
+    #[must_use]
+    pub fn skip_channel(mut self, channel: usize) -> Self {
+        self.layout.skip_channel(channel);
+        self
+    }
+
+    /// Returns the number of channels.
+    #[must_use]
+    pub fn channels(&self) -> usize {
+        self.layout.channels()
+    }
+
+    /// Returns the number of samples per second of each channel.
+    #[must_use]
+    pub fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    /// Returns the size of each sample in bits.
+    #[must_use]
+    pub fn bits_per_sample(&self) -> u16 {
+        self.bits_per_sample
+    }
+
+    /// Returns the least significant byte of each sample of the channels in the cover.
+    #[must_use]
+    pub fn samples(&self) -> Vec<u8> {
+        self.layout.samples(&self.bytes)
+    }
+
+    /// Returns the least significant byte of each sample of the channel with the supplied
+    /// index, including the channels excluded from the cover.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the index is not less than [`Audio::channels`].
+    #[must_use]
+    pub fn channel_samples(&self, channel: usize) -> Vec<u8> {
+        self.layout.channel_samples(&self.bytes, channel)
+    }
+
+    /// Replaces the samples returned by [`Audio::samples`] with the supplied ones.
+    ///
+    /// Excess samples are ignored, and samples that are not supplied are left unchanged.
+    pub fn set_samples(&mut self, samples: &[u8]) {
+        self.layout.set_samples(&mut self.bytes, samples);
+    }
+
+    /// Encodes the file into the writer.
+    ///
+    /// # Errors
+    ///
+    /// This function returns any I/O error encountered while writing.
+    pub fn encode(&self, mut writer: impl Write) -> Result<(), Error> {
+        writer.write_all(&self.bytes)?;
+        writer.flush()?;
+
+        Ok(())
+    }
+}
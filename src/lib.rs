@@ -8,10 +8,37 @@
 //! The [`binary`] module can be used to encode messages in binary data with *bit patterns*,
 //! which act as keys and should be shared with the receiver of the message. See its
 //! [documentation][`binary`] for details.
+//!
+//! ## File formats
+//!
+//! The [`formats`] module provides adapters that expose the samples of images and other
+//! files as covers, so that concealing a message does not destroy the file.
+//!
+//! ## Text
+//!
+//! The [`text`] module conceals messages in plain text, which has no low-order bits to
+//! spare, by inserting or choosing characters that do not change how the text reads.
+//!
+//! ## Steganalysis
+//!
+//! The [`analysis`] module implements statistical attacks on packages, which estimate how
+//! detectable the concealed messages are.
+//!
+//! ## Errors
+//!
+//! The implementations in this library report failures with [`Error`], which tells apart
+//! payloads that do not fit, corrupted packages, and I/O errors.
 
 use std::io;
 
+pub mod analysis;
 pub mod binary;
+pub mod formats;
+pub mod text;
+
+mod error;
+
+pub use error::Error;
 
 /// A trait for objects able to conceal steganographic messages, or carriers.
 ///
@@ -0,0 +1,499 @@
+//! The `asbs` command-line interface, which conceals and reveals files with the
+//! [`binary`] implementation.
+//!
+//! Messages are always concealed with a self-describing header, so that the receiver only
+//! needs to know the pattern, the seed, and the password, if any, that were used.
+
+use asbs::{
+    analysis,
+    binary::{self, pattern::KeyedPattern, Checksum, Fec, Header, Regions},
+    Conceal, Error, Reveal,
+};
+use clap::{builder::Resettable, error::ErrorKind, Args, CommandFactory, Parser, Subcommand};
+use std::{
+    fs::{self, File},
+    io::{self, Read, Write},
+    ops::Range,
+    path::{Path, PathBuf},
+    process::ExitCode,
+};
+
+const EXIT_CODES: &str = "\
+Exit codes:
+  0  Success
+  1  An I/O or other error occurred
+  2  The arguments are invalid
+  3  The payload does not fit into the cover
+  4  The message failed to verify, decrypt, or be corrected, or the skipped regions differ
+  5  The package contains no message";
+
+/// Conceals and reveals messages in binary files with bit patterns.
+#[derive(Parser)]
+#[command(version, after_help = EXIT_CODES)]
+struct Cli {
+    #[command(subcommand)]
+    command: Command,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// Conceals a payload in a cover and writes the package.
+    Conceal {
+        #[command(flatten)]
+        key: Key,
+
+        #[command(flatten)]
+        options: Options,
+
+        /// The cover file, or `-` for standard input.
+        #[arg(short, long)]
+        cover: PathBuf,
+
+        /// The payload file, or `-` for standard input.
+        #[arg(short, long, default_value = "-")]
+        payload: PathBuf,
+
+        /// The package file to write, or `-` for standard output.
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Reveals the message hidden in a package.
+    Reveal {
+        #[command(flatten)]
+        key: Key,
+
+        /// The package file, or `-` for standard input.
+        #[arg(short, long, default_value = "-")]
+        input: PathBuf,
+
+        /// The message file to write, or `-` for standard output.
+        #[arg(short, long, default_value = "-")]
+        output: PathBuf,
+    },
+
+    /// Prints how many payload bytes a cover can hold, not accounting for encryption.
+    Capacity {
+        #[command(flatten)]
+        key: Key,
+
+        #[command(flatten)]
+        options: Options,
+
+        /// The cover file, or `-` for standard input.
+        #[arg(default_value = "-")]
+        cover: PathBuf,
+    },
+
+    /// Checks whether a package contains a message and prints its header.
+    ///
+    /// Without a passphrase or a mask, estimates whether the package contains a message by
+    /// steganalysis of its bytes instead, and prints the probability of embedding by the
+    /// chi-square attack as well as the proportions of used bytes estimated by RS analysis
+    /// and Sample Pair Analysis. With them, exits with code 5 if the package contains no
+    /// header.
+    #[command(mut_arg("passphrase", |arg| arg.required_unless_present(Resettable::Reset)))]
+    Analyze {
+        #[command(flatten)]
+        key: Option<Key>,
+
+        /// The package file, or `-` for standard input.
+        #[arg(default_value = "-")]
+        input: PathBuf,
+    },
+}
+
+/// The options shared by the sender and the receiver.
+#[derive(Args)]
+struct Key {
+    /// Derives the bit pattern from the passphrase.
+    #[arg(long, required_unless_present = "mask", conflicts_with = "mask")]
+    passphrase: Option<String>,
+
+    /// Uses a comma-separated list of bit masks cycled over the cover bytes as the bit
+    /// pattern, e.g. `0b1,0b10,4`.
+    #[arg(long, value_parser = parse_masks)]
+    mask: Option<Masks>,
+
+    /// The number of bits per cover byte used by a passphrase pattern.
+    #[arg(
+        long,
+        default_value_t = 1,
+        value_parser = clap::value_parser!(u32).range(1..=8),
+        conflicts_with = "mask"
+    )]
+    density: u32,
+
+    /// Scatters the message over a permutation of the cover bytes derived from the seed.
+    #[arg(long)]
+    seed: Option<String>,
+
+    /// Leaves the cover bytes in the range, e.g. `0..54` or `1024..`, unchanged. May be
+    /// repeated.
+    #[arg(long, value_parser = parse_range)]
+    skip: Vec<Range<usize>>,
+
+    /// Encrypts the message with the password.
+    #[cfg(feature = "encrypt")]
+    #[arg(long)]
+    password: Option<String>,
+}
+
+impl Key {
+    fn pattern(&self) -> Box<dyn FnMut(usize) -> Option<u8>> {
+        match (&self.passphrase, &self.mask) {
+            (Some(passphrase), _) => {
+                let mut pattern = KeyedPattern::with_density(passphrase, self.density);
+                Box::new(move |index| binary::Pattern::mask(&mut pattern, index))
+            }
+            (None, Some(Masks(masks))) => {
+                let masks = masks.clone();
+                Box::new(move |index| Some(masks[index % masks.len()]))
+            }
+            (None, None) => unreachable!("clap requires either a passphrase or a mask"),
+        }
+    }
+
+    fn regions(&self) -> Regions {
+        self.skip
+            .iter()
+            .fold(Regions::new(), |regions, range| regions.skip(range.clone()))
+    }
+}
+
+/// The options of the concealed data.
+#[derive(Args)]
+struct Options {
+    /// The error correction code: `repetition:N`, `hamming`, or `reed-solomon:DATA:PARITY`.
+    #[arg(long, value_parser = parse_fec)]
+    fec: Option<Fec>,
+
+    /// The checksum appended to the payload: `crc32`, `xxhash64`, or `sha256`.
+    #[arg(long, value_parser = parse_checksum)]
+    checksum: Option<Checksum>,
+}
+
+impl Options {
+    fn apply<W: Write>(
+        &self,
+        key: &Key,
+        mut carrier: binary::Carrier<Box<dyn FnMut(usize) -> Option<u8>>, W>,
+    ) -> binary::Carrier<Box<dyn FnMut(usize) -> Option<u8>>, W> {
+        if let Some(seed) = &key.seed {
+            carrier = carrier.permuted(seed);
+        }
+        carrier = carrier.skip_regions(key.regions());
+        if let Some(fec) = self.fec {
+            carrier = carrier.fec(fec);
+        }
+        if let Some(checksum) = self.checksum {
+            carrier = carrier.checksum(checksum);
+        }
+
+        carrier
+    }
+}
+
+#[derive(Clone)]
+struct Masks(Vec<u8>);
+
+fn parse_mask(mask: &str) -> Result<u8, String> {
+    let mask = mask.trim();
+
+    let parsed = if let Some(digits) = mask.strip_prefix("0b") {
+        u8::from_str_radix(digits, 2)
+    } else if let Some(digits) = mask.strip_prefix("0x") {
+        u8::from_str_radix(digits, 16)
+    } else {
+        mask.parse()
+    };
+
+    parsed.map_err(|error| format!("invalid mask `{mask}`: {error}"))
+}
+
+fn parse_masks(masks: &str) -> Result<Masks, String> {
+    masks
+        .split(',')
+        .map(parse_mask)
+        .collect::<Result<_, _>>()
+        .map(Masks)
+}
+
+fn parse_range(range: &str) -> Result<Range<usize>, String> {
+    let Some((start, end)) = range.split_once("..") else {
+        return Err(format!("invalid range `{range}`"));
+    };
+
+    let bound = |bound: &str, default| {
+        if bound.is_empty() {
+            Ok(default)
+        } else {
+            bound
+                .parse()
+                .map_err(|error| format!("invalid range `{range}`: {error}"))
+        }
+    };
+
+    Ok(bound(start, 0)?..bound(end, usize::MAX)?)
+}
+
+fn parse_fec(fec: &str) -> Result<Fec, String> {
+    let parameters: Vec<_> = fec.split(':').collect();
+
+    let number = |parameter: &str| {
+        parameter
+            .parse::<u8>()
+            .ok()
+            .filter(|&n| n > 0)
+            .ok_or_else(|| format!("invalid parameter `{parameter}`"))
+    };
+
+    match parameters.as_slice() {
+        ["repetition", n] => Ok(Fec::Repetition(number(n)?)),
+        ["hamming"] => Ok(Fec::Hamming),
+        ["reed-solomon", data, parity] => {
+            let (data, parity) = (number(data)?, number(parity)?);
+
+            if data as usize + parity as usize > 255 {
+                return Err("Reed-Solomon block length must not exceed 255".into());
+            }
+
+            Ok(Fec::ReedSolomon { data, parity })
+        }
+        _ => Err(format!("unknown error correction code `{fec}`")),
+    }
+}
+
+fn parse_checksum(checksum: &str) -> Result<Checksum, String> {
+    match checksum {
+        "crc32" => Ok(Checksum::Crc32),
+        "xxhash64" => Ok(Checksum::XxHash64),
+        "sha256" => Ok(Checksum::Sha256),
+        _ => Err(format!("unknown checksum `{checksum}`")),
+    }
+}
+
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+fn open(path: &Path) -> io::Result<Box<dyn Read>> {
+    if is_stdio(path) {
+        Ok(Box::new(io::stdin().lock()))
+    } else {
+        Ok(Box::new(File::open(path)?))
+    }
+}
+
+/// Writes the bytes to the file, which is only created once its contents are complete, so
+/// that a failed command neither truncates an existing file nor leaves a partial one.
+fn write(path: &Path, bytes: &[u8]) -> io::Result<()> {
+    if is_stdio(path) {
+        io::stdout().lock().write_all(bytes)
+    } else {
+        fs::write(path, bytes)
+    }
+}
+
+fn read(path: &Path) -> io::Result<Vec<u8>> {
+    let mut bytes = Vec::new();
+    open(path)?.read_to_end(&mut bytes)?;
+    Ok(bytes)
+}
+
+fn print_header(header: &Header) {
+    let describe = |option: Option<String>| option.unwrap_or_else(|| "none".into());
+
+    println!("version: {}", header.version);
+    println!("length: {}", header.len);
+    println!(
+        "fec: {}",
+        describe(header.fec.map(|fec| format!("{fec:?}")))
+    );
+    println!(
+        "checksum: {}",
+        describe(header.checksum.map(|checksum| format!("{checksum:?}")))
+    );
+    println!("encrypted: {}", header.encrypted);
+    println!("compressed: {}", header.compressed);
+    println!(
+        "regions: {}",
+        describe(
+            header
+                .regions
+                .map(|fingerprint| format!("{fingerprint:#010x}"))
+        )
+    );
+    println!(
+        "matrix: {}",
+        describe(header.matrix.map(|k| format!("k = {k}")))
+    );
+    println!(
+        "trellis: {}",
+        describe(header.trellis.map(|width| format!("width = {width}")))
+    );
+}
+
+fn run(command: Command) -> Result<ExitCode, Error> {
+    match command {
+        Command::Conceal {
+            key,
+            options,
+            cover,
+            payload,
+            output,
+        } => {
+            if is_stdio(&cover) && is_stdio(&payload) {
+                Cli::command()
+                    .error(
+                        ErrorKind::ArgumentConflict,
+                        "the cover and the payload cannot both be read from standard input",
+                    )
+                    .exit();
+            }
+
+            let payload = read(&payload)?;
+            let cover = open(&cover)?;
+            let mut package = Vec::new();
+
+            #[cfg(feature = "encrypt")]
+            if let Some(password) = &key.password {
+                if options.checksum.is_some() {
+                    Cli::command()
+                        .error(
+                            ErrorKind::ArgumentConflict,
+                            "encrypted messages are authenticated and take no checksum",
+                        )
+                        .exit();
+                }
+
+                let mut carrier =
+                    binary::EncryptedCarrier::new(password, key.pattern(), &mut package)
+                        .skip_regions(key.regions());
+                if let Some(seed) = &key.seed {
+                    carrier = carrier.permuted(seed);
+                }
+                if let Some(fec) = options.fec {
+                    carrier = carrier.fec(fec);
+                }
+
+                carrier.conceal(payload.as_slice(), cover)?;
+                drop(carrier);
+
+                write(&output, &package)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let carrier = binary::Carrier::with_header(payload.len(), key.pattern(), &mut package);
+            options
+                .apply(&key, carrier)
+                .conceal(payload.as_slice(), cover)?;
+            write(&output, &package)?;
+        }
+        Command::Reveal { key, input, output } => {
+            let input = open(&input)?;
+            let mut message = Vec::new();
+
+            #[cfg(feature = "encrypt")]
+            if let Some(password) = &key.password {
+                let mut package = binary::EncryptedPackage::new(password, key.pattern(), input)
+                    .skip_regions(key.regions());
+                if let Some(seed) = &key.seed {
+                    package = package.permuted(seed);
+                }
+
+                package.reveal(&mut message)?;
+                write(&output, &message)?;
+                return Ok(ExitCode::SUCCESS);
+            }
+
+            let mut package =
+                binary::Package::with_header(key.pattern(), input).skip_regions(key.regions());
+            if let Some(seed) = &key.seed {
+                package = package.permuted(seed);
+            }
+
+            package.reveal(&mut message)?;
+            write(&output, &message)?;
+        }
+        Command::Capacity {
+            key,
+            options,
+            cover,
+        } => {
+            let cover = read(&cover)?;
+
+            let carrier = binary::Carrier::with_header(0, key.pattern(), io::sink());
+            println!("{}", options.apply(&key, carrier).capacity_for(&cover));
+        }
+        Command::Analyze { key: None, input } => {
+            let samples = read(&input)?;
+
+            let describe = |ratio: Option<f64>| {
+                ratio.map_or_else(|| "none".into(), |ratio| format!("{ratio:.3}"))
+            };
+            let probability = analysis::chi_square(&samples, samples.len().max(1))
+                .last()
+                .map(|point| point.probability);
+
+            println!("chi-square: {}", describe(probability));
+            println!(
+                "rs: {}",
+                describe(analysis::rs(&samples).map(|rs| rs.ratio))
+            );
+            println!(
+                "sample pairs: {}",
+                describe(analysis::sample_pairs(&samples).map(|spa| spa.ratio))
+            );
+        }
+        Command::Analyze {
+            key: Some(key),
+            input,
+        } => {
+            if key.passphrase.is_none() && key.mask.is_none() {
+                Cli::command()
+                    .error(
+                        ErrorKind::MissingRequiredArgument,
+                        "the header is only read with a passphrase or a mask",
+                    )
+                    .exit();
+            }
+
+            let mut package = binary::Package::with_header(key.pattern(), open(&input)?)
+                .skip_regions(key.regions());
+            if let Some(seed) = &key.seed {
+                package = package.permuted(seed);
+            }
+
+            let Some(header) = package.detect()? else {
+                eprintln!("asbs: package contains no message");
+                return Ok(ExitCode::from(5));
+            };
+
+            print_header(&header);
+        }
+    }
+
+    Ok(ExitCode::SUCCESS)
+}
+
+fn exit_code(error: &Error) -> ExitCode {
+    ExitCode::from(match error {
+        Error::InsufficientCapacity { .. } | Error::PatternExhausted => 3,
+        Error::Verification(_) | Error::Uncorrectable(_) | Error::RegionMismatch => 4,
+        #[cfg(feature = "encrypt")]
+        Error::Authentication(_) => 4,
+        Error::Header(_) | Error::TruncatedLength => 5,
+        _ => 1,
+    })
+}
+
+fn main() -> ExitCode {
+    match run(Cli::parse().command) {
+        Ok(code) => code,
+        Err(error) => {
+            eprintln!("asbs: {error}");
+            exit_code(&error)
+        }
+    }
+}
@@ -0,0 +1,66 @@
+//! Text implementations of [`Conceal`][crate::Conceal] and [`Reveal`][crate::Reveal] traits.
+//!
+//! Plain text, such as chat messages and documents, has no low-order bits to spare, as
+//! every change of a byte changes a character. The carriers in this module conceal messages
+//! in UTF-8 covers by adding or choosing characters that do not change how the text reads.
+//!
+//! Covers are read as a whole and have to be valid UTF-8, otherwise
+//! [`Error::Format`] is returned.
+//!
+//! ## Zero-width Characters
+//!
+//! The [`zero_width`] carrier inserts invisible characters between the graphemes of the
+//! cover, each of which holds two bits of the message. It holds any amount of data, but
+//! the characters are easily detected and stripped by software aware of them.
+//!
+//! ## Whitespace
+//!
+//! The [`whitespace`] carrier appends spaces and tabs to the ends of the lines of the cover,
+//! and optionally doubles the spaces between words, which suits configuration files and
+//! source code. The whitespace is invisible in most editors, but is lost to those that trim
+//! it, which the package detects.
+//!
+//! ## Homoglyphs
+//!
+//! The [`homoglyph`] carrier substitutes visually identical characters for the characters
+//! of the cover, such as the Cyrillic `а` for the Latin `a`, each of which holds a bit of
+//! the message. It adds no characters, so it survives the stripping of invisible ones, but
+//! its capacity depends on the text and the scripts the homoglyphs are taken from.
+//!
+//! ## Examples
+//!
+//! Concealing a secret token in a chat message:
+//!
+//! ```
+//! use asbs::{text::zero_width, Conceal, Reveal};
+//!
+//! let mut package = Vec::new();
+//! zero_width::Carrier::new(&mut package)
+//!     .conceal(b"token".as_slice(), "See you at noon!".as_bytes())?;
+//!
+//! let package = String::from_utf8(package).unwrap();
+//! assert_eq!(zero_width::strip(&package), "See you at noon!");
+//!
+//! let mut token = Vec::new();
+//! zero_width::Package::new(package.as_bytes()).reveal(&mut token)?;
+//!
+//! assert_eq!(token, b"token");
+//! # Ok::<(), asbs::Error>(())
+//! ```
+
+pub mod homoglyph;
+pub mod whitespace;
+pub mod zero_width;
+
+mod graphemes;
+
+use crate::Error;
+use std::io::Read;
+
+/// Reads the whole text from the reader.
+fn read_text(mut reader: impl Read) -> Result<String, Error> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes)?;
+
+    String::from_utf8(bytes).map_err(|error| Error::Format(error.into()))
+}
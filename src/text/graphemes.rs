@@ -0,0 +1,106 @@
+/// The zero width joiner, which joins emoji into sequences.
+pub(super) const ZWJ: char = '\u{200d}';
+
+/// Returns `true` if the character extends the preceding grapheme, such as a combining mark
+/// or a variation selector.
+///
+/// This covers the combining marks of the common scripts rather than the whole `Extend`
+/// property of Unicode.
+fn is_extend(c: char) -> bool {
+    matches!(
+        c,
+        '\u{0300}'..='\u{036f}'
+            | '\u{0483}'..='\u{0489}'
+            | '\u{0591}'..='\u{05bd}'
+            | '\u{05bf}'
+            | '\u{05c1}'..='\u{05c2}'
+            | '\u{05c4}'..='\u{05c5}'
+            | '\u{05c7}'
+            | '\u{0610}'..='\u{061a}'
+            | '\u{064b}'..='\u{065f}'
+            | '\u{0670}'
+            | '\u{06d6}'..='\u{06dc}'
+            | '\u{06df}'..='\u{06e4}'
+            | '\u{06e7}'..='\u{06e8}'
+            | '\u{06ea}'..='\u{06ed}'
+            | '\u{0900}'..='\u{0903}'
+            | '\u{093a}'..='\u{094f}'
+            | '\u{0951}'..='\u{0957}'
+            | '\u{0962}'..='\u{0963}'
+            | '\u{0e31}'
+            | '\u{0e34}'..='\u{0e3a}'
+            | '\u{0e47}'..='\u{0e4e}'
+            | '\u{1ab0}'..='\u{1aff}'
+            | '\u{1dc0}'..='\u{1dff}'
+            | '\u{200c}'..='\u{200d}'
+            | '\u{20d0}'..='\u{20ff}'
+            | '\u{fe00}'..='\u{fe0f}'
+            | '\u{fe20}'..='\u{fe2f}'
+            | '\u{1f3fb}'..='\u{1f3ff}'
+            | '\u{e0020}'..='\u{e007f}'
+            | '\u{e0100}'..='\u{e01ef}'
+    )
+}
+
+/// Returns `true` if the character is a regional indicator, pairs of which form flags.
+fn is_regional_indicator(c: char) -> bool {
+    matches!(c, '\u{1f1e6}'..='\u{1f1ff}')
+}
+
+/// Returns `true` if the character is an emoji or another pictograph that may be joined
+/// into a sequence.
+pub(super) fn is_pictographic(c: char) -> bool {
+    matches!(
+        c,
+        '\u{00a9}'
+            | '\u{00ae}'
+            | '\u{203c}'
+            | '\u{2049}'
+            | '\u{2122}'
+            | '\u{2139}'
+            | '\u{2194}'..='\u{21aa}'
+            | '\u{2300}'..='\u{23ff}'
+            | '\u{2460}'..='\u{27bf}'
+            | '\u{2900}'..='\u{2bff}'
+            | '\u{3030}'
+            | '\u{303d}'
+            | '\u{3297}'
+            | '\u{3299}'
+            | '\u{1f000}'..='\u{1faff}'
+    )
+}
+
+/// Returns the byte offsets of the boundaries between the graphemes of the text, excluding
+/// its start and end.
+///
+/// The graphemes approximate the extended grapheme clusters of Unicode: combining marks,
+/// emoji sequences, flags, and CRLF line breaks are never split.
+pub(super) fn boundaries(text: &str) -> Vec<usize> {
+    let mut boundaries = Vec::new();
+
+    let mut chars = text.char_indices().peekable();
+    let mut regional_indicators = 0;
+
+    while let Some((_, previous)) = chars.next() {
+        let Some(&(offset, next)) = chars.peek() else {
+            break;
+        };
+
+        if is_regional_indicator(previous) {
+            regional_indicators += 1;
+        } else {
+            regional_indicators = 0;
+        }
+
+        let is_joined = (previous == '\r' && next == '\n')
+            || is_extend(next)
+            || (previous == ZWJ && is_pictographic(next))
+            || (regional_indicators % 2 == 1 && is_regional_indicator(next));
+
+        if !is_joined {
+            boundaries.push(offset);
+        }
+    }
+
+    boundaries
+}
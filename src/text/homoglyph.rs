@@ -0,0 +1,394 @@
+//! Characters substituted by visually identical ones of the same or another script.
+//!
+//! Every character of the cover that has a homoglyph in the table of the carrier holds one
+//! bit of the message: a zero bit leaves the basic character, such as the Latin `a`, and a
+//! one bit substitutes its homoglyph, such as the Cyrillic `а`. The bits start from the most
+//! significant ones, and are preceded by the message length as a 16-bit big-endian integer,
+//! so that the receiver knows how many characters carry it. The characters after the message
+//! are left unchanged.
+//!
+//! The table consists of the pairs of every enabled [`Script`], which are [`Script::Latin`]
+//! and [`Script::Common`] by default. A character having homoglyphs in several scripts is
+//! paired with the one of the script listed first in [`Script`], so the receiver has to
+//! enable the same scripts as the sender.
+//!
+//! Unicode normalization and confusable detection, such as the ones of spam filters and
+//! domain registries, destroy or reveal the message.
+
+use crate::{text, Conceal, Error, Reveal};
+use std::io::{BufWriter, Read, Write};
+
+/// The number of bits of the message length that precedes the message.
+const LEN_BITS: usize = 16;
+
+/// The scripts the homoglyphs of the table belong to.
+///
+/// The scripts other than [`Script::Latin`] and [`Script::Common`] mix scripts within
+/// words, which is flagged by spam filters and browsers, but make for a much larger
+/// capacity. They are unsafe for covers written in Cyrillic or Greek, whose native letters
+/// would be read as homoglyphs and replaced with Latin ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Script {
+    /// Latin letters and compatibility characters of the Latin script, such as `K` and the
+    /// Kelvin sign `K`.
+    Latin,
+
+    /// Punctuation and spaces that belong to no script, such as `;` and the Greek question
+    /// mark `;`, or a space and a no-break space. No-break spaces prevent line wrapping.
+    Common,
+
+    /// Latin letters and their Cyrillic homoglyphs, such as `a` and `а`.
+    Cyrillic,
+
+    /// Latin letters and their Greek homoglyphs, such as `A` and `Α`.
+    Greek,
+}
+
+impl Script {
+    /// The scripts in the order of their precedence.
+    const ALL: [Self; 4] = [Self::Latin, Self::Common, Self::Cyrillic, Self::Greek];
+
+    /// The scripts enabled by default, which do not mix scripts within words.
+    const DEFAULT: [Self; 2] = [Self::Latin, Self::Common];
+
+    /// Returns the pairs of basic characters and their homoglyphs in the script.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::text::homoglyph::Script;
+    ///
+    /// assert!(Script::Cyrillic.pairs().contains(&('a', '\u{430}')));
+    /// ```
+    #[must_use]
+    pub fn pairs(self) -> &'static [(char, char)] {
+        match self {
+            Self::Latin => &[('K', '\u{212a}'), ('\u{c5}', '\u{212b}')],
+            Self::Common => &[(' ', '\u{a0}'), ('-', '\u{2010}'), (';', '\u{37e}')],
+            Self::Cyrillic => &[
+                ('a', '\u{430}'),
+                ('c', '\u{441}'),
+                ('e', '\u{435}'),
+                ('h', '\u{4bb}'),
+                ('i', '\u{456}'),
+                ('j', '\u{458}'),
+                ('o', '\u{43e}'),
+                ('p', '\u{440}'),
+                ('s', '\u{455}'),
+                ('x', '\u{445}'),
+                ('y', '\u{443}'),
+                ('A', '\u{410}'),
+                ('B', '\u{412}'),
+                ('C', '\u{421}'),
+                ('E', '\u{415}'),
+                ('H', '\u{41d}'),
+                ('I', '\u{406}'),
+                ('J', '\u{408}'),
+                ('K', '\u{41a}'),
+                ('M', '\u{41c}'),
+                ('O', '\u{41e}'),
+                ('P', '\u{420}'),
+                ('S', '\u{405}'),
+                ('T', '\u{422}'),
+                ('X', '\u{425}'),
+            ],
+            Self::Greek => &[
+                ('o', '\u{3bf}'),
+                ('A', '\u{391}'),
+                ('B', '\u{392}'),
+                ('E', '\u{395}'),
+                ('H', '\u{397}'),
+                ('I', '\u{399}'),
+                ('K', '\u{39a}'),
+                ('M', '\u{39c}'),
+                ('N', '\u{39d}'),
+                ('O', '\u{39f}'),
+                ('P', '\u{3a1}'),
+                ('T', '\u{3a4}'),
+                ('X', '\u{3a7}'),
+                ('Y', '\u{3a5}'),
+                ('Z', '\u{396}'),
+            ],
+        }
+    }
+}
+
+/// The pairs of characters a carrier or a package substitutes.
+#[derive(Debug, Clone)]
+struct Table {
+    pairs: Vec<(char, char)>,
+}
+
+impl Table {
+    /// Creates a table of the pairs of the supplied scripts, so that no character belongs
+    /// to more than one pair.
+    fn new(scripts: &[Script]) -> Self {
+        let mut pairs: Vec<(char, char)> = Vec::new();
+
+        for script in Script::ALL
+            .into_iter()
+            .filter(|script| scripts.contains(script))
+        {
+            for &(basic, homoglyph) in script.pairs() {
+                let is_taken = pairs.iter().any(|&(other_basic, other_homoglyph)| {
+                    [other_basic, other_homoglyph]
+                        .iter()
+                        .any(|&c| c == basic || c == homoglyph)
+                });
+
+                if !is_taken {
+                    pairs.push((basic, homoglyph));
+                }
+            }
+        }
+
+        Self { pairs }
+    }
+
+    /// Returns the pair the character belongs to along with the bit it encodes.
+    fn find(&self, c: char) -> Option<(&(char, char), bool)> {
+        self.pairs.iter().find_map(|pair| match c {
+            c if c == pair.0 => Some((pair, false)),
+            c if c == pair.1 => Some((pair, true)),
+            _ => None,
+        })
+    }
+
+    /// Returns the number of characters of the text that hold a bit.
+    fn count(&self, text: &str) -> usize {
+        text.chars().filter(|&c| self.find(c).is_some()).count()
+    }
+}
+
+/// A text carrier that conceals a message by substituting homoglyphs for characters.
+///
+/// It reads both the payload and the cover to the end in the
+/// [`conceal`][crate::Conceal::conceal] method, and writes the cover with the characters
+/// of the table substituted according to the bits of the message length and the message.
+///
+/// If the cover has too few characters of the table, [`Error::InsufficientCapacity`] is
+/// returned.
+///
+/// # Examples
+///
+/// Concealing a message without mixing scripts:
+///
+/// ```
+/// use asbs::{text::homoglyph, Conceal};
+///
+/// let cover = "Kids - both Karl and Kate - visit Kelly; Kurt stays at home ".repeat(2);
+///
+/// let mut package = Vec::new();
+/// homoglyph::Carrier::new(&mut package).conceal(b"hi".as_slice(), cover.as_bytes())?;
+///
+/// let package = String::from_utf8(package).unwrap();
+/// assert_ne!(package, cover);
+/// assert_eq!(package.chars().count(), cover.chars().count());
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Carrier<W>
+where
+    W: Write,
+{
+    writer: BufWriter<W>,
+    table: Table,
+}
+
+impl<W> Carrier<W>
+where
+    W: Write,
+{
+    /// Creates a new [`Carrier<W>`] with the supplied writer, which substitutes the
+    /// homoglyphs of [`Script::Latin`] and [`Script::Common`].
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            table: Table::new(&Script::DEFAULT),
+        }
+    }
+
+    /// Makes the table of the carrier consist of the pairs of the supplied scripts.
+    ///
+    /// The receiver has to supply the same scripts to [`Package::scripts`].
+    #[must_use]
+    pub fn scripts(mut self, scripts: &[Script]) -> Self {
+        self.table = Table::new(scripts);
+        self
+    }
+
+    /// Returns the maximum length of a payload in bytes that the carrier can conceal in the
+    /// supplied cover, accounting for the message length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::text::homoglyph::{self, Script};
+    ///
+    /// let cover = "The economic crisis spreads across Europe as prices rise.";
+    ///
+    /// let carrier = homoglyph::Carrier::new(Vec::new());
+    /// assert_eq!(carrier.capacity_for(cover), 0);
+    ///
+    /// let scripts = [Script::Common, Script::Cyrillic];
+    /// let carrier = homoglyph::Carrier::new(Vec::new()).scripts(&scripts);
+    /// assert_eq!(carrier.capacity_for(cover), 3);
+    /// ```
+    #[must_use]
+    pub fn capacity_for(&self, cover: &str) -> usize {
+        let bytes = self.table.count(cover).saturating_sub(LEN_BITS) / 8;
+        bytes.min(u16::MAX.into())
+    }
+}
+
+impl<W> Conceal for &mut Carrier<W>
+where
+    W: Write,
+{
+    type Err = Error;
+
+    fn conceal<P: Read, C: Read>(self, mut payload: P, cover: C) -> Result<usize, Error> {
+        let mut message = Vec::new();
+        payload.read_to_end(&mut message)?;
+
+        let cover = text::read_text(cover)?;
+
+        let available_bits = self.table.count(&cover);
+        let needed_bits = LEN_BITS + message.len() * 8;
+
+        if needed_bits > available_bits || u16::try_from(message.len()).is_err() {
+            return Err(Error::InsufficientCapacity {
+                needed_bits: needed_bits as u64,
+                available_bits: available_bits as u64,
+            });
+        }
+
+        let mut bits = (message.len() as u16)
+            .to_be_bytes()
+            .into_iter()
+            .chain(message)
+            .flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1));
+
+        let package: String = cover
+            .chars()
+            .map(|c| match self.table.find(c) {
+                Some((&(basic, homoglyph), _)) => match bits.next() {
+                    Some(true) => homoglyph,
+                    Some(false) => basic,
+                    None => c,
+                },
+                None => c,
+            })
+            .collect();
+
+        self.writer.write_all(package.as_bytes())?;
+        self.writer.flush()?;
+
+        Ok(package.len())
+    }
+}
+
+/// A text package that reveals a message concealed by substituting homoglyphs for
+/// characters.
+///
+/// It reads the package to the end in the [`reveal`][crate::Reveal::reveal] method and
+/// writes the message the characters of its table encode. If the package ends before the
+/// message length is read, [`Error::TruncatedLength`] is returned, and if it ends before
+/// the message does, [`Error::TruncatedPayload`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{text::homoglyph::{self, Script}, Conceal, Reveal};
+///
+/// let cover = "The economic crisis spreads across Europe as prices rise.";
+///
+/// let scripts = [Script::Common, Script::Cyrillic];
+///
+/// let mut package = Vec::new();
+/// homoglyph::Carrier::new(&mut package)
+///     .scripts(&scripts)
+///     .conceal(b"key".as_slice(), cover.as_bytes())?;
+///
+/// let mut message = Vec::new();
+/// homoglyph::Package::new(package.as_slice())
+///     .scripts(&scripts)
+///     .reveal(&mut message)?;
+///
+/// assert_eq!(message, b"key");
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Package<R>
+where
+    R: Read,
+{
+    reader: R,
+    table: Table,
+}
+
+impl<R> Package<R>
+where
+    R: Read,
+{
+    /// Creates a new [`Package<R>`] with the supplied reader, which reads the homoglyphs
+    /// of [`Script::Latin`] and [`Script::Common`].
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            table: Table::new(&Script::DEFAULT),
+        }
+    }
+
+    /// Makes the table of the package consist of the pairs of the supplied scripts, which
+    /// have to be the ones of the [`Carrier::scripts`] of the sender.
+    #[must_use]
+    pub fn scripts(mut self, scripts: &[Script]) -> Self {
+        self.table = Table::new(scripts);
+        self
+    }
+}
+
+impl<R> Reveal for &mut Package<R>
+where
+    R: Read,
+{
+    type Err = Error;
+
+    fn reveal<W: Write>(self, output: W) -> Result<usize, Error> {
+        let package = text::read_text(&mut self.reader)?;
+
+        let bits: Vec<bool> = package
+            .chars()
+            .filter_map(|c| self.table.find(c).map(|(_, bit)| bit))
+            .collect();
+
+        let mut bytes = bits
+            .chunks_exact(8)
+            .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)));
+
+        let len_bytes: Vec<u8> = bytes.by_ref().take(LEN_BITS / 8).collect();
+        let Ok(len_bytes) = <[u8; 2]>::try_from(len_bytes) else {
+            return Err(Error::TruncatedLength);
+        };
+
+        let len = u16::from_be_bytes(len_bytes) as usize;
+        let message: Vec<u8> = bytes.take(len).collect();
+
+        if message.len() < len {
+            return Err(Error::TruncatedPayload {
+                expected: len as u64,
+                actual: message.len() as u64,
+            });
+        }
+
+        let mut output = BufWriter::new(output);
+        output.write_all(&message)?;
+        output.flush()?;
+
+        Ok(message.len())
+    }
+}
@@ -0,0 +1,455 @@
+//! Spaces and tabs appended to the ends of lines, in the manner of SNOW.
+//!
+//! Every line of the cover holds a number of bits of the message in its trailing
+//! whitespace, a space for each zero bit and a tab for each one bit, starting from the most
+//! significant bits. Optionally, the gaps between the words of a line hold one more bit
+//! each, a single space for a zero bit and a double space for a one bit, which are read
+//! before the trailing whitespace of the line.
+//!
+//! The message is preceded by its length as a 32-bit big-endian integer, so that the
+//! receiver knows how many lines carry it.
+//!
+//! Editors and tools that trim trailing whitespace destroy the message. The lines that lost
+//! their whitespace are reported by [`Package`] with [`StrippedLines`].
+
+use crate::{text, Conceal, Error, Reveal};
+use std::{
+    error, fmt,
+    io::{BufWriter, Read, Write},
+    ops::Range,
+};
+
+/// The number of bits of the message length that precedes the message.
+const LEN_BITS: usize = 32;
+
+/// A line of text, split into its parts.
+struct Line<'a> {
+    /// The content of the line without its trailing whitespace.
+    content: &'a str,
+
+    /// The spaces and tabs at the end of the line.
+    trailing: &'a str,
+
+    /// The line break, which is empty for the last line of a text not ending with one.
+    newline: &'a str,
+}
+
+impl Line<'_> {
+    /// Returns the byte ranges of the runs of spaces between the words of the line.
+    fn gaps(&self) -> Vec<Range<usize>> {
+        let mut gaps = Vec::new();
+
+        let mut start = None;
+        let mut previous = None;
+        for (offset, c) in self.content.char_indices() {
+            match (c, start) {
+                (' ', None) if previous.is_some_and(|c: char| !c.is_whitespace()) => {
+                    start = Some(offset);
+                }
+                (' ', _) => {}
+                (c, Some(gap)) => {
+                    if !c.is_whitespace() {
+                        gaps.push(gap..offset);
+                    }
+                    start = None;
+                }
+                _ => {}
+            }
+            previous = Some(c);
+        }
+
+        gaps
+    }
+}
+
+/// Returns the lines of the text.
+fn lines(text: &str) -> impl Iterator<Item = Line<'_>> {
+    text.split_inclusive('\n').map(|line| {
+        let body = line
+            .strip_suffix('\n')
+            .map_or(line, |body| body.strip_suffix('\r').unwrap_or(body));
+        let content = body.trim_end_matches([' ', '\t']);
+
+        Line {
+            content,
+            trailing: &body[content.len()..],
+            newline: &line[body.len()..],
+        }
+    })
+}
+
+/// Returns the text without the trailing whitespace of its lines.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::text::whitespace;
+///
+/// assert_eq!(whitespace::strip("one \t\r\ntwo\t\nthree  "), "one\r\ntwo\nthree");
+/// ```
+#[must_use]
+pub fn strip(text: &str) -> String {
+    lines(text)
+        .flat_map(|line| [line.content, line.newline])
+        .collect()
+}
+
+/// Returns `true` if any line of the text ends with whitespace that may be a part of a
+/// concealed message.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::text::whitespace;
+///
+/// assert!(whitespace::detect("one\ntwo \nthree"));
+/// assert!(!whitespace::detect("one\ntwo\nthree\n"));
+/// ```
+#[must_use]
+pub fn detect(text: &str) -> bool {
+    lines(text).any(|line| !line.trailing.is_empty())
+}
+
+/// The error returned in [`Error::Format`] when lines of a package lost their trailing
+/// whitespace, e.g. to an editor that trims it on save.
+///
+/// Only the lines followed by lines that still carry whitespace can be detected, as the
+/// lines after the end of the message carry none.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StrippedLines {
+    lines: Vec<usize>,
+}
+
+impl StrippedLines {
+    /// Returns the zero-based numbers of the stripped lines.
+    #[must_use]
+    pub fn lines(&self) -> &[usize] {
+        &self.lines
+    }
+}
+
+impl fmt::Display for StrippedLines {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "trailing whitespace was stripped from {} lines, starting at line {}",
+            self.lines.len(),
+            self.lines[0] + 1,
+        )
+    }
+}
+
+impl error::Error for StrippedLines {}
+
+/// A text carrier that conceals a message in whitespace at the ends of lines.
+///
+/// It reads both the payload and the cover to the end in the
+/// [`conceal`][crate::Conceal::conceal] method, and writes the cover with the bits of the
+/// message length and the message in the whitespace of its lines. The trailing whitespace
+/// already present in the cover is removed beforehand, see [`strip`].
+///
+/// If the lines of the cover cannot hold the message, [`Error::InsufficientCapacity`] is
+/// returned.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{text::whitespace, Conceal};
+///
+/// let cover = "[server]\nhost = example.com\nport = 8080\n\n[client]\ntimeout = 30\n";
+///
+/// let mut package = Vec::new();
+/// whitespace::Carrier::new(&mut package)
+///     .bits_per_line(16)
+///     .conceal(b"hi".as_slice(), cover.as_bytes())?;
+///
+/// let package = String::from_utf8(package).unwrap();
+/// assert!(whitespace::detect(&package));
+/// assert_eq!(whitespace::strip(&package), cover);
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Carrier<W>
+where
+    W: Write,
+{
+    writer: BufWriter<W>,
+    bits_per_line: usize,
+    inter_word: bool,
+}
+
+impl<W> Carrier<W>
+where
+    W: Write,
+{
+    /// Creates a new [`Carrier<W>`] with the supplied writer, which conceals eight bits
+    /// at the end of every line.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            bits_per_line: 8,
+            inter_word: false,
+        }
+    }
+
+    /// Sets the number of bits concealed at the end of every line, which is also the
+    /// number of whitespace characters appended to it.
+    ///
+    /// The receiver does not have to know the number.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `bits` is zero.
+    #[must_use]
+    pub fn bits_per_line(mut self, bits: usize) -> Self {
+        assert!(bits > 0, "lines must hold at least one bit");
+
+        self.bits_per_line = bits;
+        self
+    }
+
+    /// Makes the carrier also conceal a bit in every gap between the words of the lines
+    /// that carry the message, by leaving a single space for a zero bit or a double space
+    /// for a one bit.
+    ///
+    /// This increases the capacity of prose, but alters the gaps in string literals and
+    /// other places of source code where they are significant. The receiver has to enable
+    /// [`Package::inter_word`] as well.
+    #[must_use]
+    pub fn inter_word(mut self) -> Self {
+        self.inter_word = true;
+        self
+    }
+
+    /// Returns the number of bits the lines of the cover hold, including the message length.
+    fn available_bits(&self, cover: &str) -> usize {
+        lines(cover)
+            .map(|line| {
+                let gaps = if self.inter_word {
+                    line.gaps().len()
+                } else {
+                    0
+                };
+                self.bits_per_line + gaps
+            })
+            .sum()
+    }
+
+    /// Returns the maximum length of a payload in bytes that the carrier can conceal in the
+    /// supplied cover, accounting for the message length.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use asbs::text::whitespace;
+    ///
+    /// let carrier = whitespace::Carrier::new(Vec::new());
+    ///
+    /// assert_eq!(carrier.capacity_for("one\ntwo\nthree\nfour\nfive\nsix\n"), 2);
+    /// ```
+    #[must_use]
+    pub fn capacity_for(&self, cover: &str) -> usize {
+        let bytes = self.available_bits(cover).saturating_sub(LEN_BITS) / 8;
+        bytes.min(u32::MAX as usize)
+    }
+}
+
+impl<W> Conceal for &mut Carrier<W>
+where
+    W: Write,
+{
+    type Err = Error;
+
+    fn conceal<P: Read, C: Read>(self, mut payload: P, cover: C) -> Result<usize, Error> {
+        let mut message = Vec::new();
+        payload.read_to_end(&mut message)?;
+
+        let cover = strip(&text::read_text(cover)?);
+
+        let available_bits = self.available_bits(&cover);
+        let needed_bits = LEN_BITS + message.len() * 8;
+
+        if needed_bits > available_bits || u32::try_from(message.len()).is_err() {
+            return Err(Error::InsufficientCapacity {
+                needed_bits: needed_bits as u64,
+                available_bits: available_bits as u64,
+            });
+        }
+
+        let mut bits = (message.len() as u32)
+            .to_be_bytes()
+            .into_iter()
+            .chain(message)
+            .flat_map(|byte| (0..8).rev().map(move |bit| byte >> bit & 1 == 1))
+            .peekable();
+
+        let mut package = String::with_capacity(cover.len() + needed_bits);
+        for line in lines(&cover) {
+            if bits.peek().is_none() {
+                package.push_str(line.content);
+                package.push_str(line.newline);
+                continue;
+            }
+
+            let mut start = 0;
+            if self.inter_word {
+                for gap in line.gaps() {
+                    let Some(bit) = bits.next() else {
+                        break;
+                    };
+
+                    package.push_str(&line.content[start..gap.start]);
+                    package.push_str(if bit { "  " } else { " " });
+                    start = gap.end;
+                }
+            }
+            package.push_str(&line.content[start..]);
+
+            package.extend(bits.by_ref().take(self.bits_per_line).map(|bit| {
+                if bit {
+                    '\t'
+                } else {
+                    ' '
+                }
+            }));
+            package.push_str(line.newline);
+        }
+
+        self.writer.write_all(package.as_bytes())?;
+        self.writer.flush()?;
+
+        Ok(package.len())
+    }
+}
+
+/// A text package that reveals a message concealed in whitespace at the ends of lines.
+///
+/// It reads the package to the end in the [`reveal`][crate::Reveal::reveal] method and
+/// writes the message the bits in the whitespace of its lines encode.
+///
+/// If a line carrying the message has no trailing whitespace while a later one does, its
+/// whitespace was most likely stripped, and [`Error::Format`] with [`StrippedLines`] is
+/// returned. If the lines end before the message length is read,
+/// [`Error::TruncatedLength`] is returned, and if they end before the message does,
+/// [`Error::TruncatedPayload`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{text::whitespace, Conceal, Error, Reveal};
+///
+/// let mut package = Vec::new();
+/// whitespace::Carrier::new(&mut package)
+///     .conceal(b"hi".as_slice(), "one\ntwo\nthree\nfour\nfive\nsix\n".as_bytes())?;
+///
+/// let package = String::from_utf8(package).unwrap();
+///
+/// let mut message = Vec::new();
+/// whitespace::Package::new(package.as_bytes()).reveal(&mut message)?;
+///
+/// assert_eq!(message, b"hi");
+///
+/// // An editor trimmed the second line
+/// let package = package.replacen("two        \n", "two\n", 1);
+///
+/// let Err(Error::Format(error)) = whitespace::Package::new(package.as_bytes()).reveal(Vec::new())
+/// else {
+///     panic!("stripped lines were not detected");
+/// };
+///
+/// let stripped = error.downcast_ref::<whitespace::StrippedLines>().unwrap();
+/// assert_eq!(stripped.lines(), [1]);
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Package<R>
+where
+    R: Read,
+{
+    reader: R,
+    inter_word: bool,
+}
+
+impl<R> Package<R>
+where
+    R: Read,
+{
+    /// Creates a new [`Package<R>`] with the supplied reader.
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self {
+            reader,
+            inter_word: false,
+        }
+    }
+
+    /// Makes the package also read a bit from every gap between the words of the lines,
+    /// as concealed by a [`Carrier::inter_word`] carrier.
+    #[must_use]
+    pub fn inter_word(mut self) -> Self {
+        self.inter_word = true;
+        self
+    }
+}
+
+impl<R> Reveal for &mut Package<R>
+where
+    R: Read,
+{
+    type Err = Error;
+
+    fn reveal<W: Write>(self, output: W) -> Result<usize, Error> {
+        let package = text::read_text(&mut self.reader)?;
+
+        let mut bits = Vec::new();
+        let mut bare_lines = Vec::new();
+        let mut last_carrying_line = None;
+        for (number, line) in lines(&package).enumerate() {
+            if self.inter_word {
+                bits.extend(line.gaps().into_iter().map(|gap| gap.len() > 1));
+            }
+
+            if line.trailing.is_empty() {
+                bare_lines.push(number);
+            } else {
+                bits.extend(line.trailing.chars().map(|c| c == '\t'));
+                last_carrying_line = Some(number);
+            }
+        }
+
+        let stripped: Vec<usize> = bare_lines
+            .into_iter()
+            .filter(|&number| last_carrying_line.is_some_and(|last| number < last))
+            .collect();
+
+        if !stripped.is_empty() {
+            return Err(Error::Format(StrippedLines { lines: stripped }.into()));
+        }
+
+        let mut bytes = bits
+            .chunks_exact(8)
+            .map(|bits| bits.iter().fold(0, |byte, &bit| byte << 1 | u8::from(bit)));
+
+        let len_bytes: Vec<u8> = bytes.by_ref().take(LEN_BITS / 8).collect();
+        let Ok(len_bytes) = <[u8; 4]>::try_from(len_bytes) else {
+            return Err(Error::TruncatedLength);
+        };
+
+        let len = u32::from_be_bytes(len_bytes) as usize;
+        let message: Vec<u8> = bytes.take(len).collect();
+
+        if message.len() < len {
+            return Err(Error::TruncatedPayload {
+                expected: len as u64,
+                actual: message.len() as u64,
+            });
+        }
+
+        let mut output = BufWriter::new(output);
+        output.write_all(&message)?;
+        output.flush()?;
+
+        Ok(message.len())
+    }
+}
@@ -0,0 +1,303 @@
+//! Zero-width characters inserted between the graphemes of a text.
+//!
+//! Every byte of the message is encoded as a run of four zero-width characters, each of
+//! which holds two bits, starting from the least significant ones:
+//!
+//! | Bits | Character                        |
+//! |------|----------------------------------|
+//! | `00` | U+200B zero width space          |
+//! | `01` | U+200C zero width non-joiner     |
+//! | `10` | U+200D zero width joiner         |
+//! | `11` | U+2060 word joiner               |
+//!
+//! The runs are inserted at the boundaries between graphemes, so that combining marks,
+//! emoji sequences, and flags are never split. Zero width joiners that join emoji into a
+//! sequence are a part of the text rather than the message, and are kept by [`strip`] and
+//! ignored by [`Package`].
+
+use crate::{
+    text::{
+        self,
+        graphemes::{self, ZWJ},
+    },
+    Conceal, Error, Reveal,
+};
+use std::io::{BufWriter, Read, Write};
+
+/// The characters that encode every two bits of the message, in the order of their values.
+pub const ALPHABET: [char; 4] = ['\u{200b}', '\u{200c}', ZWJ, '\u{2060}'];
+
+/// Returns the characters that encode the byte.
+fn encode(byte: u8) -> impl Iterator<Item = char> {
+    (0..4).map(move |pair| ALPHABET[usize::from(byte >> (2 * pair) & 0b11)])
+}
+
+/// Returns `true` if the character, which is preceded and followed by the supplied ones,
+/// is a part of a concealed message.
+fn is_hidden(previous: Option<char>, c: char, next: Option<char>) -> bool {
+    let is_emoji_joiner = c == ZWJ
+        && previous.is_some_and(|c| graphemes::is_pictographic(c) || c == '\u{fe0f}')
+        && next.is_some_and(graphemes::is_pictographic);
+
+    ALPHABET.contains(&c) && !is_emoji_joiner
+}
+
+/// Returns the characters of the text along with whether each of them is hidden.
+fn classify(text: &str) -> impl Iterator<Item = (char, bool)> + '_ {
+    let chars: Vec<char> = text.chars().collect();
+
+    (0..chars.len()).map(move |index| {
+        let previous = index.checked_sub(1).map(|index| chars[index]);
+        let c = chars[index];
+        let next = chars.get(index + 1).copied();
+
+        (c, is_hidden(previous, c, next))
+    })
+}
+
+/// Returns the text without the zero-width characters of concealed messages.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::text::zero_width;
+///
+/// assert_eq!(zero_width::strip("a\u{200b}\u{2060}\u{200c}\u{200b}b"), "ab");
+///
+/// // Emoji sequences are kept intact
+/// assert_eq!(zero_width::strip("👩\u{200d}💻"), "👩\u{200d}💻");
+/// ```
+#[must_use]
+pub fn strip(text: &str) -> String {
+    classify(text)
+        .filter(|&(_, is_hidden)| !is_hidden)
+        .map(|(c, _)| c)
+        .collect()
+}
+
+/// Returns `true` if the text contains zero-width characters that may be a part of a
+/// concealed message.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::text::zero_width;
+///
+/// assert!(zero_width::detect("a\u{200b}b"));
+/// assert!(!zero_width::detect("👩\u{200d}💻"));
+/// ```
+#[must_use]
+pub fn detect(text: &str) -> bool {
+    classify(text).any(|(_, is_hidden)| is_hidden)
+}
+
+/// The positions in the cover where the [`Carrier`] inserts the message.
+///
+/// The bytes of the message are distributed evenly over the positions, so that each
+/// position holds a run of the same number of bytes, give or take one.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Placement {
+    /// Every boundary between two graphemes.
+    #[default]
+    Spread,
+
+    /// Every boundary after a whitespace character, i.e. the starts of words.
+    Words,
+
+    /// The start of the cover.
+    Start,
+
+    /// The end of the cover.
+    End,
+}
+
+impl Placement {
+    /// Returns the byte offsets of the positions within the text.
+    fn offsets(self, text: &str) -> Vec<usize> {
+        match self {
+            Self::Spread => graphemes::boundaries(text),
+            Self::Words => graphemes::boundaries(text)
+                .into_iter()
+                .filter(|&offset| text[..offset].ends_with(char::is_whitespace))
+                .collect(),
+            Self::Start => vec![0],
+            Self::End => vec![text.len()],
+        }
+    }
+}
+
+/// A text carrier that conceals a message in zero-width characters.
+///
+/// It reads both the payload and the cover to the end in the
+/// [`conceal`][crate::Conceal::conceal] method, and writes the cover with the runs of
+/// zero-width characters inserted at the positions chosen by its [`Placement`]. Zero-width
+/// characters already present in the cover are removed beforehand, so that they are not
+/// mistaken for the message, see [`strip`].
+///
+/// If the cover has no positions to insert the message at, such as a cover of a single
+/// word with [`Placement::Words`], [`Error::InsufficientCapacity`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{text::zero_width::{self, Placement}, Conceal};
+///
+/// let mut package = Vec::new();
+/// zero_width::Carrier::new(&mut package)
+///     .placement(Placement::End)
+///     .conceal(b"\x1b".as_slice(), "text".as_bytes())?;
+///
+/// assert_eq!(String::from_utf8(package).unwrap(), "text\u{2060}\u{200d}\u{200c}\u{200b}");
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Carrier<W>
+where
+    W: Write,
+{
+    writer: BufWriter<W>,
+    placement: Placement,
+}
+
+impl<W> Carrier<W>
+where
+    W: Write,
+{
+    /// Creates a new [`Carrier<W>`] with the supplied writer, which spreads the message
+    /// over the whole cover.
+    #[must_use]
+    pub fn new(writer: W) -> Self {
+        Self {
+            writer: BufWriter::new(writer),
+            placement: Placement::default(),
+        }
+    }
+
+    /// Makes the carrier insert the message at the supplied positions.
+    ///
+    /// The receiver does not have to know the placement.
+    #[must_use]
+    pub fn placement(mut self, placement: Placement) -> Self {
+        self.placement = placement;
+        self
+    }
+}
+
+impl<W> Conceal for &mut Carrier<W>
+where
+    W: Write,
+{
+    type Err = Error;
+
+    fn conceal<P: Read, C: Read>(self, mut payload: P, cover: C) -> Result<usize, Error> {
+        let mut message = Vec::new();
+        payload.read_to_end(&mut message)?;
+
+        let cover = strip(&text::read_text(cover)?);
+        let offsets = self.placement.offsets(&cover);
+
+        if offsets.is_empty() && !message.is_empty() {
+            return Err(Error::InsufficientCapacity {
+                needed_bits: message.len() as u64 * 8,
+                available_bits: 0,
+            });
+        }
+
+        let mut package = String::with_capacity(cover.len() + message.len() * 12);
+        let mut start = 0;
+        for (position, &offset) in offsets.iter().enumerate() {
+            package.push_str(&cover[start..offset]);
+            start = offset;
+
+            let bytes = position * message.len() / offsets.len()
+                ..(position + 1) * message.len() / offsets.len();
+
+            for &byte in &message[bytes] {
+                package.extend(encode(byte));
+            }
+        }
+        package.push_str(&cover[start..]);
+
+        self.writer.write_all(package.as_bytes())?;
+        self.writer.flush()?;
+
+        Ok(package.len())
+    }
+}
+
+/// A text package that reveals a message concealed in zero-width characters.
+///
+/// It reads the package to the end in the [`reveal`][crate::Reveal::reveal] method and
+/// writes every byte encoded by the zero-width characters in it, regardless of where they
+/// are placed. If the number of the characters is not a multiple of four,
+/// [`Error::Format`] is returned.
+///
+/// # Examples
+///
+/// ```
+/// use asbs::{text::zero_width, Reveal};
+///
+/// let mut message = Vec::new();
+/// zero_width::Package::new("te\u{2060}\u{200d}\u{200c}\u{200b}xt".as_bytes())
+///     .reveal(&mut message)?;
+///
+/// assert_eq!(message, b"\x1b");
+/// # Ok::<(), asbs::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct Package<R>
+where
+    R: Read,
+{
+    reader: R,
+}
+
+impl<R> Package<R>
+where
+    R: Read,
+{
+    /// Creates a new [`Package<R>`] with the supplied reader.
+    #[must_use]
+    pub fn new(reader: R) -> Self {
+        Self { reader }
+    }
+}
+
+impl<R> Reveal for &mut Package<R>
+where
+    R: Read,
+{
+    type Err = Error;
+
+    fn reveal<W: Write>(self, output: W) -> Result<usize, Error> {
+        let package = text::read_text(&mut self.reader)?;
+
+        let symbols: Vec<u8> = classify(&package)
+            .filter(|&(_, is_hidden)| is_hidden)
+            .map(|(c, _)| ALPHABET.iter().position(|&symbol| symbol == c).unwrap() as u8)
+            .collect();
+
+        if !symbols.len().is_multiple_of(4) {
+            return Err(Error::Format(
+                "zero-width characters do not form whole bytes".into(),
+            ));
+        }
+
+        let message: Vec<u8> = symbols
+            .chunks_exact(4)
+            .map(|pairs| {
+                pairs
+                    .iter()
+                    .enumerate()
+                    .fold(0, |byte, (pair, &bits)| byte | bits << (2 * pair))
+            })
+            .collect();
+
+        let mut output = BufWriter::new(output);
+        output.write_all(&message)?;
+        output.flush()?;
+
+        Ok(message.len())
+    }
+}
@@ -0,0 +1,116 @@
+use asbs::{analysis, binary, formats::netpbm, Conceal, Error};
+
+fn noise(len: usize, mut state: u64) -> Vec<u8> {
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            (state >> 32) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn it_locates_the_end_of_sequential_embedding() -> Result<(), Error> {
+    // Values cluster around even ones, as in smooth images after some processing.
+    let cover: Vec<u8> = noise(16384, 1).iter().map(|&byte| byte / 4 * 2).collect();
+
+    let mut package = Vec::new();
+    binary::Carrier::new(|_| Some(0b1), &mut package)
+        .conceal(noise(1024, 2).as_slice(), cover.as_slice())?;
+
+    let curve = analysis::chi_square(&package, 1024);
+    assert_eq!(curve.len(), 16);
+
+    for point in &curve[..8] {
+        assert!(point.probability > 0.5);
+    }
+
+    for point in &curve[9..] {
+        assert!(point.probability < 0.01);
+    }
+
+    assert!(analysis::chi_square(&cover, 1024)
+        .iter()
+        .all(|point| point.probability < 0.01));
+
+    Ok(())
+}
+
+#[test]
+fn it_analyzes_image_channels() -> Result<(), Error> {
+    let raster: Vec<u8> = noise(3 * 64 * 64, 1)
+        .iter()
+        .map(|&byte| byte & !1)
+        .collect();
+    let cover = [b"P6 64 64 255\n".as_slice(), &raster].concat();
+
+    let mut image = netpbm::Image::decode(cover.as_slice())?
+        .skip_channel(1)
+        .skip_channel(2);
+
+    let mut package = Vec::new();
+    binary::Carrier::new(|_| Some(0b1), &mut package)
+        .conceal(noise(512, 2).as_slice(), image.samples().as_slice())?;
+
+    image.set_samples(&package);
+
+    let embedded = analysis::chi_square(&image.channel_samples(0), 4096);
+    let untouched = analysis::chi_square(&image.channel_samples(1), 4096);
+
+    assert_eq!(image.channel_samples(0), package);
+    assert!(embedded[0].probability > 0.5);
+    assert!(untouched[0].probability < 0.01);
+
+    Ok(())
+}
+
+/// A smooth image with some noise, whose rows are concatenated.
+fn smooth(width: usize, height: usize) -> Vec<u8> {
+    let noise = noise(width * height, 3);
+
+    (0..width * height)
+        .map(|i| {
+            let (x, y) = ((i % width) as f64, (i / width) as f64);
+            let value = 128.0 + 60.0 * (x / 23.0).sin() + 50.0 * (y / 31.0).cos();
+            (value + f64::from(noise[i] % 7) - 3.0) as u8
+        })
+        .collect()
+}
+
+#[test]
+fn it_estimates_the_ratio_of_replaced_bits() -> Result<(), Error> {
+    let cover = smooth(256, 256);
+    let selection = noise(cover.len(), 4);
+
+    for density in [0, 64, 128, 255] {
+        let pattern = |i: usize| Some(u8::from(selection[i] < density));
+        let selected = (0..cover.len()).filter(|&i| pattern(i) == Some(1)).count();
+
+        let mut package = Vec::new();
+        binary::Carrier::new(pattern, &mut package)
+            .conceal(noise(selected / 8, 5).as_slice(), cover.as_slice())?;
+
+        let ratio = selected as f64 / cover.len() as f64;
+        let rs = analysis::rs(&package).unwrap();
+        let spa = analysis::sample_pairs(&package).unwrap();
+
+        // RS analysis is unreliable when nearly all samples are used.
+        if density < 255 {
+            assert!((rs.ratio - ratio).abs() < 0.1);
+        }
+
+        assert!((spa.ratio - ratio).abs() < 0.1);
+        assert_eq!(spa.pairs, cover.len() - 1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_declines_to_estimate_too_few_samples() {
+    assert_eq!(analysis::rs(&[1, 2, 3]), None);
+    assert_eq!(analysis::sample_pairs(&[1]), None);
+    assert_eq!(analysis::sample_pairs(&[]), None);
+}
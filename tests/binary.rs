@@ -1,6 +1,21 @@
-use asbs::{binary, Conceal, Reveal};
+use asbs::{
+    binary::{
+        self,
+        pattern::{KeyedPattern, Words},
+        sample::{Be, Le, Sample, I24},
+        Checksum, Fec, Pattern, Regions,
+    },
+    Conceal, Error, Reveal,
+};
 use std::{fs::File, io};
 
+/// Returns a cover of pseudo-random bytes, whose bits need to change about half the time.
+fn noisy_cover(len: u32) -> Vec<u8> {
+    (0..len)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 13) as u8)
+        .collect()
+}
+
 #[test]
 fn it_conceals_and_reveals_with_known_length() -> io::Result<()> {
     let pattern = |i| Some(((1u8 << (i % 3)) - 1) << 1);
@@ -80,13 +95,23 @@ fn it_handles_partial_conceal() -> io::Result<()> {
 
     let mut package = Vec::with_capacity(0);
 
-    assert_eq!(
-        io::ErrorKind::WriteZero,
+    assert!(matches!(
         binary::Carrier::new(pattern, &mut package)
-        .conceal(b"this message won't be written".as_slice(), [].as_slice())
-            .unwrap_err()
-            .kind()
-    );
+            .conceal(b"this message won't be written".as_slice(), [].as_slice()),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 8,
+            available_bits: 0,
+        }),
+    ));
+
+    assert!(matches!(
+        binary::Carrier::with_embedded_len(29, pattern, &mut package)
+            .conceal(b"this message won't be written".as_slice(), [].as_slice()),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 296,
+            available_bits: 0,
+        }),
+    ));
 
     Ok(())
 }
@@ -102,13 +127,828 @@ fn it_handles_partial_reveal() -> io::Result<()> {
         File::open("tests/resources/cover")?,
     )?;
 
+    assert!(matches!(
+        binary::Package::with_embedded_len(pattern, package.as_slice())
+            .reveal([].as_mut_slice()),
+        Err(Error::Io(error)) if error.kind() == io::ErrorKind::WriteZero,
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_truncated_data() -> io::Result<()> {
+    let pattern = |_| Some(0b11);
+
+    let mut package = Vec::new();
+
+    assert!(matches!(
+        binary::Carrier::with_embedded_len(32, pattern, &mut package).conceal(
+            b"a short message".as_slice(),
+            File::open("tests/resources/cover")?
+        ),
+        Err(Error::TruncatedPayload {
+            expected: 32,
+            actual: 15,
+        }),
+    ));
+
+    assert!(matches!(
+        binary::Package::with_embedded_len(pattern, [0u8; 16].as_slice()).reveal(Vec::new()),
+        Err(Error::TruncatedLength),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_estimates_capacity() -> io::Result<()> {
+    let pattern = |i| Some(1u8 << (i % 3));
+
+    let cover = std::fs::read("tests/resources/cover")?;
+
+    assert_eq!(binary::capacity(pattern, cover.len()), cover.len() as u64);
+
+    let carrier = || {
+        binary::Carrier::with_header(0, pattern, Vec::new())
+            .fec(Fec::Hamming)
+            .checksum(Checksum::Crc32)
+            .permuted("a shared seed")
+    };
+
+    let len = carrier().capacity_for(&cover);
+    assert!(len > 0);
+
+    for (len, fits) in [(len, true), (len + 1, false)] {
+        let payload = vec![0x5a; len];
+
+        let mut carrier = binary::Carrier::with_header(len, pattern, Vec::new())
+            .fec(Fec::Hamming)
+            .checksum(Checksum::Crc32)
+            .permuted("a shared seed");
+
+        let estimate = carrier.dry_run(payload.as_slice(), cover.as_slice())?;
+        assert_eq!(fits, estimate.fits());
+
+        match carrier.conceal(payload.as_slice(), cover.as_slice()) {
+            Ok(_) => assert!(fits),
+            Err(Error::InsufficientCapacity {
+                needed_bits,
+                available_bits,
+            }) => {
+                assert!(!fits);
+                assert_eq!(estimate.needed_bits, needed_bits);
+                assert_eq!(estimate.available_bits, available_bits);
+            }
+            Err(error) => return Err(error.into()),
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_keyed_pattern() -> io::Result<()> {
+    let pattern = KeyedPattern::with_density("correct horse battery staple", 2);
+
+    let payload = b"a very very secret message";
+
+    let mut package = Vec::new();
+
+    binary::Carrier::with_embedded_len(payload.len(), pattern.clone(), &mut package)
+        .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+    let mut revealed_payload = Vec::new();
+
+    binary::Package::with_embedded_len(pattern, package.as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    Ok(())
+}
+
+#[test]
+fn it_derives_distinct_keyed_patterns() {
+    let mut first = KeyedPattern::new("first passphrase");
+    let mut second = KeyedPattern::new("second passphrase");
+
+    let first_masks: Vec<_> = (0..64).map(|i| first.mask(i)).collect();
+    let second_masks: Vec<_> = (0..64).map(|i| second.mask(i)).collect();
+
+    assert_ne!(first_masks, second_masks);
     assert_eq!(
-        io::ErrorKind::WriteZero,
+        first_masks,
+        (0..64)
+            .map(|i| KeyedPattern::new("first passphrase").mask(i))
+            .collect::<Vec<_>>(),
+    );
+}
+
+#[test]
+fn it_conceals_and_reveals_in_permuted_order() -> io::Result<()> {
+    let pattern = |_| Some(1);
+
+    let cover = std::fs::read("tests/resources/cover")?;
+
+    let payload = b"a very very secret message";
+
+    let mut package = Vec::new();
+
+    assert_eq!(
+        cover.len(),
+        binary::Carrier::with_embedded_len(payload.len(), pattern, &mut package)
+            .permuted("a shared seed")
+            .conceal(payload.as_slice(), cover.as_slice())?,
+    );
+
+    let last_changed = package.iter().zip(&cover).rposition(|(a, b)| a != b);
+    assert!(last_changed.is_some_and(|index| index >= (payload.len() + 8) * 8));
+
+    let mut revealed_payload = Vec::new();
+
+    binary::Package::with_embedded_len(pattern, package.as_slice())
+        .permuted("a shared seed")
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let mut revealed_payload = Vec::new();
+
+    binary::Package::with_len(payload.len(), pattern, package.as_slice())
+        .permuted("another seed")
+        .reveal(&mut revealed_payload)?;
+
+    assert_ne!(*payload, *revealed_payload);
+
+    Ok(())
+}
+
+#[test]
+fn it_writes_nothing_when_permuted_payload_does_not_fit() {
+    let mut package = Vec::new();
+
+    assert!(matches!(
+        binary::Carrier::with_embedded_len(22, |_| Some(1), &mut package)
+            .permuted("a shared seed")
+            .conceal(b"this message won't fit".as_slice(), [0u8; 16].as_slice()),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 240,
+            available_bits: 16,
+        }),
+    ));
+
+    assert!(package.is_empty());
+}
+
+#[test]
+fn it_corrects_corrupted_packages() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let payload = b"a very very secret message";
+
+    for fec in [
+        Fec::Repetition(3),
+        Fec::Hamming,
+        Fec::ReedSolomon {
+            data: 16,
+            parity: 8,
+        },
+    ] {
+        let mut package = Vec::new();
+
+        binary::Carrier::with_embedded_len(payload.len(), pattern, &mut package)
+            .fec(fec)
+            .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+        package[0] ^= 0b100;
+        package[10] ^= 0b1;
+        package[50] ^= 0b10;
+
+        let mut revealed_payload = Vec::new();
+
         binary::Package::with_embedded_len(pattern, package.as_slice())
-            .reveal([].as_mut_slice())
-            .unwrap_err()
-            .kind()
+            .fec(fec)
+            .reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload, "{fec:?}");
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_uncorrectable_packages() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let fec = Fec::ReedSolomon {
+        data: 16,
+        parity: 4,
+    };
+
+    let mut package = Vec::new();
+
+    binary::Carrier::new(pattern, &mut package)
+        .fec(fec)
+        .conceal(
+            b"a short message".as_slice(),
+            File::open("tests/resources/cover")?,
+        )?;
+
+    for byte in &mut package[..32] {
+        *byte ^= 0b101;
+    }
+
+    assert!(matches!(
+        binary::Package::with_len(15, pattern, package.as_slice())
+            .fec(fec)
+            .reveal(Vec::new()),
+        Err(Error::Uncorrectable(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_header() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let payload = b"a very very secret message";
+
+    let mut package = Vec::new();
+
+    binary::Carrier::with_header(payload.len(), pattern, &mut package)
+        .fec(Fec::Hamming)
+        .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+    package[1] ^= 0b10;
+    package[40] ^= 0b100;
+
+    let mut revealed_payload = Vec::new();
+
+    let mut revealed = binary::Package::with_header(pattern, package.as_slice());
+    revealed.reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let header = revealed.header().copied().unwrap();
+    assert_eq!(header.len, payload.len() as u64);
+    assert_eq!(header.fec, Some(Fec::Hamming));
+    assert!(!header.encrypted);
+
+    Ok(())
+}
+
+#[test]
+fn it_detects_header() -> io::Result<()> {
+    let pattern = |_| Some(0b11);
+
+    let mut package = Vec::new();
+
+    binary::Carrier::with_header(0, pattern, &mut package)
+        .conceal([].as_slice(), File::open("tests/resources/cover")?)?;
+
+    let header = binary::Package::with_header(pattern, package.as_slice()).detect()?;
+    assert_eq!(header.map(|header| header.len), Some(0));
+
+    let cover = File::open("tests/resources/cover")?;
+    assert_eq!(None, binary::Package::with_header(pattern, cover).detect()?);
+
+    assert!(matches!(
+        binary::Package::with_header(pattern, File::open("tests/resources/cover")?)
+            .reveal(Vec::new()),
+        Err(Error::Header(binary::HeaderError::Missing)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_oversized_header_extension() {
+    // Magic, version, flags, length, and an extension length of 2^28 - 1 bytes.
+    let header = [0xa5, 0xb5, 1, 0, 0, 0xff, 0xff, 0xff, 0x7f];
+    let package: Vec<u8> = header.iter().flat_map(|&byte| [byte; 3]).collect();
+
+    let package = || binary::Package::with_header(|_| Some(0xff), package.as_slice());
+
+    assert!(matches!(package().detect(), Ok(None)));
+    assert!(matches!(
+        package().reveal(Vec::new()),
+        Err(Error::Header(binary::HeaderError::Malformed)),
+    ));
+}
+
+#[test]
+fn it_verifies_checksums() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let payload = b"a very very secret message";
+
+    for checksum in [Checksum::Crc32, Checksum::XxHash64, Checksum::Sha256] {
+        let mut package = Vec::new();
+
+        binary::Carrier::with_embedded_len(payload.len(), pattern, &mut package)
+            .checksum(checksum)
+            .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+        let mut revealed_payload = Vec::new();
+
+        binary::Package::with_embedded_len(pattern, package.as_slice())
+            .checksum(checksum)
+            .reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload, "{checksum:?}");
+
+        let result = binary::Package::with_len(payload.len(), |_| Some(0b11), package.as_slice())
+            .checksum(checksum)
+            .reveal(Vec::new());
+
+        assert!(
+            matches!(result, Err(Error::Verification(_))),
+            "{checksum:?}",
+        );
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_verifies_checksum_from_header() -> io::Result<()> {
+    let pattern = |_| Some(0b11);
+
+    let payload = b"a very very secret message";
+
+    let mut package = Vec::new();
+
+    binary::Carrier::with_header(payload.len(), pattern, &mut package)
+        .checksum(Checksum::XxHash64)
+        .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+    let mut revealed_payload = Vec::new();
+
+    binary::Package::with_header(pattern, package.as_slice()).reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    package[150] ^= 0b1;
+
+    assert!(matches!(
+        binary::Package::with_header(pattern, package.as_slice()).reveal(Vec::new()),
+        Err(Error::Verification(_)),
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_regions() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let cover = std::fs::read("tests/resources/cover")?;
+
+    let payload = b"a very very secret message";
+    let regions = Regions::new().skip(..16).skip(64..=95);
+
+    for seed in [None, Some("a shared seed")] {
+        let mut package = Vec::new();
+
+        {
+            let mut carrier =
+                binary::Carrier::with_embedded_len(payload.len(), pattern, &mut package)
+                    .skip_regions(regions.clone());
+            if let Some(seed) = seed {
+                carrier = carrier.permuted(seed);
+            }
+
+            carrier.conceal(payload.as_slice(), cover.as_slice())?;
+        }
+
+        assert_eq!(cover[..16], package[..16]);
+        assert_eq!(cover[64..96], package[64..96]);
+
+        let mut revealed_payload = Vec::new();
+
+        let mut revealed = binary::Package::with_embedded_len(pattern, package.as_slice())
+            .skip_regions(regions.clone());
+        if let Some(seed) = seed {
+            revealed = revealed.permuted(seed);
+        }
+
+        revealed.reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+    }
+
+    let mut package = Vec::new();
+
+    binary::Carrier::with_header(payload.len(), pattern, &mut package)
+        .skip_regions(regions.clone())
+        .conceal(payload.as_slice(), cover.as_slice())?;
+
+    let mut revealed =
+        binary::Package::with_header(pattern, package.as_slice()).skip_regions(regions.clone());
+    revealed.reveal(Vec::new())?;
+
+    assert_eq!(
+        revealed.header().and_then(|header| header.regions),
+        Some(regions.fingerprint()),
     );
 
+    assert!(matches!(
+        binary::Package::with_header(pattern, package.as_slice())
+            .skip_regions(regions.clone().skip(300..))
+            .reveal(Vec::new()),
+        Err(Error::RegionMismatch),
+    ));
+
+    Ok(())
+}
+
+fn conceal_and_reveal_in_words<S: Sample>(mask: S::Mask) -> io::Result<()> {
+    let pattern = || Words::<S, _>::new(move |_| Some(mask));
+
+    let cover: Vec<u8> = (0..4099u32).map(|i| (i * 31 % 251) as u8).collect();
+
+    let payload = b"a very very secret message";
+
+    for seed in [None, Some("a shared seed")] {
+        let mut package = Vec::new();
+
+        {
+            let mut carrier = binary::Carrier::with_header(payload.len(), pattern(), &mut package);
+            if let Some(seed) = seed {
+                carrier = carrier.permuted(seed);
+            }
+
+            carrier.conceal(payload.as_slice(), cover.as_slice())?;
+        }
+
+        assert_eq!(cover.len(), package.len());
+
+        let samples = cover.chunks_exact(S::LEN).zip(package.chunks_exact(S::LEN));
+        for (cover_sample, package_sample) in samples {
+            let changed = S::read(cover_sample) ^ S::read(package_sample);
+            assert_eq!(changed & !mask.into(), 0);
+        }
+
+        let trailing = cover.len() / S::LEN * S::LEN;
+        assert_eq!(cover[trailing..], package[trailing..]);
+
+        let mut revealed_payload = Vec::new();
+
+        let mut revealed = binary::Package::with_header(pattern(), package.as_slice());
+        if let Some(seed) = seed {
+            revealed = revealed.permuted(seed);
+        }
+
+        revealed.reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_in_words() -> io::Result<()> {
+    conceal_and_reveal_in_words::<Le<u16>>(0b11)?;
+    conceal_and_reveal_in_words::<Be<u16>>(0b1_0000_0001)?;
+    conceal_and_reveal_in_words::<Le<I24>>(0b11)?;
+    conceal_and_reveal_in_words::<Be<u32>>(0b101)?;
+    conceal_and_reveal_in_words::<Le<f32>>(0b11)?;
+
+    let pattern = Words::<Le<I24>, _>::new(|_| Some(u32::MAX));
+    assert_eq!(binary::capacity(pattern, 10), 72);
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_lsb_matching() -> io::Result<()> {
+    let cover: Vec<u8> = (0..2048).map(|i| (i * 37 % 256) as u8).collect();
+    let payload = b"a very very secret message";
+
+    let conceal = |seed: &str| -> io::Result<Vec<u8>> {
+        let mut package = Vec::new();
+        binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+            .lsb_matching(seed)
+            .conceal(payload.as_slice(), cover.as_slice())?;
+
+        Ok(package)
+    };
+
+    let package = conceal("a seed")?;
+    assert_eq!(package, conceal("a seed")?);
+    assert_ne!(package, conceal("another seed")?);
+
+    let differences: Vec<i16> = cover
+        .iter()
+        .zip(&package)
+        .map(|(&cover, &package)| i16::from(package) - i16::from(cover))
+        .filter(|&difference| difference != 0)
+        .collect();
+
+    assert!(differences.iter().all(|difference| difference.abs() == 1));
+    assert!(differences.contains(&1));
+    assert!(differences.contains(&-1));
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(|_| Some(0b1), package.as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let mut package = Vec::new();
+    binary::Carrier::new(|_| Some(0b1), &mut package)
+        .lsb_matching("a seed")
+        .conceal([0b1010_1010].as_slice(), [0xff, 0x00].repeat(4).as_slice())?;
+
+    assert_eq!(package, [0xfe, 0x01].repeat(4));
+
+    let (max, min) = ([0xff, 0xff, 0x7f], [0x00, 0x00, 0x80]);
+
+    let mut package = Vec::new();
+    let pattern = Words::<Le<I24>, _>::new(|_| Some(0b1));
+    binary::Carrier::new(pattern, &mut package)
+        .lsb_matching("a seed")
+        .conceal(
+            [0b0000_1111].as_slice(),
+            [max, min].concat().repeat(4).as_slice(),
+        )?;
+
+    let (below_max, above_min) = ([0xfe, 0xff, 0x7f], [0x01, 0x00, 0x80]);
+    let expected = [
+        [max, above_min].concat().repeat(2),
+        [below_max, min].concat().repeat(2),
+    ];
+    assert_eq!(package, expected.concat());
+
+    let floats = [f32::MAX, -f32::MAX, 0.0, -0.0];
+
+    let mut package = Vec::new();
+    let pattern = Words::<Le<f32>, _>::new(|_| Some(0b1));
+    binary::Carrier::new(pattern, &mut package)
+        .lsb_matching("a seed")
+        .conceal(
+            [0b0000_1111].as_slice(),
+            floats.map(f32::to_le_bytes).concat().repeat(2).as_slice(),
+        )?;
+
+    let floats: Vec<f32> = package
+        .chunks(4)
+        .map(|bytes| f32::from_le_bytes(bytes.try_into().unwrap()))
+        .collect();
+    let below_max = f32::from_bits(f32::MAX.to_bits() - 1);
+    assert_eq!(floats[..2], [f32::MAX, -f32::MAX]);
+    assert!(floats[2] > 0.0 && floats[3] < 0.0);
+    assert_eq!(floats[4..], [below_max, -below_max, 0.0, -0.0]);
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_matrix_embedding() -> io::Result<()> {
+    let cover = noisy_cover(4096);
+    let payload = b"a very very secret message";
+
+    let changed_bits = |package: &[u8], mask: u8| -> u64 {
+        cover
+            .iter()
+            .zip(package)
+            .map(|(cover, package)| u64::from(((cover ^ package) & mask).count_ones()))
+            .sum()
+    };
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+        .conceal(payload.as_slice(), cover.as_slice())?;
+
+    let replaced_bits = changed_bits(&package, 0b1);
+
+    for seed in [None, Some("a seed")] {
+        let mut package = Vec::new();
+
+        let statistics = {
+            let mut carrier =
+                binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+                    .matrix_embedding();
+            if let Some(seed) = seed {
+                carrier = carrier.permuted(seed);
+            }
+
+            carrier.conceal(payload.as_slice(), cover.as_slice())?;
+            *carrier.matrix_statistics().unwrap()
+        };
+
+        assert_eq!(statistics.changed_bits, changed_bits(&package, 0b1));
+        assert!(statistics.changed_bits < replaced_bits);
+        assert!(statistics.k > 1);
+
+        let mut revealed_payload = Vec::new();
+
+        let mut revealed = binary::Package::with_header(|_| Some(0b1), package.as_slice());
+        if let Some(seed) = seed {
+            revealed = revealed.permuted(seed);
+        }
+
+        revealed.reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+        assert_eq!(revealed.header().unwrap().matrix, Some(statistics.k));
+    }
+
+    let mut package = Vec::new();
+
+    let pattern = |_| Some(0b11);
+    let k = {
+        let mut carrier = binary::Carrier::with_embedded_len(payload.len(), pattern, &mut package)
+            .matrix_embedding()
+            .lsb_matching("a seed");
+
+        carrier.conceal(payload.as_slice(), cover.as_slice())?;
+        carrier.matrix_statistics().unwrap().k
+    };
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_embedded_len(pattern, package.as_slice())
+        .matrix_embedding(k)
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let result = binary::Carrier::with_embedded_len(payload.len(), |_| Some(0b1), Vec::new())
+        .matrix_embedding()
+        .conceal(payload.as_slice(), &cover[..128]);
+
+    assert!(matches!(
+        result,
+        Err(Error::InsufficientCapacity {
+            needed_bits: 272,
+            available_bits: 128,
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_syndrome_trellis() -> io::Result<()> {
+    let cover = noisy_cover(8192);
+    let payload = b"a very very secret message";
+
+    // Changing the samples at even indices is practically forbidden.
+    let costs = |i: usize| if i.is_multiple_of(2) { 1e9 } else { 1.0 };
+
+    for seed in [None, Some("a seed")] {
+        let mut package = Vec::new();
+
+        let statistics = {
+            let mut carrier =
+                binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+                    .syndrome_trellis("a shared seed", costs);
+            if let Some(seed) = seed {
+                carrier = carrier.permuted(seed);
+            }
+
+            carrier.conceal(payload.as_slice(), cover.as_slice())?;
+            *carrier.trellis_statistics().unwrap()
+        };
+
+        assert!(statistics.cost < 1e9);
+        assert_eq!(statistics.width, 32);
+
+        let mut revealed_payload = Vec::new();
+
+        let mut revealed = binary::Package::with_header(|_| Some(0b1), package.as_slice())
+            .syndrome_trellis("a shared seed");
+        if let Some(seed) = seed {
+            revealed = revealed.permuted(seed);
+        }
+
+        revealed.reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+        assert_eq!(revealed.header().unwrap().trellis, Some(statistics.width));
+
+        if seed.is_none() {
+            let mut revealed = binary::Package::with_header(|_| Some(0b1), package.as_slice());
+            let result = revealed.reveal(io::sink());
+
+            assert!(matches!(result, Err(Error::MissingSeed)));
+
+            let header = binary::Package::with_header(|_| Some(0b1), package.as_slice())
+                .detect()?
+                .unwrap();
+            assert_eq!(header.trellis, Some(statistics.width));
+        }
+    }
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b11), &mut package)
+        .syndrome_trellis("a shared seed", |_| 1.0)
+        .lsb_matching("a seed")
+        .checksum(Checksum::Crc32)
+        .conceal(payload.as_slice(), cover.as_slice())?;
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(|_| Some(0b11), package.as_slice())
+        .syndrome_trellis("a shared seed")
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_embeddings() -> io::Result<()> {
+    let cover = noisy_cover(4096);
+    let payload = b"a very very secret message";
+
+    let changed_planes = |package: &[u8]| -> Vec<u64> {
+        (0..8)
+            .map(|pow| {
+                cover
+                    .iter()
+                    .zip(package)
+                    .filter(|(cover, package)| (*cover ^ *package) >> pow & 1 == 1)
+                    .count() as u64
+            })
+            .collect()
+    };
+
+    for (seed, matrix) in [(None, false), (Some("a seed"), false), (None, true)] {
+        let mut package = Vec::new();
+
+        let report = {
+            let mut carrier =
+                binary::Carrier::with_embedded_len(payload.len(), |_| Some(0b11), &mut package)
+                    .lsb_matching("a seed")
+                    .reporting();
+            if let Some(seed) = seed {
+                carrier = carrier.permuted(seed);
+            }
+            if matrix {
+                carrier = carrier.matrix_embedding();
+            }
+
+            carrier.conceal(payload.as_slice(), cover.as_slice())?;
+            carrier.report().unwrap().clone()
+        };
+
+        let plane_changes = changed_planes(&package);
+
+        assert_eq!(report.embedded_bits, 8 * (8 + payload.len() as u64));
+        assert_eq!(report.available_bits, 2 * cover.len() as u64);
+        assert_eq!(report.plane_changes, plane_changes);
+        assert_eq!(report.changed_bits, plane_changes.iter().sum::<u64>());
+
+        let last_changed = cover
+            .iter()
+            .zip(&package)
+            .rposition(|(cover, package)| cover != package);
+        assert!(report.last_byte >= last_changed);
+
+        if seed.is_none() && !matrix {
+            assert_eq!(report.touched_bytes, report.embedded_bits / 2);
+            assert_eq!(
+                report.last_byte,
+                Some(report.embedded_bits as usize / 2 - 1)
+            );
+        }
+    }
+
+    let mut carrier =
+        binary::Carrier::new(Words::<Le<u16>, _>::new(|_| Some(0x0101)), Vec::new()).reporting();
+    carrier.conceal([0xff].as_slice(), [0; 16].as_slice())?;
+
+    let report = carrier.report().unwrap();
+    assert_eq!(report.touched_bytes, 8);
+    assert_eq!(report.last_byte, Some(7));
+    assert_eq!(report.plane_changes.len(), 16);
+    assert_eq!(report.plane_changes[0], 4);
+    assert_eq!(report.plane_changes[8], 4);
+    assert_eq!(report.utilization(), 0.5);
+
+    // The pattern is queried once per sample, and only up to the data unless reporting.
+    for is_reporting in [false, true] {
+        let mut calls = 0;
+        let pattern = |_| {
+            calls += 1;
+            Some(0b1)
+        };
+
+        let mut carrier = binary::Carrier::new(pattern, Vec::new());
+        if is_reporting {
+            carrier = carrier.reporting();
+        }
+        carrier.conceal([0xff].as_slice(), [0; 16].as_slice())?;
+
+        assert_eq!(carrier.report().is_some(), is_reporting);
+        drop(carrier);
+
+        assert_eq!(calls, if is_reporting { 16 } else { 8 });
+    }
+
     Ok(())
 }
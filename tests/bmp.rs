@@ -0,0 +1,109 @@
+use asbs::{binary, formats::bmp, Conceal, Error, Reveal};
+
+fn bmp(width: u32, height: i32, bits_per_pixel: u16, pixels: &[u8]) -> Vec<u8> {
+    let mut bytes = b"BM".to_vec();
+    bytes.extend((14 + 40 + pixels.len() as u32).to_le_bytes());
+    bytes.extend([0; 4]);
+    bytes.extend(54u32.to_le_bytes());
+
+    bytes.extend(40u32.to_le_bytes());
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+    bytes.extend(1u16.to_le_bytes());
+    bytes.extend(bits_per_pixel.to_le_bytes());
+    bytes.extend(0u32.to_le_bytes());
+    bytes.extend((pixels.len() as u32).to_le_bytes());
+    bytes.extend([0; 16]);
+
+    bytes.extend(pixels);
+
+    bytes
+}
+
+#[test]
+fn it_conceals_and_reveals_in_bmp() -> Result<(), Error> {
+    let payload = b"a very secret message";
+
+    // Rows of 45 bytes are padded with 3 bytes to a stride of 48.
+    let pixels: Vec<u8> = (0..48 * 20u32)
+        .map(|i| {
+            if i % 48 < 45 {
+                (i * 31 % 251) as u8
+            } else {
+                0xaa
+            }
+        })
+        .collect();
+    let cover = bmp(15, -20, 24, &pixels);
+
+    let mut image = bmp::Image::decode(cover.as_slice())?;
+    assert_eq!(
+        (15, 20, 3),
+        (image.width(), image.height(), image.channels())
+    );
+    assert_eq!(45 * 20, image.samples().len());
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+        .conceal(payload.as_slice(), image.samples().as_slice())?;
+
+    image.set_samples(&package);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    assert_eq!(cover[..54], encoded[..54]);
+    for (index, (&byte, &original)) in encoded[54..].iter().zip(&pixels).enumerate() {
+        if index % 48 < 45 {
+            assert_eq!(original & !0b1, byte & !0b1);
+        } else {
+            assert_eq!(original, byte);
+        }
+    }
+
+    let mut revealed_payload = Vec::new();
+    let image = bmp::Image::decode(encoded.as_slice())?;
+    binary::Package::with_header(|_| Some(0b1), image.samples().as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_alpha_in_bmp() -> Result<(), Error> {
+    let mut image = bmp::Image::decode(bmp(2, 2, 32, &[0; 16]).as_slice())?.skip_alpha();
+
+    assert_eq!(12, image.samples().len());
+
+    image.set_samples(&[0xff; 12]);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    assert_eq!(encoded[54..62], [0xff, 0xff, 0xff, 0, 0xff, 0xff, 0xff, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_bmp() {
+    assert!(matches!(
+        bmp::Image::decode(b"BM".as_slice()),
+        Err(Error::Format(_)),
+    ));
+
+    let mut compressed = bmp(2, 2, 32, &[0; 16]);
+    compressed[30] = 1;
+
+    assert!(matches!(
+        bmp::Image::decode(compressed.as_slice()),
+        Err(Error::Format(_)),
+    ));
+
+    assert!(matches!(
+        bmp::Image::decode(bmp(4, 4, 24, &[0; 16]).as_slice()),
+        Err(Error::Format(_)),
+    ));
+}
@@ -0,0 +1,180 @@
+#![cfg(feature = "cli")]
+
+use std::{
+    fs,
+    io::{self, Write},
+    path::PathBuf,
+    process::{Command, Output, Stdio},
+};
+
+fn asbs(args: &[&str], stdin: &[u8]) -> io::Result<Output> {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_asbs"))
+        .args(args)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+
+    child.stdin.take().unwrap().write_all(stdin)?;
+    child.wait_with_output()
+}
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("asbs-{}-{name}", std::process::id()))
+}
+
+#[test]
+fn it_conceals_and_reveals_through_stdio() -> io::Result<()> {
+    let payload = b"a very secret message";
+
+    let package = asbs(
+        &[
+            "conceal",
+            "--passphrase",
+            "correct horse battery staple",
+            "--density",
+            "3",
+            "--fec",
+            "hamming",
+            "--checksum",
+            "crc32",
+            "--cover",
+            "tests/resources/cover",
+        ],
+        payload,
+    )?;
+
+    assert!(package.status.success());
+
+    let path = temp_path("stdio-package");
+    fs::write(&path, &package.stdout)?;
+
+    let revealed = asbs(
+        &[
+            "reveal",
+            "--passphrase",
+            "correct horse battery staple",
+            "--density",
+            "3",
+            "--input",
+            path.to_str().unwrap(),
+        ],
+        &[],
+    )?;
+
+    let header = asbs(
+        &[
+            "analyze",
+            "--passphrase",
+            "correct horse battery staple",
+            "--density",
+            "3",
+        ],
+        &package.stdout,
+    )?;
+
+    fs::remove_file(&path)?;
+
+    assert!(revealed.status.success());
+    assert_eq!(*payload, *revealed.stdout);
+
+    assert!(header.status.success());
+    assert!(String::from_utf8_lossy(&header.stdout).contains("length: 21"));
+
+    Ok(())
+}
+
+#[test]
+fn it_reports_errors_with_exit_codes() -> io::Result<()> {
+    let capacity = asbs(&["capacity", "--mask", "0b1", "tests/resources/cover"], &[])?;
+    assert!(capacity.status.success());
+
+    let len: usize = String::from_utf8_lossy(&capacity.stdout)
+        .trim()
+        .parse()
+        .unwrap();
+
+    let conceal = asbs(
+        &[
+            "conceal",
+            "--mask",
+            "0b1",
+            "--cover",
+            "tests/resources/cover",
+        ],
+        &vec![0; len + 1],
+    )?;
+    assert_eq!(Some(3), conceal.status.code());
+
+    let analyze = asbs(&["analyze", "--mask", "0b1", "tests/resources/cover"], &[])?;
+    assert_eq!(Some(5), analyze.status.code());
+
+    let reveal = asbs(&["reveal", "--mask", "0b1,0b10"], &[])?;
+    assert_eq!(Some(5), reveal.status.code());
+
+    let analyze = asbs(&["analyze", "--seed", "seed", "tests/resources/cover"], &[])?;
+    assert_eq!(Some(2), analyze.status.code());
+
+    let usage = asbs(&["reveal"], &[])?;
+    assert_eq!(Some(2), usage.status.code());
+
+    Ok(())
+}
+
+#[test]
+fn it_estimates_embedding_without_a_key() -> io::Result<()> {
+    let analyze = asbs(&["analyze", "tests/resources/cover"], &[])?;
+    let output = String::from_utf8_lossy(&analyze.stdout);
+
+    assert!(analyze.status.success());
+    assert!(output.contains("chi-square: "));
+    assert!(output.contains("rs: "));
+    assert!(output.contains("sample pairs: "));
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_the_output_on_failure() -> io::Result<()> {
+    let path = temp_path("failed-output");
+    fs::write(&path, b"previous contents")?;
+
+    let conceal = asbs(
+        &[
+            "conceal",
+            "--mask",
+            "0b1",
+            "--cover",
+            "tests/resources/cover",
+            "--output",
+            path.to_str().unwrap(),
+        ],
+        &vec![0; 1 << 20],
+    )?;
+
+    let contents = fs::read(&path)?;
+    fs::remove_file(&path)?;
+
+    assert_eq!(Some(3), conceal.status.code());
+    assert_eq!(b"previous contents", contents.as_slice());
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_density_with_masks() -> io::Result<()> {
+    let capacity = asbs(
+        &[
+            "capacity",
+            "--mask",
+            "0b1",
+            "--density",
+            "2",
+            "tests/resources/cover",
+        ],
+        &[],
+    )?;
+    assert_eq!(Some(2), capacity.status.code());
+
+    Ok(())
+}
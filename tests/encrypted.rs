@@ -0,0 +1,51 @@
+#![cfg(feature = "encrypt")]
+
+use asbs::{binary, Conceal, Error, Reveal};
+use std::{fs::File, io};
+
+#[test]
+fn it_encrypts_and_decrypts() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let payload = b"a very very secret message";
+
+    let mut package = Vec::new();
+
+    binary::EncryptedCarrier::new("a password", pattern, &mut package)
+        .conceal(payload.as_slice(), File::open("tests/resources/cover")?)?;
+
+    let mut revealed_payload = Vec::new();
+
+    binary::EncryptedPackage::new("a password", pattern, package.as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let header = binary::Package::with_header(pattern, package.as_slice()).detect()?;
+    assert!(header.is_some_and(|header| header.encrypted));
+
+    Ok(())
+}
+
+#[test]
+fn it_fails_to_authenticate_with_wrong_password() -> io::Result<()> {
+    let pattern = |_| Some(0b111);
+
+    let mut package = Vec::new();
+
+    binary::EncryptedCarrier::new("a password", pattern, &mut package).conceal(
+        b"a very very secret message".as_slice(),
+        File::open("tests/resources/cover")?,
+    )?;
+
+    let mut revealed_payload = Vec::new();
+
+    assert!(matches!(
+        binary::EncryptedPackage::new("another password", pattern, package.as_slice())
+            .reveal(&mut revealed_payload),
+        Err(Error::Authentication(_)),
+    ));
+    assert!(revealed_payload.is_empty());
+
+    Ok(())
+}
@@ -0,0 +1,249 @@
+use asbs::{binary, formats::gif, Conceal, Error, Reveal};
+
+/// Compresses the indices with LZW without actually compressing them, by clearing the
+/// dictionary before the code size grows.
+fn lzw(indices: &[u8]) -> Vec<u8> {
+    let (mut data, mut bits, mut len) = (Vec::new(), 0u32, 0);
+    let mut write = |code: u32| {
+        bits |= code << len;
+        len += 9;
+        while len >= 8 {
+            data.push(bits as u8);
+            bits >>= 8;
+            len -= 8;
+        }
+    };
+
+    for chunk in indices.chunks(254) {
+        write(256);
+        chunk.iter().for_each(|&index| write(index.into()));
+    }
+    write(257);
+
+    if len > 0 {
+        data.push(bits as u8);
+    }
+
+    data
+}
+
+fn frame(width: u16, height: u16, palette: Option<&[[u8; 3]]>, indices: &[u8]) -> Vec<u8> {
+    let mut bytes = vec![0x2c, 0, 0, 0, 0];
+    bytes.extend(width.to_le_bytes());
+    bytes.extend(height.to_le_bytes());
+
+    match palette {
+        Some(palette) => {
+            bytes.push(0x87);
+            bytes.extend(palette.as_flattened());
+        }
+        None => bytes.push(0),
+    }
+
+    bytes.push(8);
+    for chunk in lzw(indices).chunks(255) {
+        bytes.push(chunk.len() as u8);
+        bytes.extend(chunk);
+    }
+    bytes.push(0);
+
+    bytes
+}
+
+fn palette(seed: u32) -> Vec<[u8; 3]> {
+    (0..256u32)
+        .map(|i| {
+            let value = i.wrapping_mul(2_654_435_761).wrapping_add(seed);
+            [(value >> 8) as u8, (value >> 16) as u8, (value >> 24) as u8]
+        })
+        .collect()
+}
+
+/// Returns an animation of two 16x16 frames, the second of which has a local palette and
+/// a transparent index of 5.
+fn gif() -> Vec<u8> {
+    let mut bytes = b"GIF89a".to_vec();
+    bytes.extend(16u16.to_le_bytes());
+    bytes.extend(16u16.to_le_bytes());
+    bytes.extend([0xf7, 3, 0]);
+    bytes.extend(palette(0).as_flattened());
+
+    bytes.extend(b"\x21\xfe\x07comment\x00");
+
+    let indices: Vec<u8> = (0..256u32).map(|i| (i * 7 % 256) as u8).collect();
+    bytes.extend(frame(16, 16, None, &indices));
+
+    bytes.extend([0x21, 0xf9, 4, 1, 10, 0, 5, 0]);
+
+    let indices: Vec<u8> = (0..256u32).map(|i| (i * 13 % 256) as u8).collect();
+    bytes.extend(frame(16, 16, Some(&palette(1)), &indices));
+
+    bytes.push(0x3b);
+
+    bytes
+}
+
+/// Returns the colors of the pixels of every frame of the encoded image.
+fn pixels(image: &gif::Image) -> Vec<Option<[u8; 3]>> {
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded).unwrap();
+
+    let decoded = gif::Image::decode(encoded.as_slice()).unwrap();
+    (0..decoded.frame_count())
+        .flat_map(|frame| decoded.colors(frame))
+        .collect()
+}
+
+#[test]
+fn it_round_trips_gif() -> Result<(), Error> {
+    let cover = gif();
+
+    let image = gif::Image::decode(cover.as_slice())?;
+    assert_eq!(
+        (16, 16, 2),
+        (image.width(), image.height(), image.frame_count())
+    );
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let decoded = gif::Image::decode(encoded.as_slice())?;
+    assert_eq!(image.samples(), decoded.samples());
+    assert_eq!(image.palette_samples(), decoded.palette_samples());
+
+    // The transparent index 5 and its neighbor 4 are excluded from the cover.
+    assert_eq!(256, image.frame_samples(0).len());
+    assert_eq!(254, image.frame_samples(1).len());
+
+    Ok(())
+}
+
+#[test]
+fn it_round_trips_large_gif_frames() -> Result<(), Error> {
+    // Enough pixels to fill the LZW dictionary several times over.
+    let indices: Vec<u8> = (0..200 * 200u32)
+        .map(|i| (i.wrapping_mul(2_654_435_761) >> 29) as u8 | (i / 7 % 2 * 8) as u8)
+        .collect();
+
+    let mut cover = b"GIF89a\xc8\x00\xc8\x00\x00\x00\x00".to_vec();
+    cover.extend(frame(200, 200, Some(&palette(2)), &indices));
+    cover.push(0x3b);
+
+    let image = gif::Image::decode(cover.as_slice())?;
+    assert_eq!(indices, image.frame_samples(0));
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+    assert!(encoded.len() < cover.len());
+
+    let decoded = gif::Image::decode(encoded.as_slice())?;
+    assert_eq!(indices, decoded.frame_samples(0));
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_in_gif_indices() -> Result<(), Error> {
+    let cover = gif();
+    let payload = b"a very secret message";
+
+    let original = gif::Image::decode(cover.as_slice())?;
+
+    let mut image = original.clone().sort_palettes();
+    assert_eq!(pixels(&original), pixels(&image));
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+        .conceal(payload.as_slice(), image.samples().as_slice())?;
+
+    image.set_samples(&package);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let decoded = gif::Image::decode(encoded.as_slice())?;
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(|_| Some(0b1), decoded.samples().as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let transparent = |pixels: Vec<Option<[u8; 3]>>| -> Vec<bool> {
+        pixels.iter().map(Option::is_none).collect()
+    };
+    assert_eq!(
+        transparent(pixels(&original)),
+        transparent(pixels(&decoded))
+    );
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_in_gif_palette_order() -> Result<(), Error> {
+    let cover = gif();
+    let payload = b"a very very secret message";
+
+    let mut image = gif::Image::decode(cover.as_slice())?;
+    let original = pixels(&image);
+
+    let samples = image.palette_samples();
+    assert_eq!(2 * 210, samples.len());
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0xff), &mut package)
+        .conceal(payload.as_slice(), samples.as_slice())?;
+
+    image.set_palette_samples(&package);
+    assert_eq!(package, image.palette_samples());
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let decoded = gif::Image::decode(encoded.as_slice())?;
+    assert_eq!(original, pixels(&decoded));
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(|_| Some(0xff), decoded.palette_samples().as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_gif() {
+    let mut cover = gif();
+
+    assert!(matches!(
+        gif::Image::decode(&cover[1..]),
+        Err(Error::Format(_))
+    ));
+
+    cover.truncate(cover.len() - 100);
+
+    assert!(matches!(
+        gif::Image::decode(cover.as_slice()),
+        Err(Error::Format(_))
+    ));
+}
+
+#[test]
+fn it_stops_decoding_gif_frames_at_their_size() -> Result<(), Error> {
+    let mut bytes = b"GIF89a".to_vec();
+    bytes.extend(4u16.to_le_bytes());
+    bytes.extend(4u16.to_le_bytes());
+    bytes.extend([0xf7, 0, 0]);
+    bytes.extend(palette(0).as_flattened());
+
+    // The image data holds far more indices than the frame has pixels.
+    bytes.extend(frame(4, 4, None, &[1; 4096]));
+    bytes.push(0x3b);
+
+    let image = gif::Image::decode(bytes.as_slice())?;
+    assert_eq!(image.colors(0).len(), 16);
+
+    Ok(())
+}
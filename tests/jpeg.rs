@@ -0,0 +1,115 @@
+use asbs::{binary, formats::jpeg, Conceal, Error, Reveal};
+use std::fs;
+
+#[test]
+fn it_encodes_unchanged_jpeg_exactly() -> Result<(), Error> {
+    let cover = fs::read("tests/resources/cover.jpg")?;
+
+    let image = jpeg::Image::decode(cover.as_slice())?;
+    assert_eq!(
+        (60, 44, 3),
+        (image.width(), image.height(), image.channels())
+    );
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    assert_eq!(cover, encoded);
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_in_jpeg() -> Result<(), Error> {
+    let cover = fs::read("tests/resources/cover.jpg")?;
+    let payload = b"a secret";
+
+    let mut image = jpeg::Image::decode(cover.as_slice())?;
+    let samples = image.samples();
+    assert!(samples.iter().all(|&sample| sample > 1));
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+        .conceal(payload.as_slice(), samples.as_slice())?;
+
+    image.set_samples(&package);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+    assert_ne!(cover, encoded);
+
+    let decoded = jpeg::Image::decode(encoded.as_slice())?;
+    assert_eq!(samples.len(), decoded.samples().len());
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(|_| Some(0b1), decoded.samples().as_slice())
+        .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    for (&sample, &original) in decoded.samples().iter().zip(&samples) {
+        assert_eq!(original & !1, sample & !1);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_jpeg_components() -> Result<(), Error> {
+    let cover = fs::read("tests/resources/cover.jpg")?;
+
+    let image = jpeg::Image::decode(cover.as_slice())?;
+    let luma = image.channel_samples(0);
+
+    let mut image = image.skip_channel(1).skip_channel(2);
+    assert_eq!(luma, image.samples());
+
+    let chroma = image.channel_samples(1);
+
+    image.set_samples(&vec![0; luma.len()]);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let decoded = jpeg::Image::decode(encoded.as_slice())?;
+    assert!(decoded
+        .channel_samples(0)
+        .iter()
+        .all(|&sample| sample & 1 == 0));
+    assert_eq!(chroma, decoded.channel_samples(1));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_unsupported_jpeg() -> Result<(), Error> {
+    let mut cover = fs::read("tests/resources/cover.jpg")?;
+
+    assert!(matches!(
+        jpeg::Image::decode(&cover[2..]),
+        Err(Error::Format(_))
+    ));
+
+    // Turn the baseline frame into a progressive one.
+    let frame = cover
+        .windows(2)
+        .position(|marker| marker == [0xff, 0xc0])
+        .unwrap();
+    cover[frame + 1] = 0xc2;
+
+    assert!(matches!(
+        jpeg::Image::decode(cover.as_slice()),
+        Err(Error::Format(_))
+    ));
+
+    // Declare dimensions far larger than the scan data can cover.
+    cover[frame + 1] = 0xc0;
+    cover[frame + 5..frame + 9].fill(0xff);
+
+    assert!(matches!(
+        jpeg::Image::decode(cover.as_slice()),
+        Err(Error::Format(_))
+    ));
+
+    Ok(())
+}
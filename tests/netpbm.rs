@@ -0,0 +1,85 @@
+use asbs::{binary, formats::netpbm, Conceal, Error, Reveal};
+
+#[test]
+fn it_conceals_and_reveals_in_netpbm() -> Result<(), Error> {
+    let payload = b"a very secret message";
+
+    let headers: [(&[u8], usize); 4] = [
+        (b"P5\n# a comment\n40 30\n255\n", 1),
+        (b"P6 20 20 255\n", 1),
+        (b"P6\n20 10\n65535\n", 2),
+        (
+            b"P7\nWIDTH 20\nHEIGHT 15\nDEPTH 4\nMAXVAL 255\nTUPLTYPE RGB_ALPHA\nENDHDR\n",
+            1,
+        ),
+    ];
+
+    for (header, sample_len) in headers {
+        let raster: Vec<u8> = (0..1200u32).map(|i| (i * 31 % 251) as u8).collect();
+        let cover = [header, &raster].concat();
+
+        let mut image = netpbm::Image::decode(cover.as_slice())?;
+        assert_eq!(1200 / sample_len, image.samples().len());
+
+        let mut package = Vec::new();
+        binary::Carrier::with_header(payload.len(), |_| Some(0b11), &mut package)
+            .conceal(payload.as_slice(), image.samples().as_slice())?;
+
+        image.set_samples(&package);
+
+        let mut encoded = Vec::new();
+        image.encode(&mut encoded)?;
+
+        assert_eq!(*header, encoded[..header.len()]);
+
+        for (index, (&byte, &original)) in encoded[header.len()..].iter().zip(&raster).enumerate() {
+            if index % sample_len == sample_len - 1 {
+                assert_eq!(original & !0b11, byte & !0b11);
+            } else {
+                assert_eq!(original, byte);
+            }
+        }
+
+        let mut revealed_payload = Vec::new();
+        let image = netpbm::Image::decode(encoded.as_slice())?;
+        binary::Package::with_header(|_| Some(0b11), image.samples().as_slice())
+            .reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_alpha_in_netpbm() -> Result<(), Error> {
+    let header = b"P7\nWIDTH 2\nHEIGHT 1\nDEPTH 2\nMAXVAL 255\nTUPLTYPE GRAYSCALE_ALPHA\nENDHDR\n";
+    let cover = [header.as_slice(), &[0; 4]].concat();
+
+    let mut image = netpbm::Image::decode(cover.as_slice())?.skip_alpha();
+    assert_eq!(2, image.samples().len());
+
+    image.set_samples(&[0xff; 2]);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    assert_eq!(encoded[header.len()..], [0xff, 0, 0xff, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_netpbm() {
+    for image in [
+        b"P3\n1 1\n255\n0 0 0\n".as_slice(),
+        b"P6\n2 2\n255\n\0\0\0",
+        b"P6\n1 1\n0\n\0\0\0",
+        b"P7\nWIDTH 1\nHEIGHT 1\nENDHDR\n\0",
+    ] {
+        assert!(matches!(
+            netpbm::Image::decode(image),
+            Err(Error::Format(_)),
+        ));
+    }
+}
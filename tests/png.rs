@@ -0,0 +1,136 @@
+#![cfg(feature = "png")]
+
+use asbs::{binary, formats::png, Conceal, Reveal};
+
+fn encode(color_type: ::png::ColorType, bit_depth: ::png::BitDepth, data: &[u8]) -> Vec<u8> {
+    let mut image = Vec::new();
+
+    let mut encoder = ::png::Encoder::new(&mut image, 16, 16);
+    encoder.set_color(color_type);
+    encoder.set_depth(bit_depth);
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(data).unwrap();
+    writer.finish().unwrap();
+
+    image
+}
+
+#[test]
+fn it_conceals_and_reveals_in_png() -> Result<(), asbs::Error> {
+    let pixels: Vec<u8> = (0..16 * 16 * 4).map(|i| (i * 7) as u8).collect();
+    let cover = encode(::png::ColorType::Rgba, ::png::BitDepth::Eight, &pixels);
+
+    let payload = b"a very secret message";
+
+    let mut image = png::Image::decode(cover.as_slice())?.skip_alpha();
+    assert_eq!(16 * 16 * 3, image.samples().len());
+
+    let mut package = Vec::new();
+    binary::Carrier::with_header(payload.len(), |_| Some(0b1), &mut package)
+        .conceal(payload.as_slice(), image.samples().as_slice())?;
+
+    image.set_samples(&package);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let decoded = png::Image::decode(encoded.as_slice())?;
+
+    let mut revealed_payload = Vec::new();
+    binary::Package::with_header(
+        |_| Some(0b1),
+        decoded.clone().skip_alpha().samples().as_slice(),
+    )
+    .reveal(&mut revealed_payload)?;
+
+    assert_eq!(*payload, *revealed_payload);
+
+    let samples = decoded.samples();
+    for (index, (&sample, &pixel)) in samples.iter().zip(&pixels).enumerate() {
+        if index % 4 == 3 {
+            assert_eq!(pixel, sample);
+        } else {
+            assert_eq!(pixel & !1, sample & !1);
+        }
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_in_low_bytes_of_wide_samples() -> Result<(), asbs::Error> {
+    let pixels: Vec<u8> = (0..16 * 16 * 2).map(|i| (i * 13) as u8).collect();
+    let cover = encode(
+        ::png::ColorType::Grayscale,
+        ::png::BitDepth::Sixteen,
+        &pixels,
+    );
+
+    let mut image = png::Image::decode(cover.as_slice())?;
+    assert_eq!(16 * 16, image.samples().len());
+
+    image.set_samples(&[0xff; 16 * 16]);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let mut decoder = ::png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+    let mut data = vec![0; decoder.output_buffer_size()];
+    decoder.next_frame(&mut data).unwrap();
+
+    for (index, (&byte, &pixel)) in data.iter().zip(&pixels).enumerate() {
+        assert_eq!(if index % 2 == 0 { pixel } else { 0xff }, byte);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_png() {
+    assert!(matches!(
+        png::Image::decode(b"not a png".as_slice()),
+        Err(asbs::Error::Format(_)),
+    ));
+}
+
+#[test]
+fn it_preserves_png_format_and_chunks() -> Result<(), asbs::Error> {
+    let mut cover = Vec::new();
+
+    let mut encoder = ::png::Encoder::new(&mut cover, 16, 16);
+    encoder.set_color(::png::ColorType::Indexed);
+    encoder.set_depth(::png::BitDepth::Two);
+    encoder.set_palette(vec![0, 0, 0, 85, 85, 85, 170, 170, 170, 255, 255, 255]);
+    encoder.set_source_gamma(::png::ScaledFloat::new(0.5));
+    encoder
+        .add_text_chunk("Title".into(), "A gradient".into())
+        .unwrap();
+
+    let mut writer = encoder.write_header().unwrap();
+    writer.write_image_data(&[0b0001_1011; 16 * 4]).unwrap();
+    writer.finish().unwrap();
+
+    let mut image = png::Image::decode(cover.as_slice())?;
+    assert_eq!(image.samples()[..4], [0, 1, 2, 3]);
+
+    image.set_samples(&[1; 4]);
+
+    let mut encoded = Vec::new();
+    image.encode(&mut encoded)?;
+
+    let mut reader = ::png::Decoder::new(encoded.as_slice()).read_info().unwrap();
+    let info = reader.info();
+
+    assert_eq!(info.color_type, ::png::ColorType::Indexed);
+    assert_eq!(info.bit_depth, ::png::BitDepth::Two);
+    assert_eq!(info.source_gamma, Some(::png::ScaledFloat::new(0.5)));
+    assert_eq!(info.uncompressed_latin1_text[0].text, "A gradient");
+
+    let mut data = vec![0; reader.output_buffer_size()];
+    reader.next_frame(&mut data).unwrap();
+
+    assert_eq!(data[..2], [0b0101_0101, 0b0001_1011]);
+
+    Ok(())
+}
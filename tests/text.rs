@@ -0,0 +1,324 @@
+use asbs::{
+    text::{
+        homoglyph::{self, Script},
+        whitespace,
+        zero_width::{self, Placement},
+    },
+    Conceal, Error, Reveal,
+};
+
+const COVER: &str = "Meet me at the old bridge, café at noon 👩‍💻 🇩🇪";
+
+fn conceal(placement: Placement, message: &[u8], cover: &str) -> Result<String, Error> {
+    let mut package = Vec::new();
+    zero_width::Carrier::new(&mut package)
+        .placement(placement)
+        .conceal(message, cover.as_bytes())?;
+
+    Ok(String::from_utf8(package).unwrap())
+}
+
+fn reveal(package: &str) -> Result<Vec<u8>, Error> {
+    let mut message = Vec::new();
+    zero_width::Package::new(package.as_bytes()).reveal(&mut message)?;
+
+    Ok(message)
+}
+
+#[test]
+fn it_conceals_and_reveals_with_zero_width_characters() -> Result<(), Error> {
+    let message = b"a secret message, longer than the number of words".as_slice();
+
+    for placement in [
+        Placement::Spread,
+        Placement::Words,
+        Placement::Start,
+        Placement::End,
+    ] {
+        let package = conceal(placement, message, COVER)?;
+
+        assert!(zero_width::detect(&package));
+        assert_eq!(zero_width::strip(&package), COVER);
+        assert_eq!(reveal(&package)?, message);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_keeps_graphemes_intact() -> Result<(), Error> {
+    let package = conceal(Placement::Spread, &[0xff; 64], COVER)?;
+
+    for grapheme in ["é", "👩\u{200d}💻", "🇩🇪"] {
+        assert!(package.contains(grapheme));
+    }
+    assert!(!zero_width::detect(COVER));
+
+    Ok(())
+}
+
+#[test]
+fn it_replaces_zero_width_characters_in_cover() -> Result<(), Error> {
+    let cover = conceal(Placement::Spread, b"old", COVER)?;
+    let package = conceal(Placement::End, b"new", &cover)?;
+
+    assert_eq!(reveal(&package)?, b"new");
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_covers_without_positions() {
+    assert!(matches!(
+        conceal(Placement::Words, b"message", "word"),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 56,
+            available_bits: 0,
+        })
+    ));
+}
+
+#[test]
+fn it_rejects_invalid_zero_width_text() {
+    let mut message = Vec::new();
+
+    assert!(matches!(
+        zero_width::Package::new(b"\xff\xfe".as_slice()).reveal(&mut message),
+        Err(Error::Format(_))
+    ));
+    assert!(matches!(
+        reveal("a\u{200b}b\u{200c}c"),
+        Err(Error::Format(_))
+    ));
+}
+
+const SOURCE: &str = "fn main() {\r\n    let greeting = \"Hello,   world!\";\r\n\r\n    println!(\"{greeting}\");\r\n}\r\n";
+
+fn prose() -> String {
+    "The quick brown fox jumps over the lazy dog.\n".repeat(8)
+}
+
+#[test]
+fn it_conceals_and_reveals_with_whitespace() -> Result<(), Error> {
+    for bits_per_line in [8, 13, 32] {
+        let capacity = whitespace::Carrier::new(Vec::new())
+            .bits_per_line(bits_per_line)
+            .capacity_for(SOURCE);
+        let message = &b"a secret message"[..capacity];
+
+        let mut package = Vec::new();
+        whitespace::Carrier::new(&mut package)
+            .bits_per_line(bits_per_line)
+            .conceal(message, SOURCE.as_bytes())?;
+        let package = String::from_utf8(package).unwrap();
+
+        assert!(whitespace::detect(&package));
+        assert_eq!(whitespace::strip(&package), SOURCE);
+
+        let mut revealed = Vec::new();
+        whitespace::Package::new(package.as_bytes()).reveal(&mut revealed)?;
+
+        assert_eq!(revealed, message);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_conceals_and_reveals_between_words() -> Result<(), Error> {
+    let cover = prose();
+    let message = b"secret words".as_slice();
+
+    let mut carrier = whitespace::Carrier::new(Vec::new());
+    assert_eq!(carrier.capacity_for(&cover), 4);
+    assert!(carrier.conceal(message, cover.as_bytes()).is_err());
+
+    let carrier = whitespace::Carrier::new(Vec::new()).inter_word();
+    assert_eq!(carrier.capacity_for(&cover), 12);
+
+    let mut package = Vec::new();
+    whitespace::Carrier::new(&mut package)
+        .inter_word()
+        .conceal(message, cover.as_bytes())?;
+
+    let package = String::from_utf8(package).unwrap();
+    assert!(package.contains("  "));
+    assert_eq!(whitespace::strip(&package).replace("  ", " "), cover);
+
+    let mut revealed = Vec::new();
+    whitespace::Package::new(package.as_bytes())
+        .inter_word()
+        .reveal(&mut revealed)?;
+
+    assert_eq!(revealed, message);
+
+    Ok(())
+}
+
+#[test]
+fn it_detects_stripped_lines() -> Result<(), Error> {
+    let cover = prose();
+
+    let mut package = Vec::new();
+    whitespace::Carrier::new(&mut package).conceal(b"hey".as_slice(), cover.as_bytes())?;
+    let package = String::from_utf8(package).unwrap();
+
+    let lines: Vec<&str> = package.lines().collect();
+    let edited: String = lines
+        .iter()
+        .enumerate()
+        .map(|(number, line)| match number {
+            2 | 4 => format!("{}\n", line.trim_end()),
+            _ => format!("{line}\n"),
+        })
+        .collect();
+
+    let Err(Error::Format(error)) = whitespace::Package::new(edited.as_bytes()).reveal(Vec::new())
+    else {
+        panic!("stripped lines were not detected");
+    };
+    let stripped = error.downcast_ref::<whitespace::StrippedLines>().unwrap();
+
+    assert_eq!(stripped.lines(), [2, 4]);
+
+    let truncated = whitespace::strip(&package[..package.find("dog.").unwrap()]);
+    assert!(!whitespace::detect(&truncated));
+    assert!(matches!(
+        whitespace::Package::new(truncated.as_bytes()).reveal(Vec::new()),
+        Err(Error::TruncatedLength)
+    ));
+
+    let truncated: String = lines[..6].iter().map(|line| format!("{line}\n")).collect();
+    assert!(matches!(
+        whitespace::Package::new(truncated.as_bytes()).reveal(Vec::new()),
+        Err(Error::TruncatedPayload {
+            expected: 3,
+            actual: 2
+        })
+    ));
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_covers_with_few_lines() {
+    assert!(matches!(
+        whitespace::Carrier::new(Vec::new()).conceal(b"message".as_slice(), prose().as_bytes()),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 88,
+            available_bits: 64,
+        })
+    ));
+}
+
+const ARTICLE: &str = "Researchers at the Institute presented a new approach to secure messaging \
+    - one that hides messages in plain sight. Their paper, published on Monday, explains how \
+    ordinary text can carry data; critics note the method is easy to detect.";
+
+fn substitute(scripts: &[Script], message: &[u8], cover: &str) -> Result<String, Error> {
+    let mut package = Vec::new();
+    homoglyph::Carrier::new(&mut package)
+        .scripts(scripts)
+        .conceal(message, cover.as_bytes())?;
+
+    Ok(String::from_utf8(package).unwrap())
+}
+
+#[test]
+fn it_conceals_and_reveals_with_homoglyphs() -> Result<(), Error> {
+    let message = b"meet at dawn".as_slice();
+    let package = substitute(&[Script::Cyrillic, Script::Greek], message, ARTICLE)?;
+
+    assert_ne!(package, ARTICLE);
+    assert_eq!(package.chars().count(), ARTICLE.chars().count());
+    assert!(package
+        .chars()
+        .any(|c| ('\u{400}'..='\u{4ff}').contains(&c)));
+
+    let mut revealed = Vec::new();
+    homoglyph::Package::new(package.as_bytes())
+        .scripts(&[Script::Greek, Script::Cyrillic])
+        .reveal(&mut revealed)?;
+
+    assert_eq!(revealed, message);
+
+    let cover = package;
+    let package = substitute(&[Script::Cyrillic, Script::Greek], b"new", &cover)?;
+
+    let mut revealed = Vec::new();
+    homoglyph::Package::new(package.as_bytes())
+        .scripts(&[Script::Cyrillic, Script::Greek])
+        .reveal(&mut revealed)?;
+
+    assert_eq!(revealed, b"new");
+
+    Ok(())
+}
+
+#[test]
+fn it_restricts_homoglyphs_to_scripts() -> Result<(), Error> {
+    let scripts = [Script::Latin, Script::Common];
+    let carrier = homoglyph::Carrier::new(Vec::new()).scripts(&scripts);
+    let capacity = carrier.capacity_for(ARTICLE);
+
+    assert_eq!(capacity, 3);
+    assert_eq!(
+        homoglyph::Carrier::new(Vec::new()).capacity_for(ARTICLE),
+        capacity
+    );
+
+    let carrier = homoglyph::Carrier::new(Vec::new()).scripts(&[Script::Cyrillic]);
+    assert!(carrier.capacity_for(ARTICLE) > capacity);
+
+    let package = substitute(&scripts, &[0xff; 3], ARTICLE)?;
+
+    assert!(package
+        .chars()
+        .all(|c| c.is_ascii() || ['\u{a0}', '\u{2010}', '\u{37e}'].contains(&c)));
+
+    let mut revealed = Vec::new();
+    homoglyph::Package::new(package.as_bytes())
+        .scripts(&scripts)
+        .reveal(&mut revealed)?;
+
+    assert_eq!(revealed, [0xff; 3]);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_covers_with_few_homoglyphs() {
+    assert!(matches!(
+        substitute(&[Script::Latin], b"message", ARTICLE),
+        Err(Error::InsufficientCapacity {
+            needed_bits: 72,
+            available_bits: 0,
+        })
+    ));
+
+    let mut revealed = Vec::new();
+    assert!(matches!(
+        homoglyph::Package::new(b"Oh no".as_slice()).reveal(&mut revealed),
+        Err(Error::TruncatedLength)
+    ));
+    assert!(matches!(
+        homoglyph::Package::new(ARTICLE.replace('a', "\u{430}").as_bytes())
+            .scripts(&[Script::Cyrillic])
+            .reveal(&mut revealed),
+        Err(Error::TruncatedPayload { .. })
+    ));
+}
+
+#[test]
+fn it_leaves_homoglyphs_after_the_message_unchanged() -> Result<(), Error> {
+    // Native Cyrillic letters, a no-break space, a hyphen, and the Kelvin sign
+    let cover =
+        format!("{ARTICLE} \u{41f}\u{440}\u{438}\u{432}\u{435}\u{442},\u{a0}0 \u{212a} \u{2010}");
+
+    let package = substitute(&[Script::Latin, Script::Common], b"a", &cover)?;
+    let (_, suffix) = cover.split_at(ARTICLE.len());
+
+    assert!(package.ends_with(suffix));
+
+    Ok(())
+}
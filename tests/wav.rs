@@ -0,0 +1,107 @@
+use asbs::{binary, formats::wav, Conceal, Error, Reveal};
+
+fn wav(channels: u16, bits_per_sample: u16, data: &[u8]) -> Vec<u8> {
+    let block_align = channels * bits_per_sample / 8;
+
+    let mut bytes = b"RIFF".to_vec();
+    bytes.extend((4 + 26 + 8 + data.len() as u32 + 8).to_le_bytes());
+    bytes.extend(b"WAVE");
+
+    bytes.extend(b"LIST");
+    bytes.extend(1u32.to_le_bytes());
+    bytes.extend([0x7f, 0]);
+
+    bytes.extend(b"fmt ");
+    bytes.extend(16u32.to_le_bytes());
+    bytes.extend(1u16.to_le_bytes());
+    bytes.extend(channels.to_le_bytes());
+    bytes.extend(8000u32.to_le_bytes());
+    bytes.extend((8000 * u32::from(block_align)).to_le_bytes());
+    bytes.extend(block_align.to_le_bytes());
+    bytes.extend(bits_per_sample.to_le_bytes());
+
+    bytes.extend(b"data");
+    bytes.extend((data.len() as u32).to_le_bytes());
+    bytes.extend(data);
+
+    bytes
+}
+
+#[test]
+fn it_conceals_and_reveals_in_wav() -> Result<(), Error> {
+    let payload = b"a very secret message";
+
+    for bits_per_sample in [8, 16, 24, 32] {
+        let data: Vec<u8> = (0..4800u32).map(|i| (i * 31 % 251) as u8).collect();
+        let cover = wav(2, bits_per_sample, &data);
+
+        let mut audio = wav::Audio::decode(cover.as_slice())?;
+        assert_eq!(2, audio.channels());
+        assert_eq!(8000, audio.sample_rate());
+        assert_eq!(4800 / (bits_per_sample as usize / 8), audio.samples().len());
+
+        let mut package = Vec::new();
+        binary::Carrier::with_header(payload.len(), |_| Some(0b11), &mut package)
+            .conceal(payload.as_slice(), audio.samples().as_slice())?;
+
+        audio.set_samples(&package);
+
+        let mut encoded = Vec::new();
+        audio.encode(&mut encoded)?;
+
+        let header_len = cover.len() - data.len();
+        assert_eq!(cover[..header_len], encoded[..header_len]);
+
+        let sample_len = bits_per_sample as usize / 8;
+        for (index, (&byte, &original)) in encoded[header_len..].iter().zip(&data).enumerate() {
+            if index % sample_len == 0 {
+                assert_eq!(original & !0b11, byte & !0b11);
+            } else {
+                assert_eq!(original, byte);
+            }
+        }
+
+        let mut revealed_payload = Vec::new();
+        let audio = wav::Audio::decode(encoded.as_slice())?;
+        binary::Package::with_header(|_| Some(0b11), audio.samples().as_slice())
+            .reveal(&mut revealed_payload)?;
+
+        assert_eq!(*payload, *revealed_payload);
+    }
+
+    Ok(())
+}
+
+#[test]
+fn it_skips_channels_in_wav() -> Result<(), Error> {
+    let data = [0u8; 64];
+    let mut audio = wav::Audio::decode(wav(2, 16, &data).as_slice())?.skip_channel(1);
+
+    assert_eq!(16, audio.samples().len());
+
+    audio.set_samples(&[0xff; 16]);
+
+    let mut encoded = Vec::new();
+    audio.encode(&mut encoded)?;
+
+    let samples = &encoded[encoded.len() - data.len()..];
+    assert_eq!(samples[..8], [0xff, 0, 0, 0, 0xff, 0, 0, 0]);
+
+    Ok(())
+}
+
+#[test]
+fn it_rejects_invalid_wav() {
+    assert!(matches!(
+        wav::Audio::decode(b"RIFF\0\0\0\0WAVE".as_slice()),
+        Err(Error::Format(_)),
+    ));
+
+    let mut float = wav(1, 32, &[0; 16]);
+    float[30] = 3;
+
+    assert!(matches!(
+        wav::Audio::decode(float.as_slice()),
+        Err(Error::Format(_)),
+    ));
+}